@@ -1,35 +1,257 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
-#[openbrush::implementation(Ownable, AccessControl, Upgradeable)]
+#[openbrush::implementation(Ownable, AccessControl, Upgradeable, Pausable)]
 #[openbrush::contract]
 pub mod lotto_contract {
     use ink::codegen::{EmitEvent, Env};
     use ink::prelude::vec::Vec;
     use lotto::traits::{
-        config, config::*, error::*, raffle, raffle::*, reward, reward::*, Number, RaffleId,
-        LOTTO_MANAGER_ROLE,
+        classic_raffle, classic_raffle::*, config, config::*, error::*, keno, keno::*, migration,
+        migration::*, quick_pick, quick_pick::*, raffle, raffle::*, referral, referral::*, relayer,
+        relayer::*, reward, reward::*, statistics, statistics::*, subscription, subscription::*,
+        syndicate, syndicate::*, timelock, timelock::*, LotteryId, Number, RaffleId, TicketIndex,
+        DEFAULT_LOTTERY_ID, LOTTO_MANAGER_ROLE, PAUSER_ROLE,
     };
     use openbrush::contracts::access_control::*;
     use openbrush::contracts::ownable::*;
+    use openbrush::contracts::pausable::*;
+    use openbrush::contracts::upgradeable::*;
     use openbrush::{modifiers, traits::Storage};
     use phat_rollup_anchor_ink::traits::{
         meta_transaction, meta_transaction::*, rollup_anchor, rollup_anchor::*,
     };
     use scale::Encode;
 
+    /// Messages exchanged with the offchain rollup
+    pub use lotto_protocol::{
+        LottoRequestMessage, LottoResponseMessage, ProtocolError, Request, Response,
+    };
+
     /// Event emitted when the participant is registered
     #[ink(event)]
     pub struct ParticipationRegistered {
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        #[ink(topic)]
+        participant: AccountId,
+        #[ink(topic)]
+        payer: AccountId,
+        numbers: Vec<Number>,
+        /// empty when the lottery has no bonus pool
+        bonus_numbers: Vec<Number>,
+    }
+
+    /// Event emitted when several participations are registered in one call
+    #[ink(event)]
+    pub struct ParticipationsRegistered {
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        #[ink(topic)]
+        participant: AccountId,
+        #[ink(topic)]
+        payer: AccountId,
+        /// main numbers of each ticket
+        tickets: Vec<Vec<Number>>,
+        /// bonus numbers of each ticket (empty when the lottery has no bonus pool)
+        bonus_numbers: Vec<Vec<Number>>,
+    }
+
+    /// Event emitted when tickets are bought for a classic raffle
+    #[ink(event)]
+    pub struct TicketsRegistered {
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        #[ink(topic)]
+        participant: AccountId,
+        #[ink(topic)]
+        payer: AccountId,
+        first_ticket: TicketIndex,
+        nb_tickets: u32,
+    }
+
+    /// Event emitted when a keno ticket is bought
+    #[ink(event)]
+    pub struct KenoTicketRegistered {
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        #[ink(topic)]
+        participant: AccountId,
+        ticket: TicketIndex,
+        numbers: Vec<Number>,
+        stake: Balance,
+    }
+
+    /// Event emitted when a participation is sent by a relayer on behalf of the participant
+    #[ink(event)]
+    pub struct ParticipationRelayed {
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        #[ink(topic)]
+        participant: AccountId,
+        #[ink(topic)]
+        relayer: AccountId,
+        fee: Balance,
+    }
+
+    /// Event emitted when a participant buys tickets with numbers drawn by the offchain rollup
+    #[ink(event)]
+    pub struct QuickPickRequested {
+        #[ink(topic)]
+        lottery_id: LotteryId,
         #[ink(topic)]
         raffle_id: RaffleId,
         #[ink(topic)]
         participant: AccountId,
+        first_slot: u32,
+        nb_slots: u32,
+    }
+
+    /// Event emitted when the quick pick tickets still waiting for the numbers are refunded
+    #[ink(event)]
+    pub struct QuickPicksRefunded {
+        #[ink(topic)]
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        nb_slots: u32,
+    }
+
+    /// Event emitted when an account is bound to its referrer
+    #[ink(event)]
+    pub struct ReferrerRegistered {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        referrer: AccountId,
+    }
+
+    /// Event emitted when the referrer earns a share of the tickets bought by the account
+    #[ink(event)]
+    pub struct ReferralRewarded {
+        #[ink(topic)]
+        referrer: AccountId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when a syndicate is created
+    #[ink(event)]
+    pub struct SyndicateCreated {
+        #[ink(topic)]
+        syndicate_id: SyndicateId,
+        #[ink(topic)]
+        manager: AccountId,
+        account: AccountId,
+    }
+
+    /// Event emitted when a member contributes to the pooled balance of a syndicate
+    #[ink(event)]
+    pub struct SyndicateContribution {
+        #[ink(topic)]
+        syndicate_id: SyndicateId,
+        #[ink(topic)]
+        member: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the manager of a syndicate allows an account to contribute
+    #[ink(event)]
+    pub struct SyndicateMemberAdded {
+        #[ink(topic)]
+        syndicate_id: SyndicateId,
+        #[ink(topic)]
+        member: AccountId,
+    }
+
+    /// Event emitted when a member withdraws his contribution not used yet to buy tickets
+    #[ink(event)]
+    pub struct SyndicateWithdrawal {
+        #[ink(topic)]
+        syndicate_id: SyndicateId,
+        #[ink(topic)]
+        member: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when a participant subscribes to play the same numbers for the next draws
+    #[ink(event)]
+    pub struct SubscriptionCreated {
+        #[ink(topic)]
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        subscriber: AccountId,
         numbers: Vec<Number>,
+        nb_draws: u32,
+        deposit: Balance,
+    }
+
+    /// Event emitted when a subscription is cancelled
+    #[ink(event)]
+    pub struct SubscriptionCancelled {
+        #[ink(topic)]
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        subscriber: AccountId,
+        refund: Balance,
+    }
+
+    /// Event emitted when a sensitive operation is scheduled
+    #[ink(event)]
+    pub struct OperationScheduled {
+        #[ink(topic)]
+        operation_id: OperationId,
+        operation: Operation,
+        ready_at: Timestamp,
+    }
+
+    /// Event emitted when a scheduled operation is cancelled
+    #[ink(event)]
+    pub struct OperationCancelled {
+        #[ink(topic)]
+        operation_id: OperationId,
+    }
+
+    /// Event emitted when a scheduled operation is executed
+    #[ink(event)]
+    pub struct OperationExecuted {
+        #[ink(topic)]
+        operation_id: OperationId,
+    }
+
+    /// Event emitted when the storage is migrated to a new layout
+    #[ink(event)]
+    pub struct StorageMigrated {
+        from_version: u16,
+        to_version: u16,
+    }
+
+    /// Event emitted when the contract is paused
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+        reason: u8,
+    }
+
+    /// Event emitted when the contract is unpaused
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+        reason: u8,
     }
 
     /// Event emitted when the raffle is started
     #[ink(event)]
     pub struct RaffleStarted {
+        #[ink(topic)]
+        lottery_id: LotteryId,
         #[ink(topic)]
         raffle_id: RaffleId,
     }
@@ -37,6 +259,8 @@ pub mod lotto_contract {
     /// Event emitted when the raffle is ended
     #[ink(event)]
     pub struct RaffleEnded {
+        #[ink(topic)]
+        lottery_id: LotteryId,
         #[ink(topic)]
         raffle_id: RaffleId,
     }
@@ -44,17 +268,65 @@ pub mod lotto_contract {
     /// Event emitted when the raffle result is received
     #[ink(event)]
     pub struct ResultReceived {
+        #[ink(topic)]
+        lottery_id: LotteryId,
         #[ink(topic)]
         raffle_id: RaffleId,
         numbers: Vec<Number>,
+        /// empty when the lottery has no bonus pool
+        bonus_numbers: Vec<Number>,
+    }
+
+    /// Event emitted when the winning tickets of a classic raffle are received
+    #[ink(event)]
+    pub struct WinningTicketsReceived {
+        #[ink(topic)]
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        tickets: Vec<TicketIndex>,
     }
 
     /// Event emitted when the winners are revealed
     #[ink(event)]
     pub struct WinnersRevealed {
+        #[ink(topic)]
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        winners: Vec<AccountId>,
+    }
+
+    /// Event emitted when the winners of a prize tier are paid
+    #[ink(event)]
+    pub struct PrizeTierWon {
+        #[ink(topic)]
+        lottery_id: LotteryId,
         #[ink(topic)]
         raffle_id: RaffleId,
+        nb_main_matches: u8,
+        nb_bonus_matches: u8,
         winners: Vec<AccountId>,
+        prize_per_winner: Balance,
+    }
+
+    /// Event emitted when a message from the offchain rollup is rejected
+    #[ink(event)]
+    pub struct MessageRejected {
+        #[ink(topic)]
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        reason: ContractError,
+    }
+
+    /// Event emitted when a request is not queued again after too many rejected responses
+    #[ink(event)]
+    pub struct RequestDropped {
+        #[ink(topic)]
+        lottery_id: LotteryId,
+        #[ink(topic)]
+        raffle_id: RaffleId,
     }
 
     /// Event emitted when a reward is pending
@@ -78,9 +350,18 @@ pub mod lotto_contract {
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum ContractError {
         AccessControlError(AccessControlError),
+        PausableError(PausableError),
         RaffleError(RaffleError),
         RollupAnchorError(RollupAnchorError),
         TransferError,
+        UpgradeFailed,
+        UnexpectedResponse,
+        /// the offchain rollup failed to answer the request (its error encoded in SCALE)
+        OffchainError(Vec<u8>),
+        /// no request dropped for the raffle
+        NoDroppedRequest,
+        /// the response was built with another version of the protocol
+        ProtocolError(ProtocolError),
     }
 
     /// convertor from AccessControlError to ContractError
@@ -90,6 +371,13 @@ pub mod lotto_contract {
         }
     }
 
+    /// convertor from PausableError to ContractError
+    impl From<PausableError> for ContractError {
+        fn from(error: PausableError) -> Self {
+            ContractError::PausableError(error)
+        }
+    }
+
     /// convertor from RaffleError to ContractError
     impl From<RaffleError> for ContractError {
         fn from(error: RaffleError) -> Self {
@@ -104,63 +392,56 @@ pub mod lotto_contract {
         }
     }
 
-    /// convertor from RaffleError to ContractError
+    /// convertor from ProtocolError to ContractError
+    impl From<ProtocolError> for ContractError {
+        fn from(error: ProtocolError) -> Self {
+            ContractError::ProtocolError(error)
+        }
+    }
+
+    /// convertor from ContractError to RollupAnchorError
     impl From<ContractError> for RollupAnchorError {
         fn from(error: ContractError) -> Self {
             ink::env::debug_println!("Error: {:?}", error);
-            RollupAnchorError::UnsupportedAction
+            match error {
+                ContractError::RollupAnchorError(error) => error,
+                _ => RollupAnchorError::UnsupportedAction,
+            }
+        }
+    }
+
+    /// convertor from ContractError to AccessControlError
+    impl From<ContractError> for AccessControlError {
+        fn from(error: ContractError) -> Self {
+            ink::env::debug_println!("Error: {:?}", error);
+            match error {
+                ContractError::AccessControlError(error) => error,
+                _ => AccessControlError::InvalidCaller,
+            }
+        }
+    }
+
+    /// convertor from ContractError to UpgradeableError
+    impl From<ContractError> for UpgradeableError {
+        fn from(error: ContractError) -> Self {
+            ink::env::debug_println!("Error: {:?}", error);
+            UpgradeableError::Custom(ink::prelude::format!("{:?}", error).into())
         }
     }
 
-    /// Message to request the lotto lotto_draw or the list of winners
-    /// message pushed in the queue by the Ink! smart contract and read by the offchain rollup
-    #[derive(Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub struct LottoRequestMessage {
-        /// raffle id
+    /// Participation signed by the participant and sent by a relayer (meta transaction)
+    #[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct MetaParticipation {
+        /// lottery for which the participant plays
+        pub lottery_id: LotteryId,
+        /// raffle for which the participant plays
         pub raffle_id: RaffleId,
-        /// request
-        pub request: Request,
-    }
-
-    #[derive(Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub enum Request {
-        /// request to lotto_draw the n number between min and max values
-        /// arg1: number of numbers for the lotto_draw
-        /// arg2:  smallest number for the lotto_draw
-        /// arg2:  biggest number for the lotto_draw
-        DrawNumbers(u8, Number, Number),
-        /// request to check if there is a winner for the given numbers
-        CheckWinners(Vec<Number>),
-    }
-
-    /// Message sent to provide the lotto lotto_draw or the list of winners
-    /// response pushed in the queue by the offchain rollup and read by the Ink! smart contract
-    #[derive(scale::Encode, scale::Decode)]
-    pub struct LottoResponseMessage {
-        /// initial request
-        pub request: LottoRequestMessage,
-        /// response
-        pub response: Response,
-    }
-
-    #[derive(Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub enum Response {
-        /// list of numbers
-        Numbers(Vec<Number>),
-        /// list of winners
-        Winners(Vec<AccountId>),
+        /// numbers chosen by the participant
+        pub numbers: Vec<Number>,
+        /// max amount the participant allows to be debited from his pending rewards
+        /// to pay the ticket and the relayer fee
+        pub allowance: Option<Balance>,
     }
 
     /// Contract storage
@@ -172,6 +453,8 @@ pub mod lotto_contract {
         #[storage_field]
         access: access_control::Data,
         #[storage_field]
+        pause: pausable::Data,
+        #[storage_field]
         rollup_anchor: rollup_anchor::Data,
         #[storage_field]
         meta_transaction: meta_transaction::Data,
@@ -181,189 +464,1868 @@ pub mod lotto_contract {
         lotto: raffle::Data,
         #[storage_field]
         reward: reward::Data,
+        #[storage_field]
+        relayer: relayer::Data,
+        #[storage_field]
+        subscription: subscription::Data,
+        #[storage_field]
+        quick_pick: quick_pick::Data,
+        #[storage_field]
+        syndicate: syndicate::Data,
+        #[storage_field]
+        referral: referral::Data,
+        #[storage_field]
+        timelock: timelock::Data,
+        #[storage_field]
+        migration: migration::Data,
+        #[storage_field]
+        statistics: statistics::Data,
+        #[storage_field]
+        classic_raffle: classic_raffle::Data,
+        #[storage_field]
+        keno: keno::Data,
     }
 
     impl RaffleConfig for Contract {}
     impl Raffle for Contract {}
     impl RewardManager for Contract {}
+    impl RelayerFeeManager for Contract {}
+    impl SubscriptionManager for Contract {}
+    impl QuickPickManager for Contract {}
+    impl SyndicateManager for Contract {}
+    impl ReferralManager for Contract {}
+    impl TimelockManager for Contract {}
+    impl MigrationManager for Contract {}
+    impl StatisticsManager for Contract {}
+    impl ClassicRaffleManager for Contract {}
+    impl KenoManager for Contract {}
+
+    /// The code upgrade is scheduled in the timelock like the other sensitive operations
+    #[overrider(Upgradeable)]
+    fn set_code_hash(&mut self, new_code_hash: Hash) -> Result<(), UpgradeableError> {
+        let caller = Self::env().caller();
+        if !AccessControl::has_role(self, DEFAULT_ADMIN_ROLE, Some(caller)) {
+            return Err(UpgradeableError::AccessControlError(
+                AccessControlError::MissingRole,
+            ));
+        }
+        self.inner_schedule(Operation::SetCodeHash(new_code_hash))?;
+        Ok(())
+    }
+
+    /// The role is granted after the delay of the timelock like the other sensitive operations
+    #[overrider(AccessControl)]
+    fn grant_role(
+        &mut self,
+        role: RoleType,
+        account: Option<AccountId>,
+    ) -> Result<(), AccessControlError> {
+        let caller = Self::env().caller();
+        let admin_role = AccessControl::get_role_admin(self, role);
+        if !AccessControl::has_role(self, admin_role, Some(caller)) {
+            return Err(AccessControlError::MissingRole);
+        }
+        if AccessControl::has_role(self, role, account) {
+            return Err(AccessControlError::RoleRedundant);
+        }
+        self.inner_schedule(Operation::GrantRole(role, account))?;
+        Ok(())
+    }
+
+    /// The role is revoked after the delay of the timelock like the other sensitive operations
+    #[overrider(AccessControl)]
+    fn revoke_role(
+        &mut self,
+        role: RoleType,
+        account: Option<AccountId>,
+    ) -> Result<(), AccessControlError> {
+        let caller = Self::env().caller();
+        let admin_role = AccessControl::get_role_admin(self, role);
+        if !AccessControl::has_role(self, admin_role, Some(caller)) {
+            return Err(AccessControlError::MissingRole);
+        }
+        if !AccessControl::has_role(self, role, account) {
+            return Err(AccessControlError::MissingRole);
+        }
+        self.inner_schedule(Operation::RevokeRole(role, account))?;
+        Ok(())
+    }
+
+    /// Max number of subscriptions processed when a raffle starts, the others are processed by a keeper
+    const SUBSCRIPTIONS_PROCESSED_ON_START: u32 = 10;
+
+    /// Prefix of the key used in the kv store to save why a message of the offchain rollup was rejected
+    const MESSAGE_REJECTED: u32 = ink::selector_id!("MESSAGE_REJECTED");
+    /// Prefix of the key used in the kv store to save the number of times a request of the raffle was queued again
+    const MESSAGE_RETRIES: u32 = ink::selector_id!("MESSAGE_RETRIES");
+    /// Prefix of the key used in the kv store to save the request dropped after too many rejected responses
+    const DROPPED_REQUEST: u32 = ink::selector_id!("DROPPED_REQUEST");
+    /// Max number of times a request is queued again after a rejected response
+    const MAX_MESSAGE_RETRIES: u8 = 3;
+
+    /// Prefix of the keys used in the kv store to save the participations read by the offchain rollup
+    const PARTICIPATION: u32 = ink::selector_id!("PARTICIPATION");
+    /// Prefix of the key used in the kv store to save the number of participations of a raffle
+    const NB_PARTICIPATIONS: u32 = ink::selector_id!("NB_PARTICIPATIONS");
+    /// Prefix of the keys used in the kv store to save the numbers of the keno tickets
+    const KENO_TICKET: u32 = ink::selector_id!("KENO_TICKET");
+    /// Max number of participations (or keno tickets) of a raffle saved in the kv store.
+    /// The offchain rollup reads them one by one, with one call to the RPC node for each of them
+    pub const MAX_ONCHAIN_PARTICIPATIONS: u32 = 100;
 
     impl RollupAnchor for Contract {}
     impl MetaTransaction for Contract {}
 
     impl Contract {
+        /// The sensitive operations are executed `min_delay` milliseconds after their scheduling
         #[ink(constructor)]
-        pub fn new() -> Self {
+        pub fn new(min_delay: Timestamp) -> Self {
             let mut instance = Self::default();
             let caller = instance.env().caller();
             // set the owner of this contract
             ownable::Internal::_init_with_owner(&mut instance, caller);
             // set the admin of this contract
             access_control::Internal::_init_with_admin(&mut instance, Some(caller));
-            // grant the role manager
-            AccessControl::grant_role(&mut instance, LOTTO_MANAGER_ROLE, Some(caller))
-                .expect("Should grant the role LOTTO_MANAGER_ROLE");
+            // grant the role manager (without the timelock)
+            access_control::Internal::_setup_role(&mut instance, LOTTO_MANAGER_ROLE, Some(caller));
+            // grant the role pauser (without the timelock)
+            access_control::Internal::_setup_role(&mut instance, PAUSER_ROLE, Some(caller));
+            // set the delay of the timelock
+            TimelockManager::set_min_delay(&mut instance, min_delay)
+                .expect("Should set a non zero delay");
+            // a new contract uses the last layout of the storage
+            MigrationManager::set_storage_version(&mut instance, STORAGE_VERSION)
+                .expect("Should set the storage version");
             instance
         }
 
+        /// Pause the participations, the claims and the raffle workflow (pauser only)
         #[ink(message)]
-        pub fn participate(&mut self, numbers: Vec<Number>) -> Result<(), ContractError> {
-            // check if the numbers are correct
-            RaffleConfig::check_numbers(self, &numbers)?;
-            // check if the user can participate (raffle is open)
-            Raffle::can_participate(self)?;
-            // save the participation with an event
-            let participant = Self::env().caller();
-            let raffle_id = Raffle::get_current_raffle_id(self);
-            self.env().emit_event(ParticipationRegistered {
-                raffle_id,
-                participant,
-                numbers,
+        #[openbrush::modifiers(access_control::only_role(PAUSER_ROLE))]
+        pub fn pause(&mut self, reason: u8) -> Result<(), ContractError> {
+            pausable::Internal::_pause(self)?;
+            self.env().emit_event(Paused {
+                account: Self::env().caller(),
+                reason,
             });
             Ok(())
         }
 
+        /// Unpause the contract (pauser only)
         #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(PAUSER_ROLE))]
+        pub fn unpause(&mut self, reason: u8) -> Result<(), ContractError> {
+            pausable::Internal::_unpause(self)?;
+            self.env().emit_event(Unpaused {
+                account: Self::env().caller(),
+                reason,
+            });
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate(
+            &mut self,
+            lottery_id: LotteryId,
+            numbers: Vec<Number>,
+        ) -> Result<(), ContractError> {
+            let participant = Self::env().caller();
+            self.inner_participate_batch_for(lottery_id, participant, ink::prelude::vec![numbers])
+        }
+
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
         pub fn participate_batch(
             &mut self,
+            lottery_id: LotteryId,
             numbers: Vec<Vec<Number>>,
         ) -> Result<(), ContractError> {
-            // check if the numbers are correct
-            for n in numbers {
-                self.participate(n)?;
-            }
-
-            Ok(())
+            let participant = Self::env().caller();
+            self.inner_participate_batch_for(lottery_id, participant, numbers)
         }
 
-        #[ink(message)]
-        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
-        pub fn set_config(&mut self, config: Config) -> Result<(), RaffleError> {
-            // check the status, we can set the config only when the raffle is not started yet
-            let status = Raffle::get_current_status(self);
-            if status != Status::NotStarted {
-                return Err(RaffleError::IncorrectStatus);
-            }
-
-            // update the config
-            RaffleConfig::set_config(self, config)?;
+        /// Buy a ticket for the beneficiary. The caller pays the ticket and the beneficiary wins the rewards
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate_for(
+            &mut self,
+            lottery_id: LotteryId,
+            beneficiary: AccountId,
+            numbers: Vec<Number>,
+        ) -> Result<(), ContractError> {
+            self.inner_participate_batch_for(lottery_id, beneficiary, ink::prelude::vec![numbers])
+        }
 
-            Ok(())
+        /// Buy the tickets for the beneficiary. The caller pays the tickets and the beneficiary wins the rewards
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate_batch_for(
+            &mut self,
+            lottery_id: LotteryId,
+            beneficiary: AccountId,
+            numbers: Vec<Vec<Number>>,
+        ) -> Result<(), ContractError> {
+            self.inner_participate_batch_for(lottery_id, beneficiary, numbers)
         }
 
-        #[ink(message)]
-        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
-        pub fn start_raffle(&mut self) -> Result<RaffleId, ContractError> {
-            let raffle_id = self.inner_start_raffle()?;
-            Ok(raffle_id)
+        fn inner_participate_batch_for(
+            &mut self,
+            lottery_id: LotteryId,
+            beneficiary: AccountId,
+            numbers: Vec<Vec<Number>>,
+        ) -> Result<(), ContractError> {
+            // check the limits
+            RaffleConfig::check_batch_size(self, lottery_id, numbers.len())?;
+            self.inner_add_tickets(lottery_id, beneficiary, numbers.len() as u32)?;
+            // check if the tickets are paid
+            let payer = Self::env().caller();
+            self.inner_pay_tickets(lottery_id, payer, numbers.len())?;
+            // save the participations
+            self.inner_participate_batch(lottery_id, beneficiary, payer, numbers)
         }
 
-        fn inner_start_raffle(&mut self) -> Result<RaffleId, ContractError> {
-            // start new raffle
-            let raffle_id = Raffle::start_new_raffle(self)?;
+        /// Participate and bind the caller to the referrer if he has no referrer yet.
+        /// The referrer earns a share of the price of the tickets bought by the caller
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate_with_referrer(
+            &mut self,
+            lottery_id: LotteryId,
+            numbers: Vec<Number>,
+            referrer: AccountId,
+        ) -> Result<(), ContractError> {
+            let participant = Self::env().caller();
+            if ReferralManager::bind_referrer(self, participant, referrer)? {
+                self.env().emit_event(ReferrerRegistered {
+                    account: participant,
+                    referrer,
+                });
+            }
+            self.inner_participate_batch_for(lottery_id, participant, ink::prelude::vec![numbers])
+        }
 
-            // emit the event
-            self.env().emit_event(RaffleStarted { raffle_id });
+        /// Buy tickets for a classic raffle, each ticket is one entry in the draw
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate_tickets(
+            &mut self,
+            lottery_id: LotteryId,
+            nb_tickets: u32,
+        ) -> Result<TicketIndex, ContractError> {
+            let participant = Self::env().caller();
+            self.inner_participate_tickets_for(lottery_id, participant, nb_tickets)
+        }
 
-            Ok(raffle_id)
+        /// Buy tickets of a classic raffle for the beneficiary. The caller pays the tickets and
+        /// the beneficiary owns them. Return the index of the first ticket
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate_tickets_for(
+            &mut self,
+            lottery_id: LotteryId,
+            beneficiary: AccountId,
+            nb_tickets: u32,
+        ) -> Result<TicketIndex, ContractError> {
+            self.inner_participate_tickets_for(lottery_id, beneficiary, nb_tickets)
         }
 
-        #[ink(message)]
-        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
-        pub fn complete_raffle(&mut self) -> Result<(), ContractError> {
-            // stop the current raffle
-            Raffle::stop_current_raffle(self)?;
+        fn inner_participate_tickets_for(
+            &mut self,
+            lottery_id: LotteryId,
+            beneficiary: AccountId,
+            nb_tickets: u32,
+        ) -> Result<TicketIndex, ContractError> {
+            // check the lottery is a classic raffle
+            RaffleConfig::ensure_config(self, lottery_id)?.ensure_tickets_mode()?;
+            // check if the user can participate (raffle is open)
+            Raffle::can_participate(self, lottery_id)?;
+            // check the limits
+            RaffleConfig::check_batch_size(self, lottery_id, nb_tickets as usize)?;
+            self.inner_add_tickets(lottery_id, beneficiary, nb_tickets)?;
+            // check if the tickets are paid
+            let payer = Self::env().caller();
+            self.inner_pay_tickets(lottery_id, payer, nb_tickets as usize)?;
+
+            // the beneficiary owns the next tickets
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let first_ticket = ClassicRaffleManager::add_ticket_range(
+                self,
+                lottery_id,
+                raffle_id,
+                beneficiary,
+                nb_tickets,
+            )?;
+
+            self.env().emit_event(TicketsRegistered {
+                lottery_id,
+                raffle_id,
+                participant: beneficiary,
+                payer,
+                first_ticket,
+                nb_tickets,
+            });
+
+            Ok(first_ticket)
+        }
+
+        /// Buy a keno ticket, the amount transferred is the stake (at least the ticket price).
+        /// Return the index of the ticket
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate_keno(
+            &mut self,
+            lottery_id: LotteryId,
+            numbers: Vec<Number>,
+        ) -> Result<TicketIndex, ContractError> {
+            // check if the numbers are correct
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            config.check_keno_numbers(&numbers)?;
+            // check if the user can participate (raffle is open)
+            Raffle::can_participate(self, lottery_id)?;
+            // check the limits
+            let participant = Self::env().caller();
+            self.inner_add_tickets(lottery_id, participant, 1)?;
+
+            // the stake goes to the reserve used to pay the prizes
+            let stake = Self::env().transferred_value();
+            if stake == 0 || stake < config.ticket_price {
+                return Err(RaffleError::IncorrectPayment.into());
+            }
+            RewardManager::add_to_pot(self, lottery_id, stake)?;
+
+            // save the ticket if the reserve covers the max prize of all tickets
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let reserve = RewardManager::get_pot(self, lottery_id);
+            let ticket = KenoManager::add_keno_ticket(
+                self,
+                lottery_id,
+                raffle_id,
+                participant,
+                numbers.len() as u8,
+                stake,
+                reserve,
+            )?;
+            StatisticsManager::add_picked_numbers(self, lottery_id, raffle_id, &numbers)?;
+            self.inner_save_keno_ticket(lottery_id, raffle_id, ticket, &numbers)?;
+
+            self.env().emit_event(KenoTicketRegistered {
+                lottery_id,
+                raffle_id,
+                participant,
+                ticket,
+                numbers,
+                stake,
+            });
+
+            Ok(ticket)
+        }
+
+        /// check if the tickets are paid, refund the amount paid in excess,
+        /// reward the referrer of the payer and add the rest of the price in the pot of the lottery.
+        /// Return the amount added in the pot
+        fn inner_pay_tickets(
+            &mut self,
+            lottery_id: LotteryId,
+            payer: AccountId,
+            nb_tickets: usize,
+        ) -> Result<Balance, ContractError> {
+            let transferred_value = Self::env().transferred_value();
+            let refund =
+                RaffleConfig::check_payment(self, lottery_id, nb_tickets, transferred_value)?;
+
+            // refund the payer if he paid too much
+            if refund > 0 {
+                self.env()
+                    .transfer(payer, refund)
+                    .map_err(|_| ContractError::TransferError)?;
+            }
+
+            // the referrer earns a share of the price
+            let price = transferred_value.saturating_sub(refund);
+            let mut referral_reward = 0;
+            if let Some((referrer, amount)) =
+                ReferralManager::add_referral_reward(self, payer, price)?
+            {
+                RewardManager::add_pending_rewards(self, referrer, amount)?;
+                self.env().emit_event(ReferralRewarded {
+                    referrer,
+                    account: payer,
+                    amount,
+                });
+                referral_reward = amount;
+            }
+
+            // the rest of the price goes to the pot
+            let amount = price
+                .checked_sub(referral_reward)
+                .ok_or(RaffleError::SubOverFlow)?;
+            RewardManager::add_to_pot(self, lottery_id, amount)?;
+
+            Ok(amount)
+        }
+
+        /// Buy tickets with numbers randomly drawn by the offchain rollup.
+        /// The numbers are received before the end of the raffle
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn participate_quick_pick(
+            &mut self,
+            lottery_id: LotteryId,
+            nb_tickets: u32,
+        ) -> Result<(), ContractError> {
+            // check the players pick numbers in this lottery
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            config.ensure_numbers_mode()?;
+            // check if the user can participate (raffle is open)
+            Raffle::can_participate(self, lottery_id)?;
+            // check the limits
+            let participant = Self::env().caller();
+            RaffleConfig::check_batch_size(self, lottery_id, nb_tickets as usize)?;
+            self.inner_add_tickets(lottery_id, participant, nb_tickets)?;
+            // check if the tickets are paid
+            let amount = self.inner_pay_tickets(lottery_id, participant, nb_tickets as usize)?;
+            // the part of the price added in the pot is refunded if the numbers are not received
+            let amount_per_slot = amount
+                .checked_div(nb_tickets as Balance)
+                .ok_or(RaffleError::DivByZero)?;
+
+            // reserve the slots waiting for the numbers
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let first_slot = QuickPickManager::add_quick_pick_slots(
+                self,
+                lottery_id,
+                raffle_id,
+                participant,
+                nb_tickets,
+                amount_per_slot,
+            )?;
+
+            // request the numbers
+            let message = LottoRequestMessage::new(
+                lottery_id,
+                raffle_id,
+                Request::QuickPick(
+                    first_slot,
+                    nb_tickets,
+                    config.nb_numbers,
+                    config.min_number,
+                    config.max_number,
+                    config.bonus_pool,
+                ),
+            );
+            RollupAnchor::push_message(self, &message)?;
+
+            self.env().emit_event(QuickPickRequested {
+                lottery_id,
+                raffle_id,
+                participant,
+                first_slot,
+                nb_slots: nb_tickets,
+            });
+
+            Ok(())
+        }
+
+        /// Refund the quick pick tickets of the current raffle still waiting for the numbers
+        /// (ie the offchain rollup does not answer) so the raffle can be completed.
+        /// The part of the price added in the pot (without the referral reward) is credited
+        /// in the pending rewards of the participants
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn refund_pending_quick_picks(
+            &mut self,
+            lottery_id: LotteryId,
+        ) -> Result<u32, ContractError> {
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let slots =
+                QuickPickManager::take_pending_quick_pick_slots(self, lottery_id, raffle_id)?;
+            let nb_slots = slots.len() as u32;
+
+            // the numbers received later for these slots will be rejected
+            for (account, amount) in slots {
+                RewardManager::pay_from_pot(self, lottery_id, account, amount)?;
+            }
+
+            self.env().emit_event(QuickPicksRefunded {
+                lottery_id,
+                raffle_id,
+                nb_slots,
+            });
+
+            Ok(nb_slots)
+        }
+
+        /// Participate with a payload signed by the participant and sent by a relayer.
+        /// The ticket and the relayer fee are debited from the pending rewards of the participant,
+        /// so a participant without rewards can not play this way unless the relayer attaches
+        /// the payment of the ticket (then only the fee is debited, the relayer being repaid through it)
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn meta_participate(
+            &mut self,
+            request: ForwardRequest,
+            signature: [u8; 65],
+        ) -> Result<(), ContractError> {
+            // check the relayer fee policy
+            let fee = RelayerFeeManager::ensure_relayer_fee(self)?;
+
+            // check the signature and the nonce
+            MetaTransaction::use_meta_tx(self, &request, &signature)?;
+
+            let participation: MetaParticipation =
+                scale::Decode::decode(&mut request.data.as_slice())
+                    .or(Err(RaffleError::IncorrectMetaParticipation))?;
+
+            // check the participant plays for the current raffle of the lottery
+            let lottery_id = participation.lottery_id;
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            if participation.raffle_id != raffle_id {
+                return Err(RaffleError::IncorrectRaffle.into());
+            }
+
+            // the ticket is paid by the relayer if he attaches the payment
+            let participant = request.from;
+            let relayer = Self::env().caller();
+            let paid_by_relayer = Self::env().transferred_value() > 0;
+            let price = if paid_by_relayer {
+                0
+            } else {
+                RaffleConfig::get_tickets_price(self, lottery_id, 1)?
+            };
+
+            // check the allowance covers the ticket and the fee
+            let amount = price.checked_add(fee).ok_or(RaffleError::AddOverFlow)?;
+            if amount > participation.allowance.unwrap_or_default() {
+                return Err(RaffleError::InsufficientAllowance.into());
+            }
+
+            // pay the ticket and the relayer with the pending rewards of the participant
+            RewardManager::debit_pending_rewards(self, participant, amount)?;
+            if fee > 0 {
+                RewardManager::add_pending_rewards(self, relayer, fee)?;
+            }
+            if paid_by_relayer {
+                self.inner_pay_tickets(lottery_id, relayer, 1)?;
+            } else {
+                RewardManager::add_to_pot(self, lottery_id, price)?;
+            }
+
+            // save the participation
+            self.inner_add_tickets(lottery_id, participant, 1)?;
+            self.inner_participate(lottery_id, participant, participant, participation.numbers)?;
+
+            self.env().emit_event(ParticipationRelayed {
+                lottery_id,
+                raffle_id,
+                participant,
+                relayer,
+                fee,
+            });
+
+            Ok(())
+        }
+
+        /// count the tickets bought by the participant and check the limits
+        fn inner_add_tickets(
+            &mut self,
+            lottery_id: LotteryId,
+            participant: AccountId,
+            nb_tickets: u32,
+        ) -> Result<(), ContractError> {
+            let (nb_tickets_for_participant, total_nb_tickets) =
+                Raffle::add_tickets(self, lottery_id, participant, nb_tickets)?;
+            RaffleConfig::check_nb_tickets(
+                self,
+                lottery_id,
+                nb_tickets_for_participant,
+                total_nb_tickets,
+            )?;
+            Ok(())
+        }
+
+        fn inner_participate(
+            &mut self,
+            lottery_id: LotteryId,
+            participant: AccountId,
+            payer: AccountId,
+            numbers: Vec<Number>,
+        ) -> Result<(), ContractError> {
+            // check if the numbers are correct
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            config.check_numbers(&numbers)?;
+            // check if the user can participate (raffle is open)
+            Raffle::can_participate(self, lottery_id)?;
+            // save the participation with an event
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let (main_numbers, bonus_numbers) = config.split_numbers(&numbers)?;
+            StatisticsManager::add_picked_numbers(self, lottery_id, raffle_id, main_numbers)?;
+            self.inner_save_participation(
+                lottery_id,
+                raffle_id,
+                participant,
+                main_numbers,
+                bonus_numbers,
+            )?;
+            self.env().emit_event(ParticipationRegistered {
+                lottery_id,
+                raffle_id,
+                participant,
+                payer,
+                numbers: main_numbers.to_vec(),
+                bonus_numbers: bonus_numbers.to_vec(),
+            });
+            Ok(())
+        }
+
+        /// save the participations with only one check of the config and the status.
+        /// One event is emitted for all tickets (or the single participation event for one ticket)
+        fn inner_participate_batch(
+            &mut self,
+            lottery_id: LotteryId,
+            participant: AccountId,
+            payer: AccountId,
+            tickets: Vec<Vec<Number>>,
+        ) -> Result<(), ContractError> {
+            if tickets.is_empty() {
+                return Err(RaffleError::IncorrectNbTickets.into());
+            }
+
+            if tickets.len() == 1 {
+                let numbers = tickets.into_iter().next().unwrap_or_default();
+                return self.inner_participate(lottery_id, participant, payer, numbers);
+            }
+
+            // check if the numbers are correct
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            for numbers in tickets.iter() {
+                config.check_numbers(numbers)?;
+            }
+            // check if the user can participate (raffle is open)
+            Raffle::can_participate(self, lottery_id)?;
+            // save the participations with one event
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let mut main_tickets = Vec::with_capacity(tickets.len());
+            let mut bonus_tickets = Vec::with_capacity(tickets.len());
+            for numbers in tickets.iter() {
+                let (main_numbers, bonus_numbers) = config.split_numbers(numbers)?;
+                StatisticsManager::add_picked_numbers(self, lottery_id, raffle_id, main_numbers)?;
+                self.inner_save_participation(
+                    lottery_id,
+                    raffle_id,
+                    participant,
+                    main_numbers,
+                    bonus_numbers,
+                )?;
+                main_tickets.push(main_numbers.to_vec());
+                bonus_tickets.push(bonus_numbers.to_vec());
+            }
+            self.env().emit_event(ParticipationsRegistered {
+                lottery_id,
+                raffle_id,
+                participant,
+                payer,
+                tickets: main_tickets,
+                bonus_numbers: bonus_tickets,
+            });
+            Ok(())
+        }
+
+        /// save the participation in the kv store (readable by the offchain rollup)
+        /// when the participations of the lottery are saved on chain
+        fn inner_save_participation(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            participant: AccountId,
+            main_numbers: &[Number],
+            bonus_numbers: &[Number],
+        ) -> Result<(), ContractError> {
+            if !RaffleConfig::is_onchain_participations(self, lottery_id) {
+                return Ok(());
+            }
+
+            let nb_key = (NB_PARTICIPATIONS, lottery_id, raffle_id).encode();
+            let index: u32 = RollupAnchor::get_value(self, nb_key.clone())
+                .and_then(|value| scale::Decode::decode(&mut &value[..]).ok())
+                .unwrap_or_default();
+            // the offchain rollup reads a bounded number of participations
+            if index >= MAX_ONCHAIN_PARTICIPATIONS {
+                return Err(RaffleError::TooManyTickets.into());
+            }
+
+            let key = (PARTICIPATION, lottery_id, raffle_id, index).encode();
+            let participation = (participant, main_numbers, bonus_numbers).encode();
+            RollupAnchor::set_value(self, &key, Some(&participation));
+
+            let nb_participations = index.checked_add(1).ok_or(RaffleError::AddOverFlow)?;
+            RollupAnchor::set_value(self, &nb_key, Some(&nb_participations.encode()));
+            Ok(())
+        }
+
+        /// save the numbers of the keno ticket in the kv store (readable by the offchain rollup)
+        /// when the participations of the lottery are saved on chain
+        fn inner_save_keno_ticket(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            ticket: TicketIndex,
+            numbers: &[Number],
+        ) -> Result<(), ContractError> {
+            if RaffleConfig::is_onchain_participations(self, lottery_id) {
+                // the offchain rollup reads a bounded number of keno tickets
+                if ticket >= MAX_ONCHAIN_PARTICIPATIONS {
+                    return Err(RaffleError::TooManyTickets.into());
+                }
+                let key = (KENO_TICKET, lottery_id, raffle_id, ticket).encode();
+                RollupAnchor::set_value(self, &key, Some(&numbers.encode()));
+            }
+            Ok(())
+        }
+
+        /// Create a syndicate managed by the caller. The amount transferred is his contribution
+        #[ink(message, payable)]
+        pub fn create_syndicate(&mut self) -> Result<SyndicateId, ContractError> {
+            let manager = Self::env().caller();
+            let syndicate = SyndicateManager::add_syndicate(self, manager)?;
+            let syndicate_id = SyndicateManager::get_syndicate_id(self, syndicate.account)
+                .ok_or(RaffleError::UnknownSyndicate)?;
+
+            self.env().emit_event(SyndicateCreated {
+                syndicate_id,
+                manager,
+                account: syndicate.account,
+            });
+
+            if Self::env().transferred_value() > 0 {
+                self.contribute_to_syndicate(syndicate_id)?;
+            }
+
+            Ok(syndicate_id)
+        }
+
+        /// Allow the account to contribute to the syndicate (syndicate manager only)
+        #[ink(message)]
+        pub fn add_syndicate_member(
+            &mut self,
+            syndicate_id: SyndicateId,
+            member: AccountId,
+        ) -> Result<(), ContractError> {
+            SyndicateManager::add_syndicate_member(
+                self,
+                syndicate_id,
+                Self::env().caller(),
+                member,
+            )?;
+
+            self.env().emit_event(SyndicateMemberAdded {
+                syndicate_id,
+                member,
+            });
+
+            Ok(())
+        }
+
+        /// Add the amount transferred in the pooled balance of the syndicate (members only).
+        /// The tickets are paid by the members in proportion to their balance
+        #[ink(message, payable)]
+        pub fn contribute_to_syndicate(
+            &mut self,
+            syndicate_id: SyndicateId,
+        ) -> Result<(), ContractError> {
+            let member = Self::env().caller();
+            let amount = Self::env().transferred_value();
+            SyndicateManager::add_syndicate_contribution(self, syndicate_id, member, amount)?;
+
+            // the pooled balance is not used for the rewards until the tickets are bought
+            RewardManager::lock_funds(self, amount)?;
+
+            self.env().emit_event(SyndicateContribution {
+                syndicate_id,
+                member,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw the contribution not used yet to buy tickets
+        #[ink(message)]
+        pub fn withdraw_from_syndicate(
+            &mut self,
+            syndicate_id: SyndicateId,
+            amount: Balance,
+        ) -> Result<(), ContractError> {
+            let member = Self::env().caller();
+            SyndicateManager::withdraw_syndicate_contribution(self, syndicate_id, member, amount)?;
+
+            RewardManager::unlock_funds(self, amount)?;
+            self.env()
+                .transfer(member, amount)
+                .map_err(|_| ContractError::TransferError)?;
+
+            self.env().emit_event(SyndicateWithdrawal {
+                syndicate_id,
+                member,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Buy the tickets for the syndicate with the pooled balance (syndicate manager only).
+        /// The shares of the members in the rewards of the raffle are the amounts they paid
+        #[ink(message)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn syndicate_participate(
+            &mut self,
+            syndicate_id: SyndicateId,
+            lottery_id: LotteryId,
+            numbers: Vec<Vec<Number>>,
+        ) -> Result<(), ContractError> {
+            RaffleConfig::check_batch_size(self, lottery_id, numbers.len())?;
+            let price = RaffleConfig::get_tickets_price(self, lottery_id, numbers.len())?;
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let syndicate = SyndicateManager::spend_syndicate_balance(
+                self,
+                syndicate_id,
+                Self::env().caller(),
+                lottery_id,
+                raffle_id,
+                price,
+            )?;
+
+            // the tickets are paid with the pooled balance
+            RewardManager::unlock_funds(self, price)?;
+            RewardManager::add_to_pot(self, lottery_id, price)?;
+
+            // save the participations
+            self.inner_add_tickets(lottery_id, syndicate.account, numbers.len() as u32)?;
+            self.inner_participate_batch(lottery_id, syndicate.account, syndicate.account, numbers)
+        }
+
+        /// Subscribe to play the same numbers for the next draws.
+        /// The tickets for all draws are paid in advance (deposit)
+        #[ink(message, payable)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn subscribe(
+            &mut self,
+            lottery_id: LotteryId,
+            numbers: Vec<Number>,
+            nb_draws: u32,
+        ) -> Result<(), ContractError> {
+            // check if the numbers are correct
+            RaffleConfig::check_numbers(self, lottery_id, &numbers)?;
+
+            let subscriber = Self::env().caller();
+            let price_per_draw = RaffleConfig::get_tickets_price(self, lottery_id, 1)?;
+            let subscription = SubscriptionManager::add_subscription(
+                self,
+                lottery_id,
+                subscriber,
+                numbers,
+                nb_draws,
+                price_per_draw,
+            )?;
+
+            // check if the deposit is paid
+            let refund = Self::env()
+                .transferred_value()
+                .checked_sub(subscription.deposit)
+                .ok_or(RaffleError::IncorrectPayment)?;
+
+            // the deposit is not used for the rewards until the tickets are registered
+            RewardManager::lock_funds(self, subscription.deposit)?;
+
+            // refund the subscriber if he paid too much
+            if refund > 0 {
+                self.env()
+                    .transfer(subscriber, refund)
+                    .map_err(|_| ContractError::TransferError)?;
+            }
+
+            self.env().emit_event(SubscriptionCreated {
+                lottery_id,
+                subscriber,
+                numbers: subscription.numbers,
+                nb_draws,
+                deposit: subscription.deposit,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel the subscription to the lottery and refund the unused deposit
+        #[ink(message)]
+        pub fn cancel_subscription(&mut self, lottery_id: LotteryId) -> Result<(), ContractError> {
+            let subscriber = Self::env().caller();
+            let subscription =
+                SubscriptionManager::remove_subscription(self, lottery_id, subscriber)?;
+
+            // refund the unused deposit
+            let refund = subscription.deposit;
+            RewardManager::unlock_funds(self, refund)?;
+            if refund > 0 {
+                self.env()
+                    .transfer(subscriber, refund)
+                    .map_err(|_| ContractError::TransferError)?;
+            }
+
+            self.env().emit_event(SubscriptionCancelled {
+                lottery_id,
+                subscriber,
+                refund,
+            });
+
+            Ok(())
+        }
+
+        /// Register the tickets of the next subscribers (up to `limit`) for the current raffle of the lottery.
+        /// Anyone (ie a keeper) can call this method, it returns the number of registered tickets
+        #[ink(message)]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn process_subscriptions(
+            &mut self,
+            lottery_id: LotteryId,
+            limit: u32,
+        ) -> Result<u32, ContractError> {
+            // check if the raffle is open
+            Raffle::can_participate(self, lottery_id)?;
+            self.inner_process_subscriptions(lottery_id, limit)
+        }
+
+        fn inner_process_subscriptions(
+            &mut self,
+            lottery_id: LotteryId,
+            limit: u32,
+        ) -> Result<u32, ContractError> {
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            let tickets =
+                SubscriptionManager::next_subscribed_tickets(self, lottery_id, raffle_id, limit)?;
+            let mut nb_tickets = 0;
+
+            for ticket in tickets {
+                // the ticket is paid with the deposit
+                RewardManager::unlock_funds(self, ticket.price)?;
+                // the numbers may not respect the config anymore (ie the config changed since the
+                // subscription) => the subscription is cancelled instead of blocking the raffle
+                if config.check_numbers(&ticket.numbers).is_err() {
+                    self.inner_refund_subscription(lottery_id, ticket.subscriber, ticket.price)?;
+                    continue;
+                }
+                RewardManager::add_to_pot(self, lottery_id, ticket.price)?;
+                // the ticket is paid in advance, it is counted but not limited
+                Raffle::add_tickets(self, lottery_id, ticket.subscriber, 1)?;
+                // save the participation
+                self.inner_participate(
+                    lottery_id,
+                    ticket.subscriber,
+                    ticket.subscriber,
+                    ticket.numbers,
+                )?;
+                nb_tickets += 1;
+            }
+
+            Ok(nb_tickets)
+        }
+
+        /// cancel the subscription and credit the pending rewards of the subscriber with the given
+        /// ticket price and the unused deposit
+        fn inner_refund_subscription(
+            &mut self,
+            lottery_id: LotteryId,
+            subscriber: AccountId,
+            ticket_price: Balance,
+        ) -> Result<(), ContractError> {
+            // the subscription is already removed if it was the last draw
+            let deposit =
+                match SubscriptionManager::remove_subscription(self, lottery_id, subscriber) {
+                    Ok(subscription) => subscription.deposit,
+                    Err(_) => 0,
+                };
+            RewardManager::unlock_funds(self, deposit)?;
+
+            let refund = ticket_price
+                .checked_add(deposit)
+                .ok_or(RaffleError::AddOverFlow)?;
+            RewardManager::add_pending_rewards(self, subscriber, refund)?;
+
+            self.env().emit_event(SubscriptionCancelled {
+                lottery_id,
+                subscriber,
+                refund,
+            });
+
+            Ok(())
+        }
+
+        /// Enable or disable the counters of the numbers picked by the players
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_picks_tracking(&mut self, picks_tracking: bool) -> Result<(), ContractError> {
+            StatisticsManager::set_picks_tracking(self, picks_tracking);
+            Ok(())
+        }
+
+        /// Request the numbers and the winners of the lottery in one round trip with the offchain rollup
+        /// (only for the numbers mode, the indexer must be up to date when the raffle is completed)
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_draw_and_resolve(
+            &mut self,
+            lottery_id: LotteryId,
+            draw_and_resolve: bool,
+        ) -> Result<(), ContractError> {
+            RaffleConfig::set_draw_and_resolve(self, lottery_id, draw_and_resolve);
+            Ok(())
+        }
+
+        /// Save the participations of the lottery in the kv store, so the offchain rollup can read
+        /// the winners without indexer. It can change only between two raffles
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_onchain_participations(
+            &mut self,
+            lottery_id: LotteryId,
+            onchain_participations: bool,
+        ) -> Result<(), ContractError> {
+            // all participations of a raffle are saved or none of them
+            let status = Raffle::get_current_status(self, lottery_id);
+            if status != Status::NotStarted && status != Status::Closed {
+                return Err(RaffleError::IncorrectStatus.into());
+            }
+            RaffleConfig::set_onchain_participations(self, lottery_id, onchain_participations);
+            Ok(())
+        }
+
+        /// Schedule the new share of the ticket price paid to the referrer (in basis points) in the timelock
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_referral_share(
+            &mut self,
+            referral_share: u16,
+        ) -> Result<OperationId, ContractError> {
+            self.inner_schedule(Operation::SetReferralShare(referral_share))
+        }
+
+        /// Schedule the new fee policy of the relayers in the timelock
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_relayer_fee_policy(
+            &mut self,
+            fee_policy: RelayerFeePolicy,
+        ) -> Result<OperationId, ContractError> {
+            self.inner_schedule(Operation::SetRelayerFeePolicy(fee_policy))
+        }
+
+        /// Schedule the new config of the lottery in the timelock
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_config(
+            &mut self,
+            lottery_id: LotteryId,
+            config: Config,
+        ) -> Result<OperationId, ContractError> {
+            self.inner_schedule(Operation::SetConfig(lottery_id, config))
+        }
+
+        /// Schedule the new multipliers of the stake for the given number of picks of the keno lottery
+        /// (one multiplier by number of matches, from 0 to `nb_picks`)
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_paytable(
+            &mut self,
+            lottery_id: LotteryId,
+            nb_picks: u8,
+            multipliers: Vec<u32>,
+        ) -> Result<OperationId, ContractError> {
+            self.inner_schedule(Operation::SetPaytable(lottery_id, nb_picks, multipliers))
+        }
+
+        /// Schedule the new prize tiers of the lottery in the timelock
+        /// (an empty list shares the pot between the tickets matching all numbers)
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_prize_tiers(
+            &mut self,
+            lottery_id: LotteryId,
+            prize_tiers: Vec<PrizeTier>,
+        ) -> Result<OperationId, ContractError> {
+            self.inner_schedule(Operation::SetPrizeTiers(lottery_id, prize_tiers))
+        }
+
+        fn inner_set_prize_tiers(
+            &mut self,
+            lottery_id: LotteryId,
+            prize_tiers: Vec<PrizeTier>,
+        ) -> Result<(), ContractError> {
+            // the prizes of the tickets already bought can not change
+            let status = Raffle::get_current_status(self, lottery_id);
+            if status != Status::NotStarted && status != Status::Closed {
+                return Err(RaffleError::IncorrectStatus.into());
+            }
+
+            RaffleConfig::set_prize_tiers(self, lottery_id, prize_tiers)?;
+
+            Ok(())
+        }
+
+        fn inner_set_config(
+            &mut self,
+            lottery_id: LotteryId,
+            config: Config,
+        ) -> Result<(), ContractError> {
+            // check the status, we can set the config only when the raffle is not started yet
+            let status = Raffle::get_current_status(self, lottery_id);
+            if status != Status::NotStarted {
+                return Err(RaffleError::IncorrectStatus.into());
+            }
+
+            // update the config
+            RaffleConfig::set_config(self, lottery_id, config)?;
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn start_raffle(&mut self, lottery_id: LotteryId) -> Result<RaffleId, ContractError> {
+            let raffle_id = self.inner_start_raffle(lottery_id)?;
+            Ok(raffle_id)
+        }
+
+        fn inner_start_raffle(&mut self, lottery_id: LotteryId) -> Result<RaffleId, ContractError> {
+            // start new raffle with the current config of the lottery
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            let raffle_id = Raffle::start_new_raffle(self, lottery_id, config)?;
 
             // emit the event
-            let raffle_id = Raffle::get_current_raffle_id(self);
-            self.env().emit_event(RaffleEnded { raffle_id });
+            self.env().emit_event(RaffleStarted {
+                lottery_id,
+                raffle_id,
+            });
+
+            // register the tickets of the first subscribers
+            SubscriptionManager::reset_subscriptions_processing(self, lottery_id);
+            self.inner_process_subscriptions(lottery_id, SUBSCRIPTIONS_PROCESSED_ON_START)?;
+
+            Ok(raffle_id)
+        }
+
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        #[openbrush::modifiers(when_not_paused)]
+        pub fn complete_raffle(&mut self, lottery_id: LotteryId) -> Result<(), ContractError> {
+            // all quick pick tickets must have the numbers
+            QuickPickManager::ensure_no_pending_quick_pick(self, lottery_id)?;
+
+            // stop the current raffle
+            Raffle::stop_current_raffle(self, lottery_id)?;
 
-            // request the draw numbers
-            let config = RaffleConfig::ensure_config(self)?;
-            let message = LottoRequestMessage {
+            // emit the event
+            let raffle_id = Raffle::get_current_raffle_id(self, lottery_id);
+            self.env().emit_event(RaffleEnded {
+                lottery_id,
                 raffle_id,
-                request: Request::DrawNumbers(
+            });
+
+            // request the draw numbers (with the winners in one round trip if it is enabled)
+            // or the winning tickets of a classic raffle
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            let request = match config.game_mode {
+                // the winners of the prize tiers are resolved once the numbers are saved
+                GameMode::Numbers
+                    if RaffleConfig::is_draw_and_resolve(self, lottery_id)
+                        && RaffleConfig::get_prize_tiers(self, lottery_id).is_empty() =>
+                {
+                    Request::DrawAndResolve(
+                        config.nb_numbers,
+                        config.min_number,
+                        config.max_number,
+                        config.bonus_pool,
+                    )
+                }
+                GameMode::Numbers | GameMode::Keno(_) => Request::DrawNumbers(
                     config.nb_numbers,
                     config.min_number,
                     config.max_number,
+                    config.bonus_pool,
                 ),
+                GameMode::Tickets(nb_winning_tickets) => {
+                    let total_tickets =
+                        ClassicRaffleManager::get_total_tickets(self, lottery_id, raffle_id);
+                    // no more winning tickets than tickets sold
+                    Request::DrawTickets(
+                        total_tickets.min(nb_winning_tickets as u32),
+                        total_tickets,
+                    )
+                }
             };
-            RollupAnchor::push_message(self, &message)?;
+            let message = LottoRequestMessage::new(lottery_id, raffle_id, request);
+            let draw_request_id = RollupAnchor::push_message(self, &message)?;
+            Raffle::set_draw_request_id(self, lottery_id, raffle_id, draw_request_id)?;
 
             Ok(())
         }
 
+        #[allow(clippy::too_many_arguments)]
         fn inner_set_results(
             &mut self,
+            lottery_id: LotteryId,
             raffle_id: RaffleId,
-            config: Config,
+            nb_numbers: u8,
+            min_number: Number,
+            max_number: Number,
+            bonus_pool: Option<BonusPool>,
+            numbers: Vec<Number>,
+        ) -> Result<(), ContractError> {
+            let (main_numbers, bonus_numbers) = self.inner_save_results(
+                lottery_id, raffle_id, nb_numbers, min_number, max_number, bonus_pool, numbers,
+            )?;
+
+            // request to check the winners (of each prize tier) or the matches of the keno tickets
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            let prize_tiers = RaffleConfig::get_prize_tiers(self, lottery_id);
+            let request = match config.game_mode {
+                GameMode::Keno(_) => Request::CheckKenoWinners(main_numbers),
+                _ if !prize_tiers.is_empty() => Request::CheckTierWinners(
+                    main_numbers,
+                    bonus_numbers,
+                    prize_tiers
+                        .iter()
+                        .map(|tier| (tier.nb_main_matches, tier.nb_bonus_matches))
+                        .collect(),
+                ),
+                _ => Request::CheckWinners(main_numbers, bonus_numbers),
+            };
+            let message = LottoRequestMessage::new(lottery_id, raffle_id, request);
+            self.push_message(&message)?;
+
+            Ok(())
+        }
+
+        /// save the numbers and the winners received in one round trip
+        #[allow(clippy::too_many_arguments)]
+        fn inner_set_results_and_winners(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            nb_numbers: u8,
+            min_number: Number,
+            max_number: Number,
+            bonus_pool: Option<BonusPool>,
             numbers: Vec<Number>,
+            winners: Vec<AccountId>,
         ) -> Result<(), ContractError> {
+            // the winners are resolved by the offchain rollup only for the numbers mode
+            RaffleConfig::ensure_config(self, lottery_id)?.ensure_numbers_mode()?;
+
+            // both steps are applied in the same transaction (an error in the second step reverts
+            // the first one), the winners are checked with the saved numbers
+            self.inner_save_results(
+                lottery_id,
+                raffle_id,
+                nb_numbers,
+                min_number,
+                max_number,
+                bonus_pool,
+                numbers.clone(),
+            )?;
+            self.inner_set_winners(lottery_id, raffle_id, numbers, winners)
+        }
+
+        /// check and save the numbers, then return the main numbers and the bonus numbers
+        #[allow(clippy::too_many_arguments)]
+        fn inner_save_results(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            nb_numbers: u8,
+            min_number: Number,
+            max_number: Number,
+            bonus_pool: Option<BonusPool>,
+            numbers: Vec<Number>,
+        ) -> Result<(Vec<Number>, Vec<Number>), ContractError> {
             // check if the config used to select the number is correct
-            RaffleConfig::ensure_same_config(self, &config)?;
+            RaffleConfig::ensure_same_config(
+                self, lottery_id, nb_numbers, min_number, max_number, bonus_pool,
+            )?;
 
             // check if the numbers are correct
-            RaffleConfig::check_numbers(self, &numbers)?;
+            let config = RaffleConfig::ensure_config(self, lottery_id)?;
+            let (main_numbers, bonus_numbers) = config.check_results(&numbers)?;
+            let main_numbers = main_numbers.to_vec();
+            let bonus_numbers = bonus_numbers.to_vec();
 
             // set the result
-            Raffle::set_results(self, raffle_id, numbers.clone())?;
+            Raffle::set_results(self, lottery_id, raffle_id, numbers)?;
+            StatisticsManager::add_drawn_numbers(self, lottery_id, &main_numbers)?;
 
-            // save in the kv store the last raffle id used for verification
+            // save in the kv store the last raffle id of the lottery used for verification
             const LAST_RAFFLE: u32 = ink::selector_id!("LAST_RAFFLE_FOR_VERIF");
-            RollupAnchor::set_value(self, &LAST_RAFFLE.encode(), Some(&raffle_id.encode()));
+            let key = if lottery_id == DEFAULT_LOTTERY_ID {
+                LAST_RAFFLE.encode()
+            } else {
+                (LAST_RAFFLE, lottery_id).encode()
+            };
+            RollupAnchor::set_value(self, &key, Some(&raffle_id.encode()));
 
             // emmit the event
             self.env().emit_event(ResultReceived {
+                lottery_id,
                 raffle_id,
-                numbers: numbers.clone(),
+                numbers: main_numbers.clone(),
+                bonus_numbers: bonus_numbers.clone(),
             });
 
-            // request to check the winners
-            let message = LottoRequestMessage {
+            Ok((main_numbers, bonus_numbers))
+        }
+
+        fn inner_set_quick_picks(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            first_slot: u32,
+            tickets: Vec<Vec<Number>>,
+        ) -> Result<(), ContractError> {
+            // all tickets were checked (see inner_check_response) before any of them is saved
+            for (slot, numbers) in (first_slot..).zip(tickets) {
+                let participant =
+                    QuickPickManager::take_quick_pick_slot(self, lottery_id, raffle_id, slot)?;
+                self.inner_participate(lottery_id, participant, participant, numbers)?;
+            }
+
+            Ok(())
+        }
+
+        fn inner_set_winning_tickets(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            total_tickets: TicketIndex,
+            tickets: Vec<TicketIndex>,
+        ) -> Result<(), ContractError> {
+            // the number of drawn tickets was checked (see inner_check_response)
+
+            // the owners of the tickets are known on chain, no need to query the indexer
+            let winners = ClassicRaffleManager::set_winning_tickets(
+                self,
+                lottery_id,
                 raffle_id,
-                request: Request::CheckWinners(numbers),
-            };
-            self.push_message(&message)?;
+                total_tickets,
+                tickets.clone(),
+            )?;
+
+            // no number is drawn in a classic raffle, the status is updated to wait the winners
+            Raffle::set_results(self, lottery_id, raffle_id, Vec::new())?;
+
+            // emmit the event
+            self.env().emit_event(WinningTicketsReceived {
+                lottery_id,
+                raffle_id,
+                tickets,
+            });
+
+            self.inner_set_winners(lottery_id, raffle_id, Vec::new(), winners)
+        }
+
+        fn inner_set_keno_winners(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            matches: Vec<(TicketIndex, u8)>,
+        ) -> Result<(), ContractError> {
+            // the matches were counted with the correct numbers (see inner_check_response)
+            // settle all tickets and pay the prizes with the reserve of the lottery
+            let prizes = KenoManager::settle_keno_tickets(self, lottery_id, raffle_id, matches)?;
+            let mut winners = Vec::new();
+            let mut total_prizes: Balance = 0;
+            for (account, prize) in prizes {
+                if prize > 0 {
+                    RewardManager::pay_from_pot(self, lottery_id, account, prize)?;
+                    total_prizes = total_prizes
+                        .checked_add(prize)
+                        .ok_or(RaffleError::AddOverFlow)?;
+                    winners.push(account);
+                }
+            }
+
+            // set the winners in the raffle
+            Raffle::set_winners(self, lottery_id, raffle_id, winners.clone())?;
+            Raffle::set_prize(self, lottery_id, raffle_id, total_prizes, 0)?;
+
+            // emmit the event
+            self.env().emit_event(WinnersRevealed {
+                lottery_id,
+                raffle_id,
+                winners,
+            });
 
             Ok(())
         }
 
         pub fn inner_set_winners(
             &mut self,
+            lottery_id: LotteryId,
             raffle_id: RaffleId,
             numbers: Vec<Number>,
             winners: Vec<AccountId>,
         ) -> Result<(), ContractError> {
             // check if the winners were selected based on the correct numbers
-            Raffle::ensure_same_results(self, raffle_id, &numbers)?;
+            Raffle::ensure_same_results(self, lottery_id, raffle_id, &numbers)?;
 
             // set the winners in the raffle
-            Raffle::set_winners(self, raffle_id, winners.clone())?;
+            Raffle::set_winners(self, lottery_id, raffle_id, winners.clone())?;
 
             // emmit the event
             self.env().emit_event(WinnersRevealed {
+                lottery_id,
                 raffle_id,
                 winners: winners.clone(),
             });
 
             // set the winners in the reward manager
+            let pot = RewardManager::get_pot(self, lottery_id);
             if !winners.is_empty() {
-                RewardManager::add_winners(self, winners)?;
+                let prize_per_winner =
+                    RewardManager::add_winners(self, lottery_id, raffle_id, winners)?;
+                Raffle::set_prize(self, lottery_id, raffle_id, pot, prize_per_winner)?;
             } else {
+                // no winner, the pot is kept for the next raffle
+                Raffle::set_prize(self, lottery_id, raffle_id, pot, 0)?;
+                // start automatically the new raffle if there is no winner
+                self.inner_start_raffle(lottery_id)?;
+            }
+
+            Ok(())
+        }
+
+        fn inner_set_tier_winners(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            tier_winners: Vec<Vec<AccountId>>,
+        ) -> Result<(), ContractError> {
+            // the winners were selected based on the correct numbers and are given for each
+            // prize tier of the lottery (see inner_check_response)
+            let prize_tiers = RaffleConfig::get_prize_tiers(self, lottery_id);
+
+            // set the winners of all tiers in the raffle
+            let winners: Vec<AccountId> = tier_winners.iter().flatten().copied().collect();
+            Raffle::set_winners(self, lottery_id, raffle_id, winners.clone())?;
+
+            // emmit the event
+            self.env().emit_event(WinnersRevealed {
+                lottery_id,
+                raffle_id,
+                winners: winners.clone(),
+            });
+
+            // each tier shares its part of the pot, the part of a tier without winner
+            // is kept for the next raffle
+            let pot = RewardManager::get_pot(self, lottery_id);
+            for (tier, accounts) in prize_tiers.into_iter().zip(tier_winners) {
+                if accounts.is_empty() {
+                    continue;
+                }
+                let prize = pot
+                    .checked_mul(tier.share as Balance)
+                    .ok_or(RaffleError::MulOverFlow)?
+                    .checked_div(MAX_BASIS_POINTS as Balance)
+                    .ok_or(RaffleError::DivByZero)?;
+                let prize_per_winner = RewardManager::share_prize(
+                    self,
+                    lottery_id,
+                    raffle_id,
+                    prize,
+                    accounts.clone(),
+                )?;
+                self.env().emit_event(PrizeTierWon {
+                    lottery_id,
+                    raffle_id,
+                    nb_main_matches: tier.nb_main_matches,
+                    nb_bonus_matches: tier.nb_bonus_matches,
+                    winners: accounts,
+                    prize_per_winner,
+                });
+            }
+            // the prize per winner depends on the tier (see the events)
+            Raffle::set_prize(self, lottery_id, raffle_id, pot, 0)?;
+
+            if winners.is_empty() {
                 // start automatically the new raffle if there is no winner
-                self.inner_start_raffle()?;
+                self.inner_start_raffle(lottery_id)?;
+            }
+
+            Ok(())
+        }
+
+        /// apply the response received from the offchain rollup
+        fn inner_handle_response(
+            &mut self,
+            message: LottoResponseMessage,
+        ) -> Result<(), ContractError> {
+            let lottery_id = message.request.lottery_id;
+            let raffle_id = message.request.raffle_id;
+
+            match message.response {
+                Response::Numbers(numbers) => {
+                    let (nb_numbers, min_number, max_number, bonus_pool) = match message
+                        .request
+                        .request
+                    {
+                        Request::DrawNumbers(nb_numbers, min_number, max_number, bonus_pool) => {
+                            (nb_numbers, min_number, max_number, bonus_pool)
+                        }
+                        _ => return Err(ContractError::UnexpectedResponse),
+                    };
+                    self.inner_set_results(
+                        lottery_id, raffle_id, nb_numbers, min_number, max_number, bonus_pool,
+                        numbers,
+                    )?
+                }
+                Response::NumbersAndWinners(numbers, winners) => {
+                    let (nb_numbers, min_number, max_number, bonus_pool) = match message
+                        .request
+                        .request
+                    {
+                        Request::DrawAndResolve(nb_numbers, min_number, max_number, bonus_pool) => {
+                            (nb_numbers, min_number, max_number, bonus_pool)
+                        }
+                        _ => return Err(ContractError::UnexpectedResponse),
+                    };
+                    self.inner_set_results_and_winners(
+                        lottery_id, raffle_id, nb_numbers, min_number, max_number, bonus_pool,
+                        numbers, winners,
+                    )?
+                }
+                Response::Winners(winners) => {
+                    // the results are saved with the bonus numbers after the main numbers
+                    let numbers = match message.request.request {
+                        Request::CheckWinners(mut numbers, bonus_numbers) => {
+                            numbers.extend(bonus_numbers);
+                            numbers
+                        }
+                        _ => return Err(ContractError::UnexpectedResponse),
+                    };
+                    self.inner_set_winners(lottery_id, raffle_id, numbers, winners)?
+                }
+                Response::TierWinners(tier_winners) => {
+                    self.inner_set_tier_winners(lottery_id, raffle_id, tier_winners)?
+                }
+                Response::QuickPickNumbers(tickets) => {
+                    let first_slot = match message.request.request {
+                        Request::QuickPick(first_slot, ..) => first_slot,
+                        _ => return Err(ContractError::UnexpectedResponse),
+                    };
+                    self.inner_set_quick_picks(lottery_id, raffle_id, first_slot, tickets)?
+                }
+                Response::KenoMatches(matches) => {
+                    self.inner_set_keno_winners(lottery_id, raffle_id, matches)?
+                }
+                // rejected by inner_check_response
+                Response::Error(_) => return Err(ContractError::UnexpectedResponse),
+                Response::Tickets(tickets) => {
+                    let total_tickets = match message.request.request {
+                        Request::DrawTickets(_, total_tickets) => total_tickets,
+                        _ => return Err(ContractError::UnexpectedResponse),
+                    };
+                    self.inner_set_winning_tickets(lottery_id, raffle_id, total_tickets, tickets)?
+                }
+            }
+
+            Ok(())
+        }
+
+        /// check the response received from the offchain rollup against the state of the raffle,
+        /// without any write, so a rejected response leaves the storage unchanged
+        fn inner_check_response(
+            &self,
+            message: &LottoResponseMessage,
+        ) -> Result<(), ContractError> {
+            let lottery_id = message.request.lottery_id;
+            let raffle_id = message.request.raffle_id;
+
+            match (&message.request.request, &message.response) {
+                (
+                    Request::DrawNumbers(nb_numbers, min_number, max_number, bonus_pool),
+                    Response::Numbers(numbers),
+                ) => self.inner_check_results(
+                    lottery_id,
+                    raffle_id,
+                    *nb_numbers,
+                    *min_number,
+                    *max_number,
+                    *bonus_pool,
+                    numbers,
+                )?,
+                (
+                    Request::DrawAndResolve(nb_numbers, min_number, max_number, bonus_pool),
+                    Response::NumbersAndWinners(numbers, _),
+                ) => {
+                    // the winners are resolved by the offchain rollup only for the numbers mode
+                    RaffleConfig::ensure_config(self, lottery_id)?.ensure_numbers_mode()?;
+                    self.inner_check_results(
+                        lottery_id,
+                        raffle_id,
+                        *nb_numbers,
+                        *min_number,
+                        *max_number,
+                        *bonus_pool,
+                        numbers,
+                    )?
+                }
+                (Request::CheckWinners(numbers, bonus_numbers), Response::Winners(_)) => {
+                    // check if the winners were selected based on the correct numbers
+                    Raffle::ensure_current_status(
+                        self,
+                        lottery_id,
+                        raffle_id,
+                        Status::WaitingWinners,
+                    )?;
+                    // the results are saved with the bonus numbers after the main numbers
+                    let numbers = [numbers.as_slice(), bonus_numbers.as_slice()].concat();
+                    Raffle::ensure_same_results(self, lottery_id, raffle_id, &numbers)?;
+                }
+                (
+                    Request::CheckTierWinners(numbers, bonus_numbers, tiers),
+                    Response::TierWinners(tier_winners),
+                ) => {
+                    // the winners must be given for each prize tier of the lottery
+                    let expected_tiers: Vec<(u8, u8)> =
+                        RaffleConfig::get_prize_tiers(self, lottery_id)
+                            .iter()
+                            .map(|tier| (tier.nb_main_matches, tier.nb_bonus_matches))
+                            .collect();
+                    if expected_tiers != *tiers || tier_winners.len() != tiers.len() {
+                        return Err(RaffleError::IncorrectPrizeTiers.into());
+                    }
+                    // check if the winners were selected based on the correct numbers
+                    Raffle::ensure_current_status(
+                        self,
+                        lottery_id,
+                        raffle_id,
+                        Status::WaitingWinners,
+                    )?;
+                    let numbers = [numbers.as_slice(), bonus_numbers.as_slice()].concat();
+                    Raffle::ensure_same_results(self, lottery_id, raffle_id, &numbers)?;
+                }
+                (
+                    Request::QuickPick(first_slot, nb_slots, ..),
+                    Response::QuickPickNumbers(tickets),
+                ) => {
+                    // the numbers must be received while the raffle is open
+                    if Raffle::get_current_raffle_id(self, lottery_id) != raffle_id {
+                        return Err(RaffleError::IncorrectRaffle.into());
+                    }
+                    Raffle::can_participate(self, lottery_id)?;
+
+                    // one ticket by slot
+                    if tickets.len() != *nb_slots as usize {
+                        return Err(RaffleError::IncorrectNbTickets.into());
+                    }
+
+                    // all tickets are checked before any of them is saved
+                    let config = RaffleConfig::ensure_config(self, lottery_id)?;
+                    for (slot, numbers) in (*first_slot..).zip(tickets.iter()) {
+                        if QuickPickManager::get_quick_pick_slot(self, lottery_id, raffle_id, slot)
+                            .is_none()
+                        {
+                            return Err(RaffleError::UnknownQuickPickSlot.into());
+                        }
+                        config.check_numbers(numbers)?;
+                    }
+                }
+                (Request::CheckKenoWinners(numbers), Response::KenoMatches(_)) => {
+                    // check if the matches were counted with the correct numbers
+                    Raffle::ensure_current_status(
+                        self,
+                        lottery_id,
+                        raffle_id,
+                        Status::WaitingWinners,
+                    )?;
+                    Raffle::ensure_same_results(self, lottery_id, raffle_id, numbers)?;
+                }
+                (Request::DrawTickets(nb_tickets, total_tickets), Response::Tickets(tickets)) => {
+                    // check if the number of drawn tickets respects the config
+                    let config = RaffleConfig::ensure_config(self, lottery_id)?;
+                    let nb_winning_tickets = config.ensure_tickets_mode()?;
+                    if *nb_tickets != (*total_tickets).min(nb_winning_tickets as u32)
+                        || tickets.len() != *nb_tickets as usize
+                    {
+                        return Err(RaffleError::IncorrectWinningTickets.into());
+                    }
+
+                    // the tickets must be received while the raffle waits the results
+                    Raffle::ensure_current_status(
+                        self,
+                        lottery_id,
+                        raffle_id,
+                        Status::WaitingResults,
+                    )?;
+                }
+                // the request is queued again (see inner_reject_message)
+                (_, Response::Error(error)) => {
+                    return Err(ContractError::OffchainError(error.clone()))
+                }
+                _ => return Err(ContractError::UnexpectedResponse),
+            }
+
+            Ok(())
+        }
+
+        /// check the numbers drawn by the offchain rollup for the raffle waiting the results
+        #[allow(clippy::too_many_arguments)]
+        fn inner_check_results(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            nb_numbers: u8,
+            min_number: Number,
+            max_number: Number,
+            bonus_pool: Option<BonusPool>,
+            numbers: &[Number],
+        ) -> Result<(), ContractError> {
+            Raffle::ensure_current_status(self, lottery_id, raffle_id, Status::WaitingResults)?;
+
+            // check if the config used to select the number is correct
+            RaffleConfig::ensure_same_config(
+                self, lottery_id, nb_numbers, min_number, max_number, bonus_pool,
+            )?;
+
+            // check if the numbers are correct
+            RaffleConfig::ensure_config(self, lottery_id)?.check_results(numbers)?;
+
+            Ok(())
+        }
+
+        /// check if the raffle still waits the response to the request
+        fn is_pending_request(&self, request: &LottoRequestMessage) -> bool {
+            let lottery_id = request.lottery_id;
+            let raffle_id = request.raffle_id;
+            let status = match request.request {
+                Request::DrawNumbers(..)
+                | Request::DrawAndResolve(..)
+                | Request::DrawTickets(..) => Status::WaitingResults,
+                Request::CheckWinners(..)
+                | Request::CheckTierWinners(..)
+                | Request::CheckKenoWinners(..) => Status::WaitingWinners,
+                Request::QuickPick(first_slot, ..) => {
+                    // the slots are taken when the numbers are received
+                    if QuickPickManager::get_quick_pick_slot(
+                        self, lottery_id, raffle_id, first_slot,
+                    )
+                    .is_none()
+                    {
+                        return false;
+                    }
+                    Status::Ongoing
+                }
+            };
+            Raffle::ensure_current_status(self, lottery_id, raffle_id, status).is_ok()
+        }
+
+        /// save the reason of the rejection in the kv store (readable by the offchain rollup)
+        /// and emit the event. The request is queued again if the raffle still waits its response
+        fn inner_reject_message(
+            &mut self,
+            request: LottoRequestMessage,
+            reason: ContractError,
+        ) -> Result<(), ContractError> {
+            let lottery_id = request.lottery_id;
+            let raffle_id = request.raffle_id;
+
+            // the reason is saved as bytes so the offchain rollup can read it without its type
+            let key = (MESSAGE_REJECTED, lottery_id, raffle_id).encode();
+            RollupAnchor::set_value(self, &key, Some(&reason.encode().encode()));
+
+            // emmit the event
+            self.env().emit_event(MessageRejected {
+                lottery_id,
+                raffle_id,
+                reason,
+            });
+
+            if !self.is_pending_request(&request) {
+                return Ok(());
+            }
+
+            // the request was popped with the response, it is answered again by the offchain rollup
+            // until the max number of retries is reached
+            let retries_key = (MESSAGE_RETRIES, lottery_id, raffle_id).encode();
+            let nb_retries: u8 = RollupAnchor::get_value(self, retries_key.clone())
+                .and_then(|value| scale::Decode::decode(&mut &value[..]).ok())
+                .unwrap_or_default();
+            if nb_retries < MAX_MESSAGE_RETRIES {
+                RollupAnchor::set_value(self, &retries_key, Some(&(nb_retries + 1).encode()));
+                self.inner_push_request_again(&request)?;
+            } else {
+                // the request is kept so the manager can queue it again once the issue is fixed
+                let key = (DROPPED_REQUEST, lottery_id, raffle_id).encode();
+                RollupAnchor::set_value(self, &key, Some(&request.encode()));
+                self.env().emit_event(RequestDropped {
+                    lottery_id,
+                    raffle_id,
+                });
             }
 
             Ok(())
         }
 
+        /// push the request in the queue again and update the index of the request sent to draw the numbers
+        fn inner_push_request_again(
+            &mut self,
+            request: &LottoRequestMessage,
+        ) -> Result<(), ContractError> {
+            let request_id = RollupAnchor::push_message(self, request)?;
+            if matches!(
+                request.request,
+                Request::DrawNumbers(..) | Request::DrawAndResolve(..) | Request::DrawTickets(..)
+            ) {
+                Raffle::set_draw_request_id(
+                    self,
+                    request.lottery_id,
+                    request.raffle_id,
+                    request_id,
+                )?;
+            }
+            Ok(())
+        }
+
+        /// Queue again the request dropped after too many rejected responses
+        /// (ie once the offchain rollup is fixed). The counter of retries is reset
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn requeue_dropped_request(
+            &mut self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<(), ContractError> {
+            let key = (DROPPED_REQUEST, lottery_id, raffle_id).encode();
+            let request: LottoRequestMessage = RollupAnchor::get_value(self, key.clone())
+                .and_then(|value| scale::Decode::decode(&mut &value[..]).ok())
+                .ok_or(ContractError::NoDroppedRequest)?;
+            RollupAnchor::set_value(self, &key, None);
+
+            // the raffle could have been refunded or closed in the meantime
+            if !self.is_pending_request(&request) {
+                return Err(RaffleError::IncorrectStatus.into());
+            }
+
+            let retries_key = (MESSAGE_RETRIES, lottery_id, raffle_id).encode();
+            RollupAnchor::set_value(self, &retries_key, None);
+            self.inner_push_request_again(&request)
+        }
+
+        /// Schedule the registration of the attestor in the timelock
         #[ink(message)]
         #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
         pub fn register_attestor(
             &mut self,
             account_id: AccountId,
-        ) -> Result<(), AccessControlError> {
-            AccessControl::grant_role(self, ATTESTOR_ROLE, Some(account_id))?;
-            Ok(())
+        ) -> Result<OperationId, ContractError> {
+            self.inner_schedule(Operation::GrantRole(ATTESTOR_ROLE, Some(account_id)))
         }
 
         #[ink(message)]
@@ -377,53 +2339,223 @@ pub mod lotto_contract {
         }
 
         #[ink(message)]
-        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
-        pub fn terminate_me(&mut self) -> Result<(), ContractError> {
-            self.env().terminate_contract(self.env().caller());
+        pub fn get_pauser_role(&self) -> RoleType {
+            PAUSER_ROLE
+        }
+
+        /// Return the reason why the last message from the offchain rollup was rejected for the raffle
+        #[ink(message)]
+        pub fn get_message_rejection(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Option<ContractError> {
+            let key = (MESSAGE_REJECTED, lottery_id, raffle_id).encode();
+            let reason: Vec<u8> = RollupAnchor::get_value(self, key)
+                .and_then(|value| scale::Decode::decode(&mut &value[..]).ok())?;
+            scale::Decode::decode(&mut &reason[..]).ok()
         }
 
+        /// Schedule the withdrawal in the timelock, the value will be transferred to the caller
         #[ink(message)]
         #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
-        pub fn withdraw(&mut self, value: Balance) -> Result<(), ContractError> {
+        pub fn withdraw(&mut self, value: Balance) -> Result<OperationId, ContractError> {
             let caller = Self::env().caller();
-            self.env()
-                .transfer(caller, value)
-                .map_err(|_| ContractError::TransferError)?;
+            self.inner_schedule(Operation::Withdraw(caller, value))
+        }
+
+        /// Schedule the new delay of the timelock
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_min_delay(
+            &mut self,
+            min_delay: Timestamp,
+        ) -> Result<OperationId, ContractError> {
+            if min_delay == 0 {
+                return Err(RaffleError::IncorrectDelay.into());
+            }
+            self.inner_schedule(Operation::SetMinDelay(min_delay))
+        }
+
+        /// Cancel a scheduled operation
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn cancel(&mut self, operation_id: OperationId) -> Result<(), ContractError> {
+            TimelockManager::cancel_operation(self, operation_id)?;
+            self.env().emit_event(OperationCancelled { operation_id });
+            Ok(())
+        }
+
+        /// Execute a scheduled operation when the delay is over (anyone can call it)
+        #[ink(message)]
+        pub fn execute(&mut self, operation_id: OperationId) -> Result<(), ContractError> {
+            let scheduled_operation = TimelockManager::take_ready_operation(self, operation_id)?;
+            self.inner_execute(operation_id, scheduled_operation.operation)
+        }
+
+        /// Migrate the storage to the layout expected by the code (to call after set_code_hash)
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn migrate(&mut self) -> Result<u16, ContractError> {
+            let from_version = MigrationManager::get_storage_version(self);
+            while let Some(version) = MigrationManager::next_storage_version(self)? {
+                self.inner_migrate_to(version)?;
+                MigrationManager::set_storage_version(self, version)?;
+            }
+            let to_version = MigrationManager::get_storage_version(self);
+
+            if from_version != to_version {
+                self.env().emit_event(StorageMigrated {
+                    from_version,
+                    to_version,
+                });
+            }
+            Ok(to_version)
+        }
+
+        fn inner_migrate_to(&mut self, version: u16) -> Result<(), ContractError> {
+            match version {
+                // first versioned layout: the fields added since the deployment are lazy
+                // or mappings and are read with their default value, only the timelock
+                // needs a delay
+                1 => self.inner_migrate_to_timelock(),
+                // several lotteries: the config (saved with the legacy layout), the current
+                // raffle and the pot become the ones of the default lottery
+                2 => self.inner_migrate_to_lotteries(),
+                _ => Err(RaffleError::IncorrectStorageVersion.into()),
+            }
+        }
+
+        fn inner_migrate_to_timelock(&mut self) -> Result<(), ContractError> {
+            if TimelockManager::get_min_delay(self) == 0 {
+                TimelockManager::set_min_delay(self, DEFAULT_MIN_DELAY)?;
+            }
+            Ok(())
+        }
+
+        fn inner_migrate_to_lotteries(&mut self) -> Result<(), ContractError> {
+            // the data of the raffles are now saved by lottery, the migration is possible
+            // only when no raffle is in progress
+            let (raffle_id, status) = Raffle::take_legacy_raffle(self);
+            if status != Status::NotStarted && status != Status::Closed {
+                return Err(RaffleError::IncorrectStatus.into());
+            }
+            Raffle::set_current_raffle(self, DEFAULT_LOTTERY_ID, raffle_id, status);
+
+            if let Some(config) = RaffleConfig::take_legacy_config(self) {
+                RaffleConfig::set_config(self, DEFAULT_LOTTERY_ID, config)?;
+            }
+
+            // the pot was the balance not used for the pending rewards and the locked funds
+            let pot = Self::env()
+                .balance()
+                .checked_sub(RewardManager::get_total_pending_rewards(self))
+                .ok_or(RaffleError::SubOverFlow)?
+                .checked_sub(RewardManager::get_total_locked_funds(self))
+                .ok_or(RaffleError::SubOverFlow)?;
+            RewardManager::add_to_pot(self, DEFAULT_LOTTERY_ID, pot)?;
+
+            Ok(())
+        }
+
+        /// Save the operation in the timelock, it is executed via `execute` when the delay is over
+        fn inner_schedule(&mut self, operation: Operation) -> Result<OperationId, ContractError> {
+            let caller = Self::env().caller();
+            let (operation_id, scheduled_operation) =
+                TimelockManager::schedule_operation(self, operation, caller)?;
+            self.env().emit_event(OperationScheduled {
+                operation_id,
+                operation: scheduled_operation.operation.clone(),
+                ready_at: scheduled_operation.ready_at,
+            });
+            Ok(operation_id)
+        }
+
+        fn inner_execute(
+            &mut self,
+            operation_id: OperationId,
+            operation: Operation,
+        ) -> Result<(), ContractError> {
+            match operation {
+                Operation::SetConfig(lottery_id, config) => {
+                    self.inner_set_config(lottery_id, config)?
+                }
+                Operation::SetPaytable(lottery_id, nb_picks, multipliers) => {
+                    KenoManager::set_paytable(self, lottery_id, nb_picks, multipliers)?
+                }
+                Operation::GrantRole(role, account) => {
+                    access_control::Internal::_setup_role(self, role, account)
+                }
+                Operation::RevokeRole(role, account) => {
+                    access_control::Internal::_do_revoke_role(self, role, account)
+                }
+                Operation::Withdraw(account_id, value) => self
+                    .env()
+                    .transfer(account_id, value)
+                    .map_err(|_| ContractError::TransferError)?,
+                Operation::SetCodeHash(code_hash) => ink::env::set_code_hash(&code_hash.into())
+                    .map_err(|_| ContractError::UpgradeFailed)?,
+                Operation::SetMinDelay(min_delay) => {
+                    TimelockManager::set_min_delay(self, min_delay)?
+                }
+                Operation::SetPrizeTiers(lottery_id, prize_tiers) => {
+                    self.inner_set_prize_tiers(lottery_id, prize_tiers)?
+                }
+                Operation::SetReferralShare(referral_share) => {
+                    ReferralManager::set_referral_share(self, referral_share)?
+                }
+                Operation::SetRelayerFeePolicy(fee_policy) => {
+                    RelayerFeeManager::set_relayer_fee_policy(self, fee_policy)?
+                }
+            }
+            self.env().emit_event(OperationExecuted { operation_id });
             Ok(())
         }
     }
 
     impl rollup_anchor::MessageHandler for Contract {
         fn on_message_received(&mut self, action: Vec<u8>) -> Result<(), RollupAnchorError> {
+            // the messages are not proceed when the contract is paused
+            if Pausable::paused(self) {
+                return Err(RollupAnchorError::UnsupportedAction);
+            }
+
             // parse the response
             let message: LottoResponseMessage = scale::Decode::decode(&mut &action[..])
                 .or(Err(RollupAnchorError::FailedToDecode))?;
+            // a response built with another version of the protocol is rejected without being read,
+            // the request is queued again with the current version of the protocol
+            if let Err(error) = message.ensure_version() {
+                let request = LottoRequestMessage::new(
+                    message.request.lottery_id,
+                    message.request.raffle_id,
+                    message.request.request,
+                );
+                self.inner_reject_message(request, error.into())?;
+                return Ok(());
+            }
 
-            let raffle_id = message.request.raffle_id;
-
-            match message.response {
-                Response::Numbers(numbers) => {
-                    let config = match message.request.request {
-                        Request::DrawNumbers(nb_numbers, min_number, max_number) => Config {
-                            nb_numbers,
-                            min_number,
-                            max_number,
-                        },
-                        _ => return Err(RollupAnchorError::UnsupportedAction),
-                    };
-                    self.inner_set_results(raffle_id, config, numbers)
-                        .or(Err(RollupAnchorError::UnsupportedAction))?
-                }
-                Response::Winners(winners) => {
-                    let numbers = match message.request.request {
-                        Request::CheckWinners(numbers) => numbers,
-                        _ => return Err(RollupAnchorError::UnsupportedAction),
-                    };
-                    self.inner_set_winners(raffle_id, numbers, winners)
-                        .or(Err(RollupAnchorError::UnsupportedAction))?
-                }
+            // the response is checked before any write: a rejected response is saved with its
+            // reason instead of reverting the transaction, so the offchain rollup can read why
+            // it was rejected
+            if let Err(reason) = self.inner_check_response(&message) {
+                self.inner_reject_message(message.request, reason)?;
+                return Ok(());
             }
 
+            // an error when the checked response is applied (ie a reserve too low to pay the prizes)
+            // reverts the transaction, the request stays in the queue to be answered again
+            let retries_key = (
+                MESSAGE_RETRIES,
+                message.request.lottery_id,
+                message.request.raffle_id,
+            )
+                .encode();
+            self.inner_handle_response(message)?;
+
+            // the next rejected response of the raffle is retried again
+            RollupAnchor::set_value(self, &retries_key, None);
+
             Ok(())
         }
     }
@@ -466,5 +2598,14 @@ pub mod lotto_contract {
         fn emit_rewards_claimed_event(&self, account: AccountId, amount: Balance) {
             self.env().emit_event(RewardsClaimed { account, amount });
         }
+
+        fn get_reward_shares(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            account: AccountId,
+        ) -> Option<Vec<(AccountId, Balance)>> {
+            SyndicateManager::get_syndicate_shares(self, lottery_id, raffle_id, account)
+        }
     }
 }
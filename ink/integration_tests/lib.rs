@@ -6,31 +6,59 @@ mod e2e_tests {
     use ink_e2e::subxt::tx::Signer;
     use ink_e2e::{build_message, PolkadotConfig};
     use openbrush::contracts::access_control::accesscontrol_external::AccessControl;
+    use openbrush::contracts::pausable::pausable_external::Pausable;
+    use openbrush::contracts::upgradeable::upgradeable_external::Upgradeable;
     use openbrush::traits::AccountId;
     use openbrush::traits::Balance;
     use scale::Decode;
     use scale::Encode;
 
-    use lotto::traits::config::Config;
+    use lotto::traits::classic_raffle::classicrafflemanager_external::ClassicRaffleManager;
+    use lotto::traits::config::{BonusPool, Config, GameMode, PrizeTier};
+    use lotto::traits::error::RaffleError;
+    use lotto::traits::keno::kenomanager_external::KenoManager;
+    use lotto::traits::migration::migrationmanager_external::MigrationManager;
+    use lotto::traits::migration::STORAGE_VERSION;
+    use lotto::traits::quick_pick::quickpickmanager_external::QuickPickManager;
     use lotto::traits::raffle::raffle_external::Raffle;
+    use lotto::traits::referral::referralmanager_external::ReferralManager;
     use lotto::traits::reward::rewardmanager_external::RewardManager;
+    use lotto::traits::statistics::statisticsmanager_external::StatisticsManager;
+    use lotto::traits::subscription::subscriptionmanager_external::SubscriptionManager;
+    use lotto::traits::syndicate::syndicatemanager_external::SyndicateManager;
+    use lotto::traits::timelock::timelockmanager_external::TimelockManager;
+    use lotto::traits::timelock::{Operation, DEFAULT_MIN_DELAY};
+    use lotto::traits::LotteryId;
     use lotto::traits::Number;
     use lotto::traits::RaffleId;
+    use lotto::traits::TicketIndex;
+    use lotto::traits::DEFAULT_LOTTERY_ID;
 
     use lotto_contract::{lotto_contract, *};
+    use lotto_contract_legacy::lotto_contract as legacy_contract;
+    use lotto_legacy::traits::config::Config as LegacyConfig;
+    use lotto_legacy::traits::reward::rewardmanager_external::RewardManager as LegacyRewardManager;
+    use lotto_protocol::{ProtocolError, PROTOCOL_VERSION};
 
     use phat_rollup_anchor_ink::traits::meta_transaction::metatransaction_external::MetaTransaction;
     use phat_rollup_anchor_ink::traits::rollup_anchor::rollupanchor_external::RollupAnchor;
 
-    use lotto::traits::raffle::Status;
+    use lotto::traits::raffle::{RaffleInfo, Status};
+    use lotto::traits::relayer::RelayerFeePolicy;
     use phat_rollup_anchor_ink::traits::rollup_anchor::*;
 
     type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+    /// lottery used by the tests
+    const LOTTERY_ID: LotteryId = DEFAULT_LOTTERY_ID;
+
+    /// delay (in milliseconds) of the timelock used by the tests
+    const MIN_DELAY: u64 = 1_000;
+
     async fn alice_instantiates_contract(
         client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
     ) -> AccountId {
-        let lotto_constructor = lotto_contract::ContractRef::new();
+        let lotto_constructor = lotto_contract::ContractRef::new(MIN_DELAY);
         let lotto_contract_id = client
             .instantiate(
                 "lotto_contract",
@@ -54,13 +82,48 @@ mod e2e_tests {
             nb_numbers: 4,
             min_number: 1,
             max_number: 50,
+            ticket_price: 0,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
         };
-        let set_config = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.set_config(config));
+        alice_sets_config(client, contract_id, LOTTERY_ID, config).await;
+    }
+
+    async fn alice_sets_config(
+        client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+        contract_id: &AccountId,
+        lottery_id: LotteryId,
+        config: Config,
+    ) {
+        alice_sets_config(&mut client, &contract_id, lottery_id, config).await;
+        execute_last_operation(client, contract_id).await;
+    }
+
+    /// wait for the delay of the timelock and execute the last scheduled operation
+    async fn execute_last_operation(
+        client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+        contract_id: &AccountId,
+    ) {
+        let get_next_operation_id =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_next_operation_id());
+        let operation_id = client
+            .call_dry_run(&ink_e2e::alice(), &get_next_operation_id, 0, None)
+            .await
+            .return_value()
+            - 1;
+
+        std::thread::sleep(std::time::Duration::from_millis(2 * MIN_DELAY));
+
+        let execute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.execute(operation_id));
         client
-            .call(&ink_e2e::alice(), set_config, 0, None)
+            .call(&ink_e2e::alice(), execute, 0, None)
             .await
-            .expect("set config failed");
+            .expect("execute failed");
     }
 
     async fn alice_grants_bob_as_attestor(
@@ -75,6 +138,7 @@ mod e2e_tests {
             .call(&ink_e2e::alice(), grant_role, 0, None)
             .await
             .expect("grant bob as attestor failed");
+        execute_last_operation(client, contract_id).await;
     }
 
     async fn alice_starts_raffle(
@@ -82,7 +146,7 @@ mod e2e_tests {
         contract_id: &AccountId,
     ) -> RaffleId {
         let start_raffle = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.start_raffle());
+            .call(|contract| contract.start_raffle(LOTTERY_ID));
         client
             .call(&ink_e2e::alice(), start_raffle, 0, None)
             .await
@@ -96,7 +160,7 @@ mod e2e_tests {
         contract_id: &AccountId,
     ) {
         let stop_raffle = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.complete_raffle());
+            .call(|contract| contract.complete_raffle(LOTTERY_ID));
         client
             .call(&ink_e2e::alice(), stop_raffle, 0, None)
             .await
@@ -109,15 +173,10 @@ mod e2e_tests {
         raffle_id: RaffleId,
         numbers: Vec<Number>,
     ) {
-        let request = LottoRequestMessage {
-            raffle_id,
-            request: Request::DrawNumbers(4, 1, 50),
-        };
+        let request =
+            LottoRequestMessage::new(LOTTERY_ID, raffle_id, Request::DrawNumbers(4, 1, 50, None));
 
-        let payload = LottoResponseMessage {
-            request,
-            response: Response::Numbers(numbers.clone()),
-        };
+        let payload = LottoResponseMessage::new(request, Response::Numbers(numbers.clone()));
 
         let actions = vec![HandleActionInput::Reply(payload.encode())];
         let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
@@ -138,15 +197,13 @@ mod e2e_tests {
         numbers: Vec<Number>,
         winners: Vec<AccountId>,
     ) {
-        let request = LottoRequestMessage {
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
             raffle_id,
-            request: Request::CheckWinners(numbers.clone()),
-        };
+            Request::CheckWinners(numbers.clone(), vec![]),
+        );
 
-        let payload = LottoResponseMessage {
-            request,
-            response: Response::Winners(winners.clone()),
-        };
+        let payload = LottoResponseMessage::new(request, Response::Winners(winners.clone()));
 
         let actions = vec![HandleActionInput::Reply(payload.encode())];
         let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
@@ -167,20 +224,35 @@ mod e2e_tests {
         numbers: Vec<Number>,
     ) {
         let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.participate(numbers.clone()));
+            .call(|contract| contract.participate(LOTTERY_ID, numbers.clone()));
         client
             .call(signer, participate, 0, None)
             .await
             .expect("Participate failed");
     }
 
+    async fn participates_with_value(
+        client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+        contract_id: &AccountId,
+        signer: &ink_e2e::Keypair,
+        numbers: Vec<Number>,
+        value: Balance,
+    ) {
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(LOTTERY_ID, numbers.clone()));
+        client
+            .call(signer, participate, value, None)
+            .await
+            .expect("Participate failed");
+    }
+
     async fn get_current_raffle_id(
         client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
         contract_id: &AccountId,
     ) -> RaffleId {
         let get_current_raffle_id =
             build_message::<lotto_contract::ContractRef>(contract_id.clone())
-                .call(|contract| contract.get_current_raffle_id());
+                .call(|contract| contract.get_current_raffle_id(LOTTERY_ID));
 
         let raffle_id = client
             .call_dry_run(&ink_e2e::alice(), &get_current_raffle_id, 0, None)
@@ -218,7 +290,7 @@ mod e2e_tests {
         contract_id: &AccountId,
     ) -> Status {
         let get_current_status = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.get_current_status());
+            .call(|contract| contract.get_current_status(LOTTERY_ID));
 
         let result = client
             .call_dry_run(&ink_e2e::alice(), &get_current_status, 0, None)
@@ -233,7 +305,7 @@ mod e2e_tests {
         raffle_id: RaffleId,
     ) -> Option<Vec<Number>> {
         let get_results = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.get_results(raffle_id));
+            .call(|contract| contract.get_results(LOTTERY_ID, raffle_id));
 
         let result = client
             .call_dry_run(&ink_e2e::alice(), &get_results, 0, None)
@@ -248,7 +320,7 @@ mod e2e_tests {
         raffle_id: RaffleId,
     ) -> Option<Vec<AccountId>> {
         let get_winners = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.get_winners(raffle_id));
+            .call(|contract| contract.get_winners(LOTTERY_ID, raffle_id));
 
         let result = client
             .call_dry_run(&ink_e2e::alice(), &get_winners, 0, None)
@@ -301,7 +373,7 @@ mod e2e_tests {
 
         // fund the contract
         let fund_contract = build_message::<lotto_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.fund());
+            .call(|contract| contract.fund(LOTTERY_ID));
         client
             .call(&ink_e2e::alice(), fund_contract, value, None)
             .await
@@ -702,6 +774,32 @@ mod e2e_tests {
             "we should not be able to proceed bad messages"
         );
 
+        // message built with another version of the protocol => the message is rejected
+        let request = LottoRequestMessage::new(LOTTERY_ID, 1, Request::DrawNumbers(4, 1, 50, None));
+        let mut payload = LottoResponseMessage::new(request, Response::Numbers(vec![5, 40, 8, 2]));
+        payload.version = PROTOCOL_VERSION + 1;
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("the rejected message should not revert the transaction");
+
+        // the version is saved as reason
+        let get_message_rejection =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_message_rejection(LOTTERY_ID, 1));
+        assert_eq!(
+            Some(lotto_contract::ContractError::ProtocolError(
+                ProtocolError::UnsupportedVersion(PROTOCOL_VERSION + 1)
+            )),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_message_rejection, 0, None)
+                .await
+                .return_value()
+        );
+
         Ok(())
     }
 
@@ -728,6 +826,7 @@ mod e2e_tests {
             .call(&ink_e2e::alice(), grant_role, 0, None)
             .await
             .expect("grant the attestor failed");
+        execute_last_operation(&mut client, &contract_id).await;
 
         // prepare the meta transaction
         let data = RollupCondEqMethodParams::encode(&(vec![], vec![], vec![]));
@@ -774,4 +873,3219 @@ mod e2e_tests {
 
         Ok(())
     }
+
+    ///
+    /// Test the participation through a relayer
+    /// Alice is the owner
+    /// Dave is the participant (he signs the payload)
+    /// Charlie is the relayer (ie the payer of the tx fees)
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_meta_participate(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // use the ecsda account because we are not able to verify the sr25519 signature
+        let from = ink::primitives::AccountId::from(
+            Signer::<PolkadotConfig>::account_id(&subxt_signer::ecdsa::dev::dave()).0,
+        );
+
+        // prepare the meta transaction
+        let participation = MetaParticipation {
+            lottery_id: LOTTERY_ID,
+            raffle_id,
+            numbers: vec![5, 40, 8, 2],
+            allowance: None,
+        };
+        let data = participation.encode();
+        let prepare_meta_tx = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.prepare(from, data.clone()));
+        let (request, _hash) = client
+            .call_dry_run(&ink_e2e::charlie(), &prepare_meta_tx, 0, None)
+            .await
+            .return_value()
+            .expect("Expected value when preparing meta tx");
+
+        assert_eq!(0, request.nonce);
+        assert_eq!(from, request.from);
+        assert_eq!(&data, &request.data);
+
+        // Dave signs the message
+        let keypair = subxt_signer::ecdsa::dev::dave();
+        let signature = keypair.sign(&scale::Encode::encode(&request)).0;
+
+        // the relayers are not accepted by default
+        let meta_participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.meta_participate(request.clone(), signature));
+        let result = client
+            .call(&ink_e2e::charlie(), meta_participate, 0, None)
+            .await;
+        assert!(
+            result.is_err(),
+            "The participation should be refused because the relayers are disabled"
+        );
+
+        // alice accepts the relayers without fee
+        let set_fee_policy = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_relayer_fee_policy(RelayerFeePolicy::Free));
+        client
+            .call(&ink_e2e::alice(), set_fee_policy, 0, None)
+            .await
+            .expect("set relayer fee policy failed");
+        execute_last_operation(&mut client, &contract_id).await;
+
+        // charlie sends the participation
+        let meta_participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.meta_participate(request.clone(), signature));
+        let result = client
+            .call(&ink_e2e::charlie(), meta_participate, 0, None)
+            .await
+            .expect("meta participate should not failed");
+        assert!(result.contains_event("Contracts", "ContractEmitted"));
+
+        // do it again => it must failed
+        let meta_participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.meta_participate(request.clone(), signature));
+        let result = client
+            .call(&ink_e2e::charlie(), meta_participate, 0, None)
+            .await;
+        assert!(
+            result.is_err(),
+            "This participation should not be proceed because the nonce is obsolete"
+        );
+
+        // alice sets a fee for the relayer => dave has no reward to pay it
+        let set_fee_policy = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_relayer_fee_policy(RelayerFeePolicy::Fixed(1)));
+        client
+            .call(&ink_e2e::alice(), set_fee_policy, 0, None)
+            .await
+            .expect("set relayer fee policy failed");
+        execute_last_operation(&mut client, &contract_id).await;
+
+        let participation = MetaParticipation {
+            lottery_id: LOTTERY_ID,
+            raffle_id,
+            numbers: vec![5, 40, 8, 2],
+            allowance: Some(1),
+        };
+        let prepare_meta_tx = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.prepare(from, participation.encode()));
+        let (request, _hash) = client
+            .call_dry_run(&ink_e2e::charlie(), &prepare_meta_tx, 0, None)
+            .await
+            .return_value()
+            .expect("Expected value when preparing meta tx");
+        assert_eq!(1, request.nonce);
+        let signature = keypair.sign(&scale::Encode::encode(&request)).0;
+
+        let meta_participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.meta_participate(request.clone(), signature));
+        let result = client
+            .call(&ink_e2e::charlie(), meta_participate, 0, None)
+            .await;
+        assert!(
+            result.is_err(),
+            "The participation should be refused because dave has no reward to pay the fee"
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Test the participation through a relayer who pays the ticket
+    /// Alice is the owner
+    /// Dave is the participant without reward
+    /// Charlie is the relayer
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_meta_participate_paid_by_relayer(
+        mut client: ink_e2e::Client<C, E>,
+    ) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 10
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 10,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // alice accepts the relayers without fee
+        let set_fee_policy = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_relayer_fee_policy(RelayerFeePolicy::Free));
+        client
+            .call(&ink_e2e::alice(), set_fee_policy, 0, None)
+            .await
+            .expect("set relayer fee policy failed");
+        execute_last_operation(&mut client, &contract_id).await;
+
+        // dave signs the participation
+        let from = ink::primitives::AccountId::from(
+            Signer::<PolkadotConfig>::account_id(&subxt_signer::ecdsa::dev::dave()).0,
+        );
+        let participation = MetaParticipation {
+            lottery_id: LOTTERY_ID,
+            raffle_id,
+            numbers: vec![5, 40, 8, 2],
+            allowance: None,
+        };
+        let prepare_meta_tx = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.prepare(from, participation.encode()));
+        let (request, _hash) = client
+            .call_dry_run(&ink_e2e::charlie(), &prepare_meta_tx, 0, None)
+            .await
+            .return_value()
+            .expect("Expected value when preparing meta tx");
+        let keypair = subxt_signer::ecdsa::dev::dave();
+        let signature = keypair.sign(&scale::Encode::encode(&request)).0;
+
+        // dave has no reward to pay the ticket
+        let meta_participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.meta_participate(request.clone(), signature));
+        let result = client
+            .call(&ink_e2e::charlie(), meta_participate, 0, None)
+            .await;
+        assert!(
+            result.is_err(),
+            "The participation should be refused because dave has no reward to pay the ticket"
+        );
+
+        // when
+        // charlie attaches the payment of the ticket
+        let meta_participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.meta_participate(request.clone(), signature));
+        client
+            .call(&ink_e2e::charlie(), meta_participate, 10, None)
+            .await
+            .expect("meta participate paid by the relayer failed");
+
+        // then
+        // the ticket of dave is paid and the price goes to the pot
+        let get_pot = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_pot(LOTTERY_ID));
+        assert_eq!(
+            10,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_pot, 0, None)
+                .await
+                .return_value()
+        );
+        let raffle_info = get_raffle_info(&mut client, &contract_id, raffle_id)
+            .await
+            .expect("no raffle info");
+        assert_eq!(1, raffle_info.nb_tickets);
+
+        Ok(())
+    }
+
+    ///
+    /// Test the tickets bought on behalf of someone else
+    /// Alice is the owner
+    /// Charlie is the payer
+    /// Dave is the beneficiary
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_participate_for(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 10
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 10,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        alice_starts_raffle(&mut client, &contract_id).await;
+
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // charlie does not pay enough => it must failed
+        let participate_for = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_for(LOTTERY_ID, dave_address, vec![5, 40, 8, 2]));
+        let result = client
+            .call(&ink_e2e::charlie(), participate_for, 5, None)
+            .await;
+        assert!(result.is_err(), "the ticket should not be bought");
+
+        let contract_balance_before = client
+            .balance(contract_id)
+            .await
+            .expect("getting contract balance failed");
+
+        // charlie pays too much => he is refunded
+        let participate_for = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_for(LOTTERY_ID, dave_address, vec![5, 40, 8, 2]));
+        let result = client
+            .call(&ink_e2e::charlie(), participate_for, 15, None)
+            .await
+            .expect("participate for dave failed");
+        assert!(result.contains_event("Contracts", "ContractEmitted"));
+
+        // charlie buys two tickets for dave
+        let participate_batch_for =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract
+                    .participate_batch_for(dave_address, vec![vec![3, 6, 7, 5], vec![12, 4, 6, 2]])
+            });
+        client
+            .call(&ink_e2e::charlie(), participate_batch_for, 20, None)
+            .await
+            .expect("participate batch for dave failed");
+
+        // only the price of the tickets is kept by the contract
+        let contract_balance_after = client
+            .balance(contract_id)
+            .await
+            .expect("getting contract balance failed");
+        assert_eq!(contract_balance_before + 30, contract_balance_after);
+
+        Ok(())
+    }
+
+    ///
+    /// Test the subscriptions
+    /// Alice is the owner
+    /// Dave subscribes for 2 draws
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_subscriptions(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 10
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 10,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // dave does not pay the deposit => it must failed
+        let subscribe = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.subscribe(LOTTERY_ID, vec![5, 40, 8, 2], 2));
+        let result = client.call(&ink_e2e::dave(), subscribe, 10, None).await;
+        assert!(result.is_err(), "the deposit is not paid");
+
+        // dave subscribes for 2 draws
+        let subscribe = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.subscribe(LOTTERY_ID, vec![5, 40, 8, 2], 2));
+        client
+            .call(&ink_e2e::dave(), subscribe, 20, None)
+            .await
+            .expect("subscribe failed");
+
+        let get_total_locked_funds =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_total_locked_funds());
+        assert_eq!(
+            20,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_total_locked_funds, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the ticket is registered when the raffle starts
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        let get_subscription = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_subscription(LOTTERY_ID, dave_address));
+        let subscription = client
+            .call_dry_run(&ink_e2e::alice(), &get_subscription, 0, None)
+            .await
+            .return_value()
+            .expect("subscription not found");
+        assert_eq!(1, subscription.remaining_draws);
+        assert_eq!(10, subscription.deposit);
+        assert_eq!(Some(raffle_id), subscription.last_raffle_id);
+
+        // the keeper processes the subscriptions => no new ticket for this raffle
+        let process_subscriptions =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.process_subscriptions(LOTTERY_ID, 10));
+        let nb_tickets = client
+            .call(&ink_e2e::charlie(), process_subscriptions, 0, None)
+            .await
+            .expect("process subscriptions failed")
+            .return_value()
+            .expect("no value");
+        assert_eq!(0, nb_tickets);
+
+        // dave cancels the subscription => the unused deposit is refunded
+        let cancel_subscription = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.cancel_subscription(LOTTERY_ID));
+        client
+            .call(&ink_e2e::dave(), cancel_subscription, 0, None)
+            .await
+            .expect("cancel subscription failed");
+
+        let get_subscription = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_subscription(LOTTERY_ID, dave_address));
+        assert_eq!(
+            None,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_subscription, 0, None)
+                .await
+                .return_value()
+        );
+
+        let get_total_locked_funds =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_total_locked_funds());
+        assert_eq!(
+            0,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_total_locked_funds, 0, None)
+                .await
+                .return_value()
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Test the subscriptions when the numbers do not respect the config anymore
+    /// Alice is the owner
+    /// Dave subscribes before the config changes
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_outdated_subscription(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 10
+        let mut config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 10,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // dave subscribes for 2 draws with 4 numbers
+        let subscribe = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.subscribe(LOTTERY_ID, vec![5, 40, 8, 2], 2));
+        client
+            .call(&ink_e2e::dave(), subscribe, 20, None)
+            .await
+            .expect("subscribe failed");
+
+        // alice changes the config before the first raffle => 5 numbers
+        config.nb_numbers = 5;
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        // when
+        // the raffle starts even if the subscribed ticket is not valid anymore
+        alice_starts_raffle(&mut client, &contract_id).await;
+
+        // then
+        // the subscription is cancelled and the deposit is refunded in the pending rewards
+        let get_subscription = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_subscription(LOTTERY_ID, dave_address));
+        assert_eq!(
+            None,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_subscription, 0, None)
+                .await
+                .return_value()
+        );
+        assert_eq!(
+            Some(20),
+            get_pending_rewards_from(&mut client, &contract_id, &dave_address).await
+        );
+
+        let get_total_locked_funds =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_total_locked_funds());
+        assert_eq!(
+            0,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_total_locked_funds, 0, None)
+                .await
+                .return_value()
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Test the quick pick tickets
+    /// Alice is the owner
+    /// Bob is the attestor
+    /// Dave buys quick pick tickets
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_quick_pick(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // dave buys 2 quick pick tickets
+        let participate_quick_pick =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.participate_quick_pick(LOTTERY_ID, 2));
+        client
+            .call(&ink_e2e::dave(), participate_quick_pick, 0, None)
+            .await
+            .expect("participate quick pick failed");
+
+        let get_nb_pending_quick_picks =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_nb_pending_quick_picks(LOTTERY_ID));
+        assert_eq!(
+            2,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_nb_pending_quick_picks, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the raffle can not be completed while the numbers are not received
+        let complete_raffle = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.complete_raffle(LOTTERY_ID));
+        let result = client
+            .call(&ink_e2e::alice(), complete_raffle, 0, None)
+            .await;
+        assert!(result.is_err(), "the quick pick tickets have no number");
+
+        // bob sends the numbers
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::QuickPick(0, 2, 4, 1, 50, None),
+        );
+        let payload = LottoResponseMessage::new(
+            request,
+            Response::QuickPickNumbers(vec![vec![5, 40, 8, 2], vec![3, 6, 7, 5]]),
+        );
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        let result = client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send quick pick numbers failed");
+        assert!(result.contains_event("Contracts", "ContractEmitted"));
+
+        let get_nb_pending_quick_picks =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_nb_pending_quick_picks(LOTTERY_ID));
+        assert_eq!(
+            0,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_nb_pending_quick_picks, 0, None)
+                .await
+                .return_value()
+        );
+
+        // now the raffle can be completed
+        alice_stops_raffle(&mut client, &contract_id).await;
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Test the refund of the quick pick tickets without numbers
+    /// Alice is the owner
+    /// Bob is the attestor
+    /// Dave buys quick pick tickets
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_refund_quick_picks(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 10
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 10,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+        // the referrer earns 10%
+        let set_referral_share = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_referral_share(1_000));
+        client
+            .call(&ink_e2e::alice(), set_referral_share, 0, None)
+            .await
+            .expect("set referral share failed");
+        execute_last_operation(&mut client, &contract_id).await;
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // dave participates with charlie as referrer
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let participate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.participate_with_referrer(LOTTERY_ID, vec![5, 40, 8, 2], charlie_address)
+            });
+        client
+            .call(&ink_e2e::dave(), participate, 10, None)
+            .await
+            .expect("participate with referrer failed");
+
+        // dave buys 2 quick pick tickets => 2 for charlie and 18 in the pot
+        let participate_quick_pick =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.participate_quick_pick(LOTTERY_ID, 2));
+        client
+            .call(&ink_e2e::dave(), participate_quick_pick, 20, None)
+            .await
+            .expect("participate quick pick failed");
+
+        // only the manager can refund the tickets
+        let refund_pending_quick_picks =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.refund_pending_quick_picks(LOTTERY_ID));
+        let result = client
+            .call(&ink_e2e::dave(), refund_pending_quick_picks, 0, None)
+            .await;
+        assert!(result.is_err(), "only the manager can refund the tickets");
+
+        // when
+        // the numbers are never received => alice refunds the tickets
+        let refund_pending_quick_picks =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.refund_pending_quick_picks(LOTTERY_ID));
+        let nb_slots = client
+            .call(&ink_e2e::alice(), refund_pending_quick_picks, 0, None)
+            .await
+            .expect("refund pending quick picks failed")
+            .return_value()
+            .expect("no value");
+        assert_eq!(2, nb_slots);
+
+        // then
+        // only the part of the price added in the pot is refunded
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        assert_eq!(
+            Some(18),
+            get_pending_rewards_from(&mut client, &contract_id, &dave_address).await
+        );
+
+        let get_nb_pending_quick_picks =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_nb_pending_quick_picks(LOTTERY_ID));
+        assert_eq!(
+            0,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_nb_pending_quick_picks, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the raffle can be completed
+        alice_stops_raffle(&mut client, &contract_id).await;
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+
+        // the numbers received too late are rejected
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::QuickPick(0, 2, 4, 1, 50, None),
+        );
+        let payload = LottoResponseMessage::new(
+            request,
+            Response::QuickPickNumbers(vec![vec![5, 40, 8, 2], vec![3, 6, 7, 5]]),
+        );
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("rollup cond eq failed");
+        assert_eq!(
+            Some(18),
+            get_pending_rewards_from(&mut client, &contract_id, &dave_address).await
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Test the syndicates
+    /// Alice is the owner
+    /// Bob is the attestor
+    /// Charlie manages the syndicate and Dave is a member
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_syndicate(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 10
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 10,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        fund(&mut client, &contract_id, 100).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // charlie creates the syndicate with 30
+        let create_syndicate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.create_syndicate());
+        let syndicate_id = client
+            .call(&ink_e2e::charlie(), create_syndicate, 30, None)
+            .await
+            .expect("create syndicate failed")
+            .return_value()
+            .expect("no value");
+
+        // dave is not a member => he can not contribute
+        let contribute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.contribute_to_syndicate(syndicate_id));
+        let result = client.call(&ink_e2e::dave(), contribute, 10, None).await;
+        assert!(result.is_err(), "dave is not a member");
+
+        // only the manager can add the members
+        let add_syndicate_member =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.add_syndicate_member(syndicate_id, dave_address));
+        let result = client
+            .call(&ink_e2e::dave(), add_syndicate_member, 0, None)
+            .await;
+        assert!(result.is_err(), "dave is not the manager");
+
+        let add_syndicate_member =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.add_syndicate_member(syndicate_id, dave_address));
+        client
+            .call(&ink_e2e::charlie(), add_syndicate_member, 0, None)
+            .await
+            .expect("add syndicate member failed");
+
+        // dave contributes with 10
+        let contribute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.contribute_to_syndicate(syndicate_id));
+        client
+            .call(&ink_e2e::dave(), contribute, 10, None)
+            .await
+            .expect("contribute to syndicate failed");
+
+        let get_syndicate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_syndicate(syndicate_id));
+        let syndicate = client
+            .call_dry_run(&ink_e2e::alice(), &get_syndicate, 0, None)
+            .await
+            .return_value()
+            .expect("syndicate not found");
+        assert_eq!(2, syndicate.members.len());
+        assert_eq!(40, syndicate.balance);
+
+        let tickets = vec![
+            vec![5, 40, 8, 2],
+            vec![1, 2, 3, 4],
+            vec![6, 7, 9, 10],
+            vec![11, 12, 13, 14],
+        ];
+
+        // only the manager can buy the tickets
+        let syndicate_participate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.syndicate_participate(syndicate_id, LOTTERY_ID, tickets.clone())
+            });
+        let result = client
+            .call(&ink_e2e::dave(), syndicate_participate, 0, None)
+            .await;
+        assert!(result.is_err(), "dave is not the manager");
+
+        // the 4 tickets are paid with the pooled balance
+        let syndicate_participate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.syndicate_participate(syndicate_id, LOTTERY_ID, tickets.clone())
+            });
+        client
+            .call(&ink_e2e::charlie(), syndicate_participate, 0, None)
+            .await
+            .expect("syndicate participate failed");
+
+        // the shares in the raffle are the amounts paid by the members
+        let get_syndicate_raffle_shares =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.get_syndicate_raffle_shares(syndicate_id, LOTTERY_ID, raffle_id)
+            });
+        assert_eq!(
+            Some(vec![(charlie_address, 30), (dave_address, 10)]),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_syndicate_raffle_shares, 0, None)
+                .await
+                .return_value()
+        );
+
+        // a contribution after the tickets are bought does not change the shares in the raffle
+        let contribute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.contribute_to_syndicate(syndicate_id));
+        client
+            .call(&ink_e2e::dave(), contribute, 50, None)
+            .await
+            .expect("contribute to syndicate failed");
+
+        // dave can not withdraw more than his balance
+        let withdraw_from_syndicate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.withdraw_from_syndicate(syndicate_id, 60));
+        let result = client
+            .call(&ink_e2e::dave(), withdraw_from_syndicate, 0, None)
+            .await;
+        assert!(result.is_err(), "dave withdraws more than his balance");
+
+        // dave withdraws the unused contribution
+        let withdraw_from_syndicate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.withdraw_from_syndicate(syndicate_id, 50));
+        client
+            .call(&ink_e2e::dave(), withdraw_from_syndicate, 0, None)
+            .await
+            .expect("withdraw from syndicate failed");
+
+        let get_syndicate_member_balance =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_syndicate_member_balance(syndicate_id, dave_address));
+        assert_eq!(
+            0,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_syndicate_member_balance, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the syndicate wins
+        alice_stops_raffle(&mut client, &contract_id).await;
+        let results: Vec<Number> = vec![5, 40, 8, 2];
+        bob_sends_results(&mut client, &contract_id, raffle_id, results.clone()).await;
+        bob_sends_winners(
+            &mut client,
+            &contract_id,
+            raffle_id,
+            results,
+            vec![syndicate.account],
+        )
+        .await;
+
+        // the reward (pot of 140) is split between the members in proportion to the amounts paid
+        assert_eq!(
+            Some(105),
+            get_pending_rewards_from(&mut client, &contract_id, &charlie_address).await
+        );
+        assert_eq!(
+            Some(35),
+            get_pending_rewards_from(&mut client, &contract_id, &dave_address).await
+        );
+        assert_eq!(
+            None,
+            get_pending_rewards_from(&mut client, &contract_id, &syndicate.account).await
+        );
+
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::dave(), 35).await;
+
+        Ok(())
+    }
+
+    /// The shares of the members do not divide the prize evenly
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_syndicate_remainder(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 10
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 10,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        fund(&mut client, &contract_id, 100).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // charlie pays 20 and dave pays 10
+        let create_syndicate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.create_syndicate());
+        let syndicate = client
+            .call(&ink_e2e::charlie(), create_syndicate, 20, None)
+            .await
+            .expect("create syndicate failed")
+            .return_value()
+            .expect("no value");
+        let add_syndicate_member =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.add_syndicate_member(syndicate, dave_address));
+        client
+            .call(&ink_e2e::charlie(), add_syndicate_member, 0, None)
+            .await
+            .expect("add syndicate member failed");
+        let contribute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.contribute_to_syndicate(syndicate));
+        client
+            .call(&ink_e2e::dave(), contribute, 10, None)
+            .await
+            .expect("contribute to syndicate failed");
+
+        let tickets = vec![vec![5, 40, 8, 2], vec![1, 2, 3, 4], vec![6, 7, 9, 10]];
+        let syndicate_participate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.syndicate_participate(syndicate, LOTTERY_ID, tickets.clone())
+            });
+        client
+            .call(&ink_e2e::charlie(), syndicate_participate, 0, None)
+            .await
+            .expect("syndicate participate failed");
+
+        let get_syndicate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_syndicate(syndicate));
+        let syndicate_account = client
+            .call_dry_run(&ink_e2e::alice(), &get_syndicate, 0, None)
+            .await
+            .return_value()
+            .expect("syndicate not found")
+            .account;
+
+        // the syndicate wins the pot of 130
+        alice_stops_raffle(&mut client, &contract_id).await;
+        let results: Vec<Number> = vec![5, 40, 8, 2];
+        bob_sends_results(&mut client, &contract_id, raffle_id, results.clone()).await;
+        bob_sends_winners(
+            &mut client,
+            &contract_id,
+            raffle_id,
+            results,
+            vec![syndicate_account],
+        )
+        .await;
+
+        // 130 * 20 / 30 = 86 and 130 * 10 / 30 = 43
+        assert_eq!(
+            Some(86),
+            get_pending_rewards_from(&mut client, &contract_id, &charlie_address).await
+        );
+        assert_eq!(
+            Some(43),
+            get_pending_rewards_from(&mut client, &contract_id, &dave_address).await
+        );
+
+        // the remainder goes back to the pot
+        let get_pot = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_pot(LOTTERY_ID));
+        assert_eq!(
+            1,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_pot, 0, None)
+                .await
+                .return_value()
+        );
+        assert_eq!(
+            129,
+            get_total_pending_rewards(&mut client, &contract_id).await
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Test the referral program
+    /// Alice is the owner
+    /// Charlie is the referrer of Dave
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_referral(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // the ticket price is 100
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 100,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        // the referrer earns 10%
+        let set_referral_share = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_referral_share(1_000));
+        client
+            .call(&ink_e2e::alice(), set_referral_share, 0, None)
+            .await
+            .expect("set referral share failed");
+        execute_last_operation(&mut client, &contract_id).await;
+
+        alice_starts_raffle(&mut client, &contract_id).await;
+
+        let bob_address = ink::primitives::AccountId::from(ink_e2e::bob().public_key().0);
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // dave participates with charlie as referrer
+        let participate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.participate_with_referrer(LOTTERY_ID, vec![5, 40, 8, 2], charlie_address)
+            });
+        client
+            .call(&ink_e2e::dave(), participate, 100, None)
+            .await
+            .expect("participate with referrer failed");
+
+        // dave participates again with bob as referrer => charlie stays the referrer
+        let participate =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.participate_with_referrer(LOTTERY_ID, vec![3, 6, 7, 5], bob_address)
+            });
+        client
+            .call(&ink_e2e::dave(), participate, 100, None)
+            .await
+            .expect("participate with referrer failed");
+
+        // dave participates without referrer => charlie earns his share
+        participates_with_value(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![12, 4, 6, 2],
+            100,
+        )
+        .await;
+
+        let get_referrer = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_referrer(dave_address));
+        assert_eq!(
+            Some(charlie_address),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_referrer, 0, None)
+                .await
+                .return_value()
+        );
+
+        let get_nb_referrals = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_nb_referrals(charlie_address));
+        assert_eq!(
+            1,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_nb_referrals, 0, None)
+                .await
+                .return_value()
+        );
+
+        let get_referral_rewards =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_referral_rewards(charlie_address));
+        assert_eq!(
+            30,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_referral_rewards, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the referral rewards are claimed as the other rewards
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::charlie(), 30).await;
+        assert_eq!(
+            None,
+            get_pending_rewards_from(&mut client, &contract_id, &bob_address).await
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Test the limits of tickets
+    /// Alice is the owner
+    /// Charlie and Dave participate
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_tickets_limits(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 0,
+            max_tickets_per_account: Some(3),
+            max_batch_size: Some(2),
+            max_tickets: Some(4),
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // the batch is too large
+        let participate_batch = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| {
+                contract.participate_batch(
+                    LOTTERY_ID,
+                    vec![vec![5, 40, 8, 2], vec![3, 6, 7, 5], vec![12, 4, 6, 2]],
+                )
+            });
+        let result = client
+            .call(&ink_e2e::dave(), participate_batch, 0, None)
+            .await;
+        assert!(result.is_err(), "the batch is too large");
+
+        // dave buys 3 tickets
+        let participate_batch = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| {
+                contract.participate_batch(LOTTERY_ID, vec![vec![5, 40, 8, 2], vec![3, 6, 7, 5]])
+            });
+        client
+            .call(&ink_e2e::dave(), participate_batch, 0, None)
+            .await
+            .expect("participate batch failed");
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![12, 4, 6, 2],
+        )
+        .await;
+
+        // dave can not buy more tickets
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(LOTTERY_ID, vec![15, 44, 4, 1]));
+        let result = client.call(&ink_e2e::dave(), participate, 0, None).await;
+        assert!(result.is_err(), "too many tickets for dave");
+
+        // charlie buys the last ticket
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::charlie(),
+            vec![50, 3, 8, 2],
+        )
+        .await;
+
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(LOTTERY_ID, vec![34, 6, 2, 5]));
+        let result = client.call(&ink_e2e::charlie(), participate, 0, None).await;
+        assert!(result.is_err(), "too many tickets for the raffle");
+
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let get_nb_tickets_for = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_nb_tickets_for(LOTTERY_ID, raffle_id, dave_address));
+        assert_eq!(
+            3,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_nb_tickets_for, 0, None)
+                .await
+                .return_value()
+        );
+
+        let get_nb_tickets = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_nb_tickets(LOTTERY_ID, raffle_id));
+        assert_eq!(
+            4,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_nb_tickets, 0, None)
+                .await
+                .return_value()
+        );
+
+        Ok(())
+    }
+
+    ///
+    /// Compare the weight of a batch participation with the weight of the single participations
+    ///
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_participate_batch_weight(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_starts_raffle(&mut client, &contract_id).await;
+
+        let tickets: Vec<Vec<Number>> = (1..=10).map(|i| vec![i, i + 10, i + 20, i + 30]).collect();
+
+        // weight of the single participations
+        let mut single_ref_time = 0;
+        for numbers in tickets.iter() {
+            let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.participate(LOTTERY_ID, numbers.clone()));
+            let result = client
+                .call_dry_run(&ink_e2e::dave(), &participate, 0, None)
+                .await;
+            assert_eq!(Ok(()), result.return_value());
+            single_ref_time += result.exec_result.gas_consumed.ref_time();
+        }
+
+        // weight of the batch participation
+        let participate_batch = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_batch(LOTTERY_ID, tickets.clone()));
+        let result = client
+            .call_dry_run(&ink_e2e::dave(), &participate_batch, 0, None)
+            .await;
+        assert_eq!(Ok(()), result.return_value());
+        let batch_ref_time = result.exec_result.gas_consumed.ref_time();
+
+        assert!(batch_ref_time < single_ref_time);
+
+        // only one event is emitted for the batch
+        let participate_batch = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_batch(LOTTERY_ID, tickets.clone()));
+        let result = client
+            .call(&ink_e2e::dave(), participate_batch, 0, None)
+            .await
+            .expect("participate batch failed");
+        let nb_events = result
+            .events
+            .iter()
+            .filter(|e| {
+                e.as_ref()
+                    .map(|e| {
+                        e.pallet_name() == "Contracts" && e.variant_name() == "ContractEmitted"
+                    })
+                    .unwrap_or(false)
+            })
+            .count();
+        assert_eq!(1, nb_events);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_pause(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_starts_raffle(&mut client, &contract_id).await;
+
+        // bob can not pause the contract
+        let pause = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.pause(1));
+        let result = client.call(&ink_e2e::bob(), pause, 0, None).await;
+        assert!(result.is_err(), "only the pauser can pause the contract");
+
+        // alice pauses the contract
+        let pause = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.pause(1));
+        client
+            .call(&ink_e2e::alice(), pause, 0, None)
+            .await
+            .expect("pause failed");
+
+        let paused = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.paused());
+        assert!(client
+            .call_dry_run(&ink_e2e::alice(), &paused, 0, None)
+            .await
+            .return_value());
+
+        // no participation when the contract is paused
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(LOTTERY_ID, vec![5, 40, 8, 2]));
+        let result = client.call(&ink_e2e::dave(), participate, 0, None).await;
+        assert!(result.is_err(), "the contract is paused");
+
+        let participate_batch = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_batch(LOTTERY_ID, vec![vec![5, 40, 8, 2]]));
+        let result = client
+            .call(&ink_e2e::dave(), participate_batch, 0, None)
+            .await;
+        assert!(result.is_err(), "the contract is paused");
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let participate_for = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| {
+                contract.participate_for(LOTTERY_ID, charlie_address, vec![5, 40, 8, 2])
+            });
+        let result = client
+            .call(&ink_e2e::dave(), participate_for, 0, None)
+            .await;
+        assert!(result.is_err(), "the contract is paused");
+
+        let participate_with_referrer =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.participate_with_referrer(LOTTERY_ID, vec![5, 40, 8, 2], charlie_address)
+            });
+        let result = client
+            .call(&ink_e2e::dave(), participate_with_referrer, 0, None)
+            .await;
+        assert!(result.is_err(), "the contract is paused");
+
+        // the raffle can not be completed
+        let complete_raffle = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.complete_raffle(LOTTERY_ID));
+        let result = client
+            .call(&ink_e2e::alice(), complete_raffle, 0, None)
+            .await;
+        assert!(result.is_err(), "the contract is paused");
+
+        // bob can not unpause the contract
+        let unpause = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.unpause(1));
+        let result = client.call(&ink_e2e::bob(), unpause, 0, None).await;
+        assert!(result.is_err(), "only the pauser can unpause the contract");
+
+        // alice unpauses the contract
+        let unpause = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.unpause(1));
+        client
+            .call(&ink_e2e::alice(), unpause, 0, None)
+            .await
+            .expect("unpause failed");
+
+        // dave can participate again
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_timelock(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        let get_min_delay = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_min_delay());
+        assert_eq!(
+            MIN_DELAY,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_min_delay, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the delay can not be zero
+        let set_min_delay = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_min_delay(0));
+        let result = client.call(&ink_e2e::alice(), set_min_delay, 0, None).await;
+        assert!(result.is_err(), "the delay can not be zero");
+
+        // the config is scheduled
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 0,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        let set_config = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_config(LOTTERY_ID, config));
+        let operation_id = client
+            .call(&ink_e2e::alice(), set_config, 0, None)
+            .await
+            .expect("set config failed")
+            .return_value()
+            .expect("operation id not returned");
+
+        let get_config = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_config(LOTTERY_ID));
+        assert_eq!(
+            None,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_config, 0, None)
+                .await
+                .return_value()
+        );
+
+        let get_pending_operations =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_pending_operations(0, 10));
+        let pending_operations = client
+            .call_dry_run(&ink_e2e::alice(), &get_pending_operations, 0, None)
+            .await
+            .return_value();
+        assert_eq!(1, pending_operations.len());
+        assert_eq!(operation_id, pending_operations[0].0);
+        assert_eq!(
+            Operation::SetConfig(LOTTERY_ID, config),
+            pending_operations[0].1.operation
+        );
+
+        // the operation can not be executed before the delay
+        let execute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.execute(operation_id));
+        let result = client.call(&ink_e2e::bob(), execute, 0, None).await;
+        assert!(result.is_err(), "the operation is not ready");
+
+        // when
+        // bob executes the operation after the delay
+        std::thread::sleep(std::time::Duration::from_millis(2 * MIN_DELAY));
+        let execute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.execute(operation_id));
+        client
+            .call(&ink_e2e::bob(), execute, 0, None)
+            .await
+            .expect("execute failed");
+
+        // then
+        assert_eq!(
+            Some(config),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_config, 0, None)
+                .await
+                .return_value()
+        );
+        let pending_operations = client
+            .call_dry_run(&ink_e2e::alice(), &get_pending_operations, 0, None)
+            .await
+            .return_value();
+        assert_eq!(0, pending_operations.len());
+
+        // the executed operation can not be executed again
+        let execute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.execute(operation_id));
+        let result = client.call(&ink_e2e::bob(), execute, 0, None).await;
+        assert!(result.is_err(), "the operation is already executed");
+
+        // the role granted with openbrush is also scheduled
+        let bob_address = ink::primitives::AccountId::from(ink_e2e::bob().public_key().0);
+        let grant_role = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.grant_role(ATTESTOR_ROLE, Some(bob_address)));
+        client
+            .call(&ink_e2e::alice(), grant_role, 0, None)
+            .await
+            .expect("grant role failed");
+
+        let has_role = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.has_role(ATTESTOR_ROLE, Some(bob_address)));
+        assert!(!client
+            .call_dry_run(&ink_e2e::alice(), &has_role, 0, None)
+            .await
+            .return_value());
+
+        // bob can not cancel the operation
+        let operation_id = operation_id + 1;
+        let cancel = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.cancel(operation_id));
+        let result = client.call(&ink_e2e::bob(), cancel, 0, None).await;
+        assert!(result.is_err(), "only the admin can cancel the operation");
+
+        // alice cancels the operation
+        let cancel = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.cancel(operation_id));
+        client
+            .call(&ink_e2e::alice(), cancel, 0, None)
+            .await
+            .expect("cancel failed");
+
+        // the cancelled operation can not be executed
+        std::thread::sleep(std::time::Duration::from_millis(2 * MIN_DELAY));
+        let execute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.execute(operation_id));
+        let result = client.call(&ink_e2e::bob(), execute, 0, None).await;
+        assert!(result.is_err(), "the operation is cancelled");
+
+        assert!(!client
+            .call_dry_run(&ink_e2e::alice(), &has_role, 0, None)
+            .await
+            .return_value());
+
+        Ok(())
+    }
+
+    async fn bob_sends_legacy_response(
+        client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+        contract_id: &AccountId,
+        payload: legacy_contract::LottoResponseMessage,
+    ) {
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send legacy response failed");
+    }
+
+    #[ink_e2e::test(
+        additional_contracts = "contracts/lotto/Cargo.toml integration_tests/legacy/contract/Cargo.toml"
+    )]
+    async fn test_upgrade(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given a contract deployed with the first layout of the storage
+        let contract_id = client
+            .instantiate(
+                "lotto_contract_legacy",
+                &ink_e2e::alice(),
+                legacy_contract::ContractRef::new(),
+                0,
+                None,
+            )
+            .await
+            .expect("instantiate failed")
+            .account_id;
+
+        let set_config =
+            build_message::<legacy_contract::ContractRef>(contract_id.clone()).call(|contract| {
+                contract.set_config(LegacyConfig {
+                    nb_numbers: 4,
+                    min_number: 1,
+                    max_number: 50,
+                })
+            });
+        client
+            .call(&ink_e2e::alice(), set_config, 0, None)
+            .await
+            .expect("set config failed");
+
+        let register_attestor = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| {
+                contract.register_attestor(AccountId::from(ink_e2e::bob().public_key().0))
+            });
+        client
+            .call(&ink_e2e::alice(), register_attestor, 0, None)
+            .await
+            .expect("register attestor failed");
+
+        let fund = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.fund());
+        client
+            .call(&ink_e2e::alice(), fund, 100, None)
+            .await
+            .expect("fund contract failed");
+
+        // dave wins the first raffle
+        let start_raffle = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.start_raffle());
+        let raffle_id = client
+            .call(&ink_e2e::alice(), start_raffle, 0, None)
+            .await
+            .expect("start raffle failed")
+            .return_value()
+            .expect("no raffle id");
+
+        let participate = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(vec![5, 40, 8, 2]));
+        client
+            .call(&ink_e2e::dave(), participate, 0, None)
+            .await
+            .expect("participate failed");
+
+        let complete_raffle = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.complete_raffle());
+        client
+            .call(&ink_e2e::alice(), complete_raffle, 0, None)
+            .await
+            .expect("complete raffle failed");
+
+        let results: Vec<Number> = vec![5, 40, 8, 2];
+        let request = legacy_contract::LottoRequestMessage {
+            raffle_id,
+            request: legacy_contract::Request::DrawNumbers(4, 1, 50),
+        };
+        let payload = legacy_contract::LottoResponseMessage {
+            request,
+            response: legacy_contract::Response::Numbers(results.clone()),
+        };
+        bob_sends_legacy_response(&mut client, &contract_id, payload).await;
+
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let request = legacy_contract::LottoRequestMessage {
+            raffle_id,
+            request: legacy_contract::Request::CheckWinners(results.clone()),
+        };
+        let payload = legacy_contract::LottoResponseMessage {
+            request,
+            response: legacy_contract::Response::Winners(vec![dave_address]),
+        };
+        bob_sends_legacy_response(&mut client, &contract_id, payload).await;
+
+        // upload the new code and upgrade the contract
+        let code_hash = client
+            .upload("lotto_contract", &ink_e2e::alice(), None)
+            .await
+            .expect("upload failed")
+            .code_hash;
+
+        // bob can not upgrade the contract
+        let set_code_hash = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_code_hash(code_hash));
+        let result = client.call(&ink_e2e::bob(), set_code_hash, 0, None).await;
+        assert!(result.is_err(), "only the owner can upgrade the contract");
+
+        let set_code_hash = build_message::<legacy_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_code_hash(code_hash));
+        client
+            .call(&ink_e2e::alice(), set_code_hash, 0, None)
+            .await
+            .expect("set code hash failed");
+
+        // the storage has not been versioned yet
+        let get_storage_version = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_storage_version());
+        assert_eq!(
+            0,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_storage_version, 0, None)
+                .await
+                .return_value()
+        );
+
+        // bob can not migrate the storage
+        let migrate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.migrate());
+        let result = client.call(&ink_e2e::bob(), migrate, 0, None).await;
+        assert!(result.is_err(), "only the admin can migrate the storage");
+
+        let migrate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.migrate());
+        let version = client
+            .call(&ink_e2e::alice(), migrate, 0, None)
+            .await
+            .expect("migrate failed")
+            .return_value()
+            .expect("no storage version");
+        assert_eq!(STORAGE_VERSION, version);
+
+        // the config is decoded with the legacy layout and moved to the default lottery
+        let get_config = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_config(LOTTERY_ID));
+        assert_eq!(
+            Some(Config {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+                ticket_price: 0,
+                max_tickets_per_account: None,
+                max_batch_size: None,
+                max_tickets: None,
+                bonus_pool: None,
+                game_mode: GameMode::Numbers,
+            }),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_config, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the timelock gets a delay
+        let get_min_delay = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_min_delay());
+        assert_eq!(
+            DEFAULT_MIN_DELAY,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_min_delay, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the raffles, results, winners and pending rewards are kept
+        assert_eq!(
+            raffle_id,
+            get_current_raffle_id(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            Status::Closed,
+            get_current_status(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            Some(results),
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            Some(vec![dave_address]),
+            get_winners(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            Some(100),
+            get_pending_rewards_from(&mut client, &contract_id, &dave_address).await
+        );
+
+        // the raffle workflow still works after the upgrade
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+        assert_eq!(2, raffle_id);
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::dave(), 100).await;
+
+        Ok(())
+    }
+
+    async fn get_raffle_info(
+        client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+        contract_id: &AccountId,
+        raffle_id: RaffleId,
+    ) -> Option<RaffleInfo> {
+        let get_raffle_info = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_raffle_info(LOTTERY_ID, raffle_id));
+        client
+            .call_dry_run(&ink_e2e::alice(), &get_raffle_info, 0, None)
+            .await
+            .return_value()
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_raffle_info(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        fund(&mut client, &contract_id, 100).await;
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+        let raffle_info = get_raffle_info(&mut client, &contract_id, raffle_id)
+            .await
+            .expect("no raffle info");
+        assert_eq!(raffle_id, raffle_info.raffle_id);
+        assert_eq!(Status::Ongoing, raffle_info.status);
+        assert_eq!(4, raffle_info.config.nb_numbers);
+        assert_eq!(None, raffle_info.completed_at);
+
+        // dave buys two tickets and charlie one
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![3, 6, 7, 5],
+        )
+        .await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::charlie(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+
+        alice_stops_raffle(&mut client, &contract_id).await;
+        let raffle_info = get_raffle_info(&mut client, &contract_id, raffle_id)
+            .await
+            .expect("no raffle info");
+        assert_eq!(Status::WaitingResults, raffle_info.status);
+        assert_eq!(3, raffle_info.nb_tickets);
+        assert_eq!(2, raffle_info.nb_participants);
+        assert!(raffle_info.completed_at.is_some());
+        assert!(raffle_info.draw_request_id.is_some());
+
+        // dave and charlie win
+        let results: Vec<Number> = vec![5, 40, 8, 2];
+        bob_sends_results(&mut client, &contract_id, raffle_id, results.clone()).await;
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        bob_sends_winners(
+            &mut client,
+            &contract_id,
+            raffle_id,
+            results,
+            vec![dave_address, charlie_address],
+        )
+        .await;
+
+        let raffle_info = get_raffle_info(&mut client, &contract_id, raffle_id)
+            .await
+            .expect("no raffle info");
+        assert_eq!(Status::Closed, raffle_info.status);
+        assert!(raffle_info.results_at.is_some());
+        assert!(raffle_info.winners_at.is_some());
+        assert_eq!(2, raffle_info.nb_winners);
+        assert_eq!(100, raffle_info.pot);
+        assert_eq!(50, raffle_info.prize_per_winner);
+
+        // list the raffles
+        let raffle_id_2 = alice_starts_raffle(&mut client, &contract_id).await;
+        let get_raffles = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_raffles(LOTTERY_ID, 1, 10));
+        let raffles = client
+            .call_dry_run(&ink_e2e::alice(), &get_raffles, 0, None)
+            .await
+            .return_value();
+        assert_eq!(2, raffles.len());
+        assert_eq!(raffle_id, raffles[0].raffle_id);
+        assert_eq!(raffle_id_2, raffles[1].raffle_id);
+        assert_eq!(Status::Ongoing, raffles[1].status);
+
+        let get_raffles = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_raffles(LOTTERY_ID, 2, 1));
+        let raffles = client
+            .call_dry_run(&ink_e2e::alice(), &get_raffles, 0, None)
+            .await
+            .return_value();
+        assert_eq!(1, raffles.len());
+        assert_eq!(raffle_id_2, raffles[0].raffle_id);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_statistics(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+        // only the manager can enable the tracking of the picked numbers
+        let set_picks_tracking = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_picks_tracking(true));
+        let result = client
+            .call(&ink_e2e::bob(), set_picks_tracking, 0, None)
+            .await;
+        assert!(result.is_err(), "only the manager can enable the tracking");
+
+        let set_picks_tracking = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_picks_tracking(true));
+        client
+            .call(&ink_e2e::alice(), set_picks_tracking, 0, None)
+            .await
+            .expect("set picks tracking failed");
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::charlie(),
+            vec![5, 6, 7, 8],
+        )
+        .await;
+
+        let get_picked_frequencies =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_picked_frequencies(LOTTERY_ID, raffle_id, 5, 4));
+        assert_eq!(
+            vec![(5, 2), (6, 1), (7, 1), (8, 2)],
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_picked_frequencies, 0, None)
+                .await
+                .return_value()
+        );
+
+        // count the drawn numbers
+        alice_stops_raffle(&mut client, &contract_id).await;
+        let results: Vec<Number> = vec![5, 40, 8, 2];
+        bob_sends_results(&mut client, &contract_id, raffle_id, results).await;
+
+        let get_nb_draws = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_nb_draws(LOTTERY_ID));
+        assert_eq!(
+            1,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_nb_draws, 0, None)
+                .await
+                .return_value()
+        );
+
+        let get_drawn_frequencies =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_drawn_frequencies(LOTTERY_ID, 1, 5));
+        assert_eq!(
+            vec![(1, 0), (2, 1), (3, 0), (4, 0), (5, 1)],
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_drawn_frequencies, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the page is bounded
+        let get_drawn_frequencies =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_drawn_frequencies(LOTTERY_ID, 1, u16::MAX));
+        let frequencies = client
+            .call_dry_run(&ink_e2e::alice(), &get_drawn_frequencies, 0, None)
+            .await
+            .return_value();
+        assert_eq!(
+            lotto::traits::statistics::MAX_NUMBERS_BY_PAGE as usize,
+            frequencies.len()
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_multiple_lotteries(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        fund(&mut client, &contract_id, 100).await;
+
+        // a second lottery with another config
+        let other_lottery_id: LotteryId = 1;
+        let config = Config {
+            nb_numbers: 5,
+            min_number: 1,
+            max_number: 20,
+            ticket_price: 0,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, other_lottery_id, config).await;
+
+        let get_lottery_ids = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_lottery_ids());
+        assert_eq!(
+            vec![LOTTERY_ID, other_lottery_id],
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_lottery_ids, 0, None)
+                .await
+                .return_value()
+        );
+
+        let fund_lottery = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.fund(other_lottery_id));
+        client
+            .call(&ink_e2e::alice(), fund_lottery, 50, None)
+            .await
+            .expect("fund lottery failed");
+
+        // each lottery has its own raffle sequence
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+        let start_raffle = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.start_raffle(other_lottery_id));
+        let other_raffle_id = client
+            .call(&ink_e2e::alice(), start_raffle, 0, None)
+            .await
+            .expect("start raffle failed")
+            .return_value()
+            .expect("no value");
+        assert_eq!(1, raffle_id);
+        assert_eq!(1, other_raffle_id);
+
+        // the numbers must respect the config of the lottery
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(other_lottery_id, vec![5, 40, 8, 2]));
+        let result = client.call(&ink_e2e::charlie(), participate, 0, None).await;
+        assert!(result.is_err(), "the numbers do not respect the config");
+
+        let other_results: Vec<Number> = vec![5, 12, 8, 2, 17];
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(other_lottery_id, other_results.clone()));
+        client
+            .call(&ink_e2e::charlie(), participate, 0, None)
+            .await
+            .expect("Participate failed");
+
+        // complete only the second lottery
+        let complete_raffle = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.complete_raffle(other_lottery_id));
+        client
+            .call(&ink_e2e::alice(), complete_raffle, 0, None)
+            .await
+            .expect("complete raffle failed");
+
+        let request = LottoRequestMessage::new(
+            other_lottery_id,
+            other_raffle_id,
+            Request::DrawNumbers(5, 1, 20, None),
+        );
+        let payload = LottoResponseMessage::new(request, Response::Numbers(other_results.clone()));
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let request = LottoRequestMessage::new(
+            other_lottery_id,
+            other_raffle_id,
+            Request::CheckWinners(other_results.clone(), vec![]),
+        );
+        let winners_payload =
+            LottoResponseMessage::new(request, Response::Winners(vec![charlie_address]));
+        let actions = vec![
+            HandleActionInput::Reply(payload.encode()),
+            HandleActionInput::Reply(winners_payload.encode()),
+        ];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results and winners failed");
+
+        // the winner of the second lottery shares only the pot of this lottery
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::charlie(), 50).await;
+
+        let get_pot = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_pot(LOTTERY_ID));
+        assert_eq!(
+            100,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_pot, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the first lottery is still open
+        assert_eq!(
+            Status::Ongoing,
+            get_current_status(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            None,
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+
+        let get_results = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_results(other_lottery_id, other_raffle_id));
+        assert_eq!(
+            Some(other_results),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_results, 0, None)
+                .await
+                .return_value()
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_bonus_pool(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+        // 4 numbers between 1 and 50 and 2 bonus numbers between 1 and 10
+        let bonus_pool = BonusPool {
+            nb_numbers: 2,
+            min_number: 1,
+            max_number: 10,
+        };
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 0,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: Some(bonus_pool),
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+        fund(&mut client, &contract_id, 100).await;
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // the ticket must have the bonus numbers
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(LOTTERY_ID, vec![5, 40, 8, 2]));
+        let result = client.call(&ink_e2e::dave(), participate, 0, None).await;
+        assert!(result.is_err(), "the bonus numbers are missing");
+
+        // the bonus numbers must be in the bonus pool
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(LOTTERY_ID, vec![5, 40, 8, 2, 3, 40]));
+        let result = client.call(&ink_e2e::dave(), participate, 0, None).await;
+        assert!(result.is_err(), "the bonus number is out of the bonus pool");
+
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2, 3, 7],
+        )
+        .await;
+        alice_stops_raffle(&mut client, &contract_id).await;
+
+        // the phat contract draws both pools
+        let results: Vec<Number> = vec![5, 40, 8, 2, 3, 7];
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::DrawNumbers(4, 1, 50, Some(bonus_pool)),
+        );
+        let payload = LottoResponseMessage::new(request, Response::Numbers(results.clone()));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results failed");
+        assert_eq!(
+            Some(results),
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+
+        // the winners are checked with the main numbers and the bonus numbers
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::CheckWinners(vec![5, 40, 8, 2], vec![3, 7]),
+        );
+        let payload = LottoResponseMessage::new(request, Response::Winners(vec![dave_address]));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send winners failed");
+
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::dave(), 100).await;
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_prize_tiers(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+        // 4 numbers between 1 and 50 and 1 bonus number between 1 and 10
+        let bonus_pool = BonusPool {
+            nb_numbers: 1,
+            min_number: 1,
+            max_number: 10,
+        };
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+            ticket_price: 0,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: Some(bonus_pool),
+            game_mode: GameMode::Numbers,
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        // 50% for 4 + 1, 20% for 4 + 0 and 10% for 3 + 1
+        let prize_tiers = vec![
+            PrizeTier {
+                nb_main_matches: 4,
+                nb_bonus_matches: 1,
+                share: 5_000,
+            },
+            PrizeTier {
+                nb_main_matches: 4,
+                nb_bonus_matches: 0,
+                share: 2_000,
+            },
+            PrizeTier {
+                nb_main_matches: 3,
+                nb_bonus_matches: 1,
+                share: 1_000,
+            },
+        ];
+
+        // the tiers can not have more matches than the numbers drawn
+        let set_prize_tiers = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| {
+                contract.set_prize_tiers(
+                    LOTTERY_ID,
+                    vec![PrizeTier {
+                        nb_main_matches: 5,
+                        nb_bonus_matches: 0,
+                        share: 1_000,
+                    }],
+                )
+            });
+        client
+            .call(&ink_e2e::alice(), set_prize_tiers, 0, None)
+            .await
+            .expect("schedule prize tiers failed");
+        let get_next_operation_id =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_next_operation_id());
+        let operation_id = client
+            .call_dry_run(&ink_e2e::alice(), &get_next_operation_id, 0, None)
+            .await
+            .return_value()
+            - 1;
+        std::thread::sleep(std::time::Duration::from_millis(2 * MIN_DELAY));
+        let execute = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.execute(operation_id));
+        let result = client
+            .call_dry_run(&ink_e2e::alice(), &execute, 0, None)
+            .await
+            .return_value();
+        assert_eq!(
+            Err(lotto_contract::ContractError::RaffleError(
+                RaffleError::IncorrectPrizeTiers
+            )),
+            result
+        );
+
+        let set_prize_tiers = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_prize_tiers(LOTTERY_ID, prize_tiers.clone()));
+        client
+            .call(&ink_e2e::alice(), set_prize_tiers, 0, None)
+            .await
+            .expect("schedule prize tiers failed");
+        execute_last_operation(&mut client, &contract_id).await;
+
+        let get_prize_tiers = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_prize_tiers(LOTTERY_ID));
+        assert_eq!(
+            prize_tiers,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_prize_tiers, 0, None)
+                .await
+                .return_value()
+        );
+
+        fund(&mut client, &contract_id, 100).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2, 7],
+        )
+        .await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::charlie(),
+            vec![5, 40, 8, 2, 3],
+        )
+        .await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::eve(),
+            vec![5, 40, 8, 2, 9],
+        )
+        .await;
+        alice_stops_raffle(&mut client, &contract_id).await;
+
+        // the phat contract draws both pools
+        let results: Vec<Number> = vec![5, 40, 8, 2, 7];
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::DrawNumbers(4, 1, 50, Some(bonus_pool)),
+        );
+        let payload = LottoResponseMessage::new(request, Response::Numbers(results.clone()));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results failed");
+
+        // the phat contract sends the winners of each tier
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let eve_address = ink::primitives::AccountId::from(ink_e2e::eve().public_key().0);
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::CheckTierWinners(vec![5, 40, 8, 2], vec![7], vec![(4, 1), (4, 0), (3, 1)]),
+        );
+        let payload = LottoResponseMessage::new(
+            request,
+            Response::TierWinners(vec![
+                vec![dave_address],
+                vec![charlie_address, eve_address],
+                vec![],
+            ]),
+        );
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send tier winners failed");
+
+        assert_eq!(
+            vec![dave_address, charlie_address, eve_address],
+            get_winners(&mut client, &contract_id, raffle_id)
+                .await
+                .expect("no winners")
+        );
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::dave(), 50).await;
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::charlie(), 10).await;
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::eve(), 10).await;
+
+        // the share of the tier without winner stays in the pot
+        let get_pot = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_pot(LOTTERY_ID));
+        assert_eq!(
+            30,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_pot, 0, None)
+                .await
+                .return_value()
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_classic_raffle(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+        // classic raffle with 2 winning tickets, no number is picked
+        let config = Config {
+            nb_numbers: 0,
+            min_number: 0,
+            max_number: 0,
+            ticket_price: 0,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Tickets(2),
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+        fund(&mut client, &contract_id, 100).await;
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // no participation with numbers
+        let participate = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate(LOTTERY_ID, vec![5, 40, 8, 2]));
+        let result = client.call(&ink_e2e::dave(), participate, 0, None).await;
+        assert!(result.is_err(), "the players do not pick numbers");
+
+        // dave buys the tickets 0, 1 and 2 and charlie the ticket 3
+        let participate_tickets = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_tickets(LOTTERY_ID, 3));
+        let first_ticket = client
+            .call(&ink_e2e::dave(), participate_tickets, 0, None)
+            .await
+            .expect("participate tickets failed")
+            .return_value()
+            .expect("no value");
+        assert_eq!(0, first_ticket);
+
+        let participate_tickets = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_tickets(LOTTERY_ID, 1));
+        let first_ticket = client
+            .call(&ink_e2e::charlie(), participate_tickets, 0, None)
+            .await
+            .expect("participate tickets failed")
+            .return_value()
+            .expect("no value");
+        assert_eq!(3, first_ticket);
+
+        // the owners of the tickets are known on chain
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        for (ticket, owner) in [
+            (0, Some(dave_address)),
+            (2, Some(dave_address)),
+            (3, Some(charlie_address)),
+            (4, None),
+        ] {
+            let get_ticket_owner =
+                build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                    .call(|contract| contract.get_ticket_owner(LOTTERY_ID, raffle_id, ticket));
+            assert_eq!(
+                owner,
+                client
+                    .call_dry_run(&ink_e2e::alice(), &get_ticket_owner, 0, None)
+                    .await
+                    .return_value()
+            );
+        }
+
+        alice_stops_raffle(&mut client, &contract_id).await;
+
+        // the phat contract draws 2 tickets among 4, the winners are resolved without indexer
+        let winning_tickets: Vec<TicketIndex> = vec![1, 3];
+        let request = LottoRequestMessage::new(LOTTERY_ID, raffle_id, Request::DrawTickets(2, 4));
+        let payload =
+            LottoResponseMessage::new(request, Response::Tickets(winning_tickets.clone()));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send winning tickets failed");
+
+        let get_winning_tickets = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_winning_tickets(LOTTERY_ID, raffle_id));
+        assert_eq!(
+            Some(winning_tickets),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_winning_tickets, 0, None)
+                .await
+                .return_value()
+        );
+        assert_eq!(
+            Some(vec![dave_address, charlie_address]),
+            get_winners(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            Status::Closed,
+            get_current_status(&mut client, &contract_id).await
+        );
+
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::dave(), 50).await;
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::charlie(), 50).await;
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_keno(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+        // 20 numbers drawn between 1 and 80, the players pick up to 10 numbers
+        let config = Config {
+            nb_numbers: 20,
+            min_number: 1,
+            max_number: 80,
+            ticket_price: 1,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: Some(100),
+            bonus_pool: None,
+            game_mode: GameMode::Keno(10),
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+
+        // paytables for 1 and 2 picks
+        for (nb_picks, multipliers) in [(1, vec![0, 3]), (2, vec![0, 1, 10])] {
+            let set_paytable = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.set_paytable(LOTTERY_ID, nb_picks, multipliers.clone()));
+            client
+                .call(&ink_e2e::alice(), set_paytable, 0, None)
+                .await
+                .expect("set paytable failed");
+            execute_last_operation(&mut client, &contract_id).await;
+        }
+        fund(&mut client, &contract_id, 100).await;
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // no paytable for 3 picks
+        let participate_keno = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_keno(LOTTERY_ID, vec![5, 40, 8]));
+        let result = client
+            .call(&ink_e2e::dave(), participate_keno, 10, None)
+            .await;
+        assert!(result.is_err(), "no paytable for 3 picks");
+
+        // a number can be picked only once
+        let participate_keno = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_keno(LOTTERY_ID, vec![5, 5]));
+        let result = client
+            .call(&ink_e2e::dave(), participate_keno, 10, None)
+            .await;
+        assert!(result.is_err(), "the same number is picked twice");
+
+        // dave stakes 10 on 2 numbers: the max prize is 100 and the reserve is 110
+        let participate_keno = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_keno(LOTTERY_ID, vec![5, 40]));
+        let ticket = client
+            .call(&ink_e2e::dave(), participate_keno, 10, None)
+            .await
+            .expect("participate keno failed")
+            .return_value()
+            .expect("no value");
+        assert_eq!(0, ticket);
+
+        // the reserve can not cover another max prize of 50
+        let participate_keno = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_keno(LOTTERY_ID, vec![5, 6]));
+        let result = client
+            .call(&ink_e2e::charlie(), participate_keno, 5, None)
+            .await;
+        assert!(result.is_err(), "the reserve is insufficient");
+
+        // charlie stakes 2 on 1 number: the max prize is 6
+        let participate_keno = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_keno(LOTTERY_ID, vec![7]));
+        let ticket = client
+            .call(&ink_e2e::charlie(), participate_keno, 2, None)
+            .await
+            .expect("participate keno failed")
+            .return_value()
+            .expect("no value");
+        assert_eq!(1, ticket);
+
+        let get_liability = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_liability(LOTTERY_ID));
+        assert_eq!(
+            106,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_liability, 0, None)
+                .await
+                .return_value()
+        );
+
+        alice_stops_raffle(&mut client, &contract_id).await;
+
+        // the drawn numbers contain 5 and 40 but not 7
+        let results: Vec<Number> = vec![
+            5, 40, 1, 2, 3, 4, 6, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+        ];
+        let request =
+            LottoRequestMessage::new(LOTTERY_ID, raffle_id, Request::DrawNumbers(20, 1, 80, None));
+        let payload = LottoResponseMessage::new(request, Response::Numbers(results.clone()));
+
+        // the phat contract counts the matches of each ticket
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::CheckKenoWinners(results.clone()),
+        );
+        let matches_payload =
+            LottoResponseMessage::new(request, Response::KenoMatches(vec![(0, 2), (1, 0)]));
+        let actions = vec![
+            HandleActionInput::Reply(payload.encode()),
+            HandleActionInput::Reply(matches_payload.encode()),
+        ];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results and matches failed");
+
+        // dave wins 10 times his stake, charlie wins nothing
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        assert_eq!(
+            Some(vec![dave_address]),
+            get_winners(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            Status::Closed,
+            get_current_status(&mut client, &contract_id).await
+        );
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::dave(), 100).await;
+
+        // the tickets are settled and the prize is paid with the reserve
+        assert_eq!(
+            0,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_liability, 0, None)
+                .await
+                .return_value()
+        );
+        let get_pot = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_pot(LOTTERY_ID));
+        assert_eq!(
+            12,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_pot, 0, None)
+                .await
+                .return_value()
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_message_rejected(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+        alice_stops_raffle(&mut client, &contract_id).await;
+
+        // bob sends the results of another raffle => the message is rejected but not reverted
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id + 1,
+            Request::DrawNumbers(4, 1, 50, None),
+        );
+        let payload = LottoResponseMessage::new(request, Response::Numbers(vec![5, 40, 8, 2]));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        let result = client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("the rejected message should not revert the transaction");
+        // events : MessageProcessedTo and MessageRejected
+        assert!(result.contains_event("Contracts", "ContractEmitted"));
+
+        // the raffle still waits the results
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            None,
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+
+        // the reason is saved for the raffle of the message
+        let get_message_rejection =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_message_rejection(LOTTERY_ID, raffle_id + 1));
+        assert_eq!(
+            Some(lotto_contract::ContractError::RaffleError(
+                RaffleError::IncorrectRaffle
+            )),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_message_rejection, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the phat contract fails to answer the request => the error is saved as reason
+        let request =
+            LottoRequestMessage::new(LOTTERY_ID, raffle_id, Request::DrawNumbers(4, 1, 50, None));
+        let payload = LottoResponseMessage::new(request, Response::Error(vec![10]));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("the error response should not revert the transaction");
+        let get_message_rejection =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_message_rejection(LOTTERY_ID, raffle_id));
+        assert_eq!(
+            Some(lotto_contract::ContractError::OffchainError(vec![10])),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_message_rejection, 0, None)
+                .await
+                .return_value()
+        );
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+
+        // the request to draw the numbers is at the head of the queue
+        let queue_head_key = [QUEUE_PREFIX, QUEUE_HEAD_KEY].concat();
+        let get_queue_head = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(queue_head_key.clone()));
+        let queue_head = client
+            .call_dry_run(&ink_e2e::alice(), &get_queue_head, 0, None)
+            .await
+            .return_value();
+        let head = queue_head
+            .as_ref()
+            .map(|value| u32::decode(&mut &value[..]).unwrap())
+            .unwrap_or_default();
+        let request =
+            LottoRequestMessage::new(LOTTERY_ID, raffle_id, Request::DrawNumbers(4, 1, 50, None));
+
+        // bob pops the request with numbers out of range => the response is rejected
+        let payload =
+            LottoResponseMessage::new(request.clone(), Response::Numbers(vec![5, 40, 8, 200]));
+        let conditions = vec![(queue_head_key.clone(), queue_head)];
+        let actions = vec![
+            HandleActionInput::Reply(payload.encode()),
+            HandleActionInput::SetQueueHead(head + 1),
+        ];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(conditions.clone(), vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("the rejected message should not revert the transaction");
+
+        // nothing is saved and the reason is readable
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            None,
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+        let get_message_rejection =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_message_rejection(LOTTERY_ID, raffle_id));
+        assert_eq!(
+            Some(lotto_contract::ContractError::RaffleError(
+                RaffleError::IncorrectNumbers
+            )),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_message_rejection, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the popped request is queued again
+        let queue_head = client
+            .call_dry_run(&ink_e2e::alice(), &get_queue_head, 0, None)
+            .await
+            .return_value();
+        assert_eq!(Some((head + 1).encode()), queue_head);
+        let queue_tail_key = [QUEUE_PREFIX, QUEUE_TAIL_KEY].concat();
+        let get_queue_tail = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(queue_tail_key.clone()));
+        let tail = client
+            .call_dry_run(&ink_e2e::alice(), &get_queue_tail, 0, None)
+            .await
+            .return_value()
+            .map(|value| u32::decode(&mut &value[..]).unwrap())
+            .expect("no queue tail");
+        let queue_key = [QUEUE_PREFIX, &(tail - 1).encode()].concat();
+        let get_queued_request = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(queue_key.clone()));
+        let queued_request = client
+            .call_dry_run(&ink_e2e::alice(), &get_queued_request, 0, None)
+            .await
+            .return_value()
+            .map(|value| LottoRequestMessage::decode(&mut &value[..]).unwrap());
+        assert_eq!(Some(request.clone()), queued_request);
+        // the raffle refers to the new request
+        let raffle_info = get_raffle_info(&mut client, &contract_id, raffle_id)
+            .await
+            .expect("no raffle info");
+        assert_eq!(Some(tail - 1), raffle_info.draw_request_id);
+
+        // bob pops the request again with the correct numbers
+        let payload = LottoResponseMessage::new(request, Response::Numbers(vec![5, 40, 8, 2]));
+        let conditions = vec![(queue_head_key.clone(), queue_head)];
+        let actions = vec![
+            HandleActionInput::Reply(payload.encode()),
+            HandleActionInput::SetQueueHead(head + 2),
+        ];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(conditions.clone(), vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results failed");
+        assert_eq!(
+            Status::WaitingWinners,
+            get_current_status(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            Some(vec![5, 40, 8, 2]),
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+
+        // the raffle can still finish
+        bob_sends_winners(
+            &mut client,
+            &contract_id,
+            raffle_id,
+            vec![5, 40, 8, 2],
+            vec![],
+        )
+        .await;
+        assert_eq!(
+            Some(vec![]),
+            get_winners(&mut client, &contract_id, raffle_id).await
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_request_dropped(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+        alice_stops_raffle(&mut client, &contract_id).await;
+
+        let queue_tail_key = [QUEUE_PREFIX, QUEUE_TAIL_KEY].concat();
+        let get_queue_tail = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(queue_tail_key.clone()));
+        let tail = client
+            .call_dry_run(&ink_e2e::alice(), &get_queue_tail, 0, None)
+            .await
+            .return_value()
+            .map(|value| u32::decode(&mut &value[..]).unwrap())
+            .expect("no queue tail");
+
+        // when
+        // the phat contract fails 4 times to draw the numbers
+        let request =
+            LottoRequestMessage::new(LOTTERY_ID, raffle_id, Request::DrawNumbers(4, 1, 50, None));
+        let payload = LottoResponseMessage::new(request.clone(), Response::Error(vec![10]));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        for _ in 0..4 {
+            let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+            client
+                .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+                .await
+                .expect("the error response should not revert the transaction");
+        }
+
+        // then
+        // the request is queued again 3 times only
+        let new_tail = client
+            .call_dry_run(&ink_e2e::alice(), &get_queue_tail, 0, None)
+            .await
+            .return_value()
+            .map(|value| u32::decode(&mut &value[..]).unwrap())
+            .expect("no queue tail");
+        assert_eq!(tail + 3, new_tail);
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+
+        // only the manager queues the dropped request again
+        let requeue_dropped_request =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.requeue_dropped_request(LOTTERY_ID, raffle_id));
+        let result = client
+            .call(&ink_e2e::dave(), requeue_dropped_request, 0, None)
+            .await;
+        assert!(
+            result.is_err(),
+            "only the manager can queue the request again"
+        );
+
+        let requeue_dropped_request =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.requeue_dropped_request(LOTTERY_ID, raffle_id));
+        client
+            .call(&ink_e2e::alice(), requeue_dropped_request, 0, None)
+            .await
+            .expect("requeue dropped request failed");
+
+        let queue_key = [QUEUE_PREFIX, &new_tail.encode()].concat();
+        let get_queued_request = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(queue_key.clone()));
+        let queued_request = client
+            .call_dry_run(&ink_e2e::alice(), &get_queued_request, 0, None)
+            .await
+            .return_value()
+            .map(|value| LottoRequestMessage::decode(&mut &value[..]).unwrap());
+        assert_eq!(Some(request), queued_request);
+        let raffle_info = get_raffle_info(&mut client, &contract_id, raffle_id)
+            .await
+            .expect("no raffle info");
+        assert_eq!(Some(new_tail), raffle_info.draw_request_id);
+
+        // the request is not dropped anymore
+        let requeue_dropped_request =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.requeue_dropped_request(LOTTERY_ID, raffle_id));
+        let result = client
+            .call(&ink_e2e::alice(), requeue_dropped_request, 0, None)
+            .await;
+        assert!(result.is_err(), "no request to queue again");
+
+        // the raffle can still finish
+        bob_sends_results(&mut client, &contract_id, raffle_id, vec![5, 40, 8, 2]).await;
+        assert_eq!(
+            Status::WaitingWinners,
+            get_current_status(&mut client, &contract_id).await
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_draw_and_resolve(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+        alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+        fund(&mut client, &contract_id, 100).await;
+
+        // only the manager requests the numbers and the winners in one round trip
+        let set_draw_and_resolve =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.set_draw_and_resolve(LOTTERY_ID, true));
+        let result = client
+            .call(&ink_e2e::dave(), set_draw_and_resolve, 0, None)
+            .await;
+        assert!(result.is_err(), "dave is not the manager");
+
+        let set_draw_and_resolve =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.set_draw_and_resolve(LOTTERY_ID, true));
+        client
+            .call(&ink_e2e::alice(), set_draw_and_resolve, 0, None)
+            .await
+            .expect("set draw and resolve failed");
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::charlie(),
+            vec![3, 6, 7, 5],
+        )
+        .await;
+        alice_stops_raffle(&mut client, &contract_id).await;
+
+        // a combined response to another request is rejected
+        let request =
+            LottoRequestMessage::new(LOTTERY_ID, raffle_id, Request::DrawNumbers(4, 1, 50, None));
+        let payload =
+            LottoResponseMessage::new(request, Response::NumbersAndWinners(vec![], vec![]));
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results failed");
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+        let get_message_rejection =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_message_rejection(LOTTERY_ID, raffle_id));
+        assert_eq!(
+            Some(lotto_contract::ContractError::UnexpectedResponse),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_message_rejection, 0, None)
+                .await
+                .return_value()
+        );
+
+        // a combined response with wrong numbers is rejected, neither the numbers nor the
+        // winners are saved
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::DrawAndResolve(4, 1, 50, None),
+        );
+        let payload = LottoResponseMessage::new(
+            request,
+            Response::NumbersAndWinners(vec![5, 40, 8, 200], vec![dave_address]),
+        );
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results and winners failed");
+        assert_eq!(
+            Status::WaitingResults,
+            get_current_status(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            None,
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            None,
+            get_winners(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            Some(lotto_contract::ContractError::RaffleError(
+                RaffleError::IncorrectNumbers
+            )),
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_message_rejection, 0, None)
+                .await
+                .return_value()
+        );
+
+        // the phat contract sends the numbers and the winners in one response
+        let request = LottoRequestMessage::new(
+            LOTTERY_ID,
+            raffle_id,
+            Request::DrawAndResolve(4, 1, 50, None),
+        );
+        let payload = LottoResponseMessage::new(
+            request,
+            Response::NumbersAndWinners(vec![5, 40, 8, 2], vec![dave_address]),
+        );
+        let actions = vec![HandleActionInput::Reply(payload.encode())];
+        let rollup_cond_eq = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .expect("send results and winners failed");
+
+        // both steps are applied
+        assert_eq!(
+            Some(vec![5, 40, 8, 2]),
+            get_results(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            Some(vec![dave_address]),
+            get_winners(&mut client, &contract_id, raffle_id).await
+        );
+        assert_eq!(
+            Status::Closed,
+            get_current_status(&mut client, &contract_id).await
+        );
+        assert_eq!(
+            Some(raffle_id),
+            get_last_raffle_for_verif(&mut client, &contract_id).await
+        );
+        check_and_claim_rewards(&mut client, &contract_id, &ink_e2e::dave(), 100).await;
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_onchain_participations(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+        alice_configures_contract(&mut client, &contract_id).await;
+
+        // only the manager saves the participations on chain
+        let set_onchain_participations =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.set_onchain_participations(LOTTERY_ID, true));
+        let result = client
+            .call(&ink_e2e::dave(), set_onchain_participations, 0, None)
+            .await;
+        assert!(result.is_err(), "dave is not the manager");
+
+        let set_onchain_participations =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.set_onchain_participations(LOTTERY_ID, true));
+        client
+            .call(&ink_e2e::alice(), set_onchain_participations, 0, None)
+            .await
+            .expect("set onchain participations failed");
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        // the flag can not change while the participations are open
+        let set_onchain_participations =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.set_onchain_participations(LOTTERY_ID, false));
+        let result = client
+            .call(&ink_e2e::alice(), set_onchain_participations, 0, None)
+            .await;
+        assert!(result.is_err(), "the raffle is ongoing");
+
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::dave(),
+            vec![5, 40, 8, 2],
+        )
+        .await;
+        participates(
+            &mut client,
+            &contract_id,
+            &ink_e2e::charlie(),
+            vec![3, 6, 7, 5],
+        )
+        .await;
+
+        // the participations are readable by the offchain rollup in the kv store
+        const PARTICIPATION: u32 = ink::selector_id!("PARTICIPATION");
+        const NB_PARTICIPATIONS: u32 = ink::selector_id!("NB_PARTICIPATIONS");
+
+        let key = (NB_PARTICIPATIONS, LOTTERY_ID, raffle_id).encode();
+        let get_value = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(key.clone()));
+        let nb_participations = client
+            .call_dry_run(&ink_e2e::alice(), &get_value, 0, None)
+            .await
+            .return_value()
+            .map(|value| u32::decode(&mut &value[..]).unwrap());
+        assert_eq!(Some(2), nb_participations);
+
+        let key = (PARTICIPATION, LOTTERY_ID, raffle_id, 1u32).encode();
+        let get_value = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(key.clone()));
+        let participation = client
+            .call_dry_run(&ink_e2e::alice(), &get_value, 0, None)
+            .await
+            .return_value()
+            .map(|value| <(AccountId, Vec<Number>, Vec<Number>)>::decode(&mut &value[..]).unwrap());
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        assert_eq!(
+            Some((charlie_address, vec![3, 6, 7, 5], vec![])),
+            participation
+        );
+
+        // the offchain rollup reads only the participations counted
+        let key = (PARTICIPATION, LOTTERY_ID, raffle_id, 2u32).encode();
+        let get_value = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_value(key.clone()));
+        assert_eq!(
+            None,
+            client
+                .call_dry_run(&ink_e2e::alice(), &get_value, 0, None)
+                .await
+                .return_value()
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test(additional_contracts = "contracts/lotto/Cargo.toml")]
+    async fn test_onchain_keno_tickets(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let contract_id = alice_instantiates_contract(&mut client).await;
+
+        // 20 numbers drawn between 1 and 80, the players pick up to 10 numbers
+        let config = Config {
+            nb_numbers: 20,
+            min_number: 1,
+            max_number: 80,
+            ticket_price: 1,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: Some(100),
+            bonus_pool: None,
+            game_mode: GameMode::Keno(10),
+        };
+        alice_sets_config(&mut client, &contract_id, LOTTERY_ID, config).await;
+        let set_paytable = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_paytable(LOTTERY_ID, 2, vec![0, 1, 10]));
+        client
+            .call(&ink_e2e::alice(), set_paytable, 0, None)
+            .await
+            .expect("set paytable failed");
+        execute_last_operation(&mut client, &contract_id).await;
+        fund(&mut client, &contract_id, 100).await;
+
+        let set_onchain_participations =
+            build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.set_onchain_participations(LOTTERY_ID, true));
+        client
+            .call(&ink_e2e::alice(), set_onchain_participations, 0, None)
+            .await
+            .expect("set onchain participations failed");
+
+        let raffle_id = alice_starts_raffle(&mut client, &contract_id).await;
+
+        for (signer, numbers) in [
+            (ink_e2e::dave(), vec![5, 40]),
+            (ink_e2e::charlie(), vec![3, 6]),
+        ] {
+            let participate_keno =
+                build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                    .call(|contract| contract.participate_keno(LOTTERY_ID, numbers.clone()));
+            client
+                .call(&signer, participate_keno, 2, None)
+                .await
+                .expect("participate keno failed");
+        }
+
+        // the keno tickets are readable by the offchain rollup in the kv store,
+        // with consecutive indexes from 0
+        const KENO_TICKET: u32 = ink::selector_id!("KENO_TICKET");
+
+        let mut tickets = Vec::new();
+        for ticket in 0..lotto_contract::MAX_ONCHAIN_PARTICIPATIONS {
+            let key = (KENO_TICKET, LOTTERY_ID, raffle_id, ticket).encode();
+            let get_value = build_message::<lotto_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.get_value(key.clone()));
+            let numbers = client
+                .call_dry_run(&ink_e2e::alice(), &get_value, 0, None)
+                .await
+                .return_value()
+                .map(|value| Vec::<Number>::decode(&mut &value[..]).unwrap());
+            match numbers {
+                Some(numbers) => tickets.push((ticket, numbers)),
+                None => break,
+            }
+        }
+        assert_eq!(vec![(0, vec![5, 40]), (1, vec![3, 6])], tickets);
+
+        Ok(())
+    }
 }
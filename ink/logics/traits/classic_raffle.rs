@@ -0,0 +1,170 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use crate::traits::{LotteryId, RaffleId, TicketIndex};
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::{AccountId, Storage};
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    /// number of tickets sold for a raffle (ie the index of the next ticket)
+    nb_tickets: Mapping<(LotteryId, RaffleId), TicketIndex>,
+    /// number of ranges of tickets for a raffle
+    nb_ranges: Mapping<(LotteryId, RaffleId), u32>,
+    /// ranges of tickets sorted by first ticket ((lottery, raffle, range index) -> range)
+    ranges: Mapping<(LotteryId, RaffleId, u32), TicketRange>,
+    /// tickets drawn for a raffle
+    winning_tickets: Mapping<(LotteryId, RaffleId), Vec<TicketIndex>>,
+}
+
+/// Consecutive tickets bought in one participation.
+/// The range ends where the next range starts (or at the last ticket of the raffle)
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct TicketRange {
+    pub first_ticket: TicketIndex,
+    pub owner: AccountId,
+}
+
+#[openbrush::trait_definition]
+pub trait ClassicRaffleManager: Storage<Data> {
+    /// give the next tickets of the raffle to the owner and return the index of the first one
+    fn add_ticket_range(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        owner: AccountId,
+        nb_tickets: u32,
+    ) -> Result<TicketIndex, RaffleError> {
+        if nb_tickets == 0 {
+            return Err(IncorrectNbTickets);
+        }
+
+        let first_ticket = self.get_total_tickets(lottery_id, raffle_id);
+        let total_tickets = first_ticket.checked_add(nb_tickets).ok_or(AddOverFlow)?;
+        let nb_ranges = self
+            .data::<Data>()
+            .nb_ranges
+            .get(&(lottery_id, raffle_id))
+            .unwrap_or_default();
+
+        self.data::<Data>().ranges.insert(
+            &(lottery_id, raffle_id, nb_ranges),
+            &TicketRange {
+                first_ticket,
+                owner,
+            },
+        );
+        self.data::<Data>().nb_ranges.insert(
+            &(lottery_id, raffle_id),
+            &nb_ranges.checked_add(1).ok_or(AddOverFlow)?,
+        );
+        self.data::<Data>()
+            .nb_tickets
+            .insert(&(lottery_id, raffle_id), &total_tickets);
+
+        Ok(first_ticket)
+    }
+
+    /// return the number of tickets sold for the classic raffle
+    #[ink(message)]
+    fn get_total_tickets(&self, lottery_id: LotteryId, raffle_id: RaffleId) -> TicketIndex {
+        self.data::<Data>()
+            .nb_tickets
+            .get(&(lottery_id, raffle_id))
+            .unwrap_or_default()
+    }
+
+    /// return the owner of the ticket (binary search in the ranges of tickets)
+    #[ink(message)]
+    fn get_ticket_owner(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        ticket: TicketIndex,
+    ) -> Option<AccountId> {
+        if ticket >= self.get_total_tickets(lottery_id, raffle_id) {
+            return None;
+        }
+
+        // find the last range starting before the ticket
+        let mut low = 0;
+        let mut high = self
+            .data::<Data>()
+            .nb_ranges
+            .get(&(lottery_id, raffle_id))
+            .unwrap_or_default();
+        let mut owner = None;
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let range = self
+                .data::<Data>()
+                .ranges
+                .get(&(lottery_id, raffle_id, middle))?;
+            if range.first_ticket <= ticket {
+                owner = Some(range.owner);
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        owner
+    }
+
+    /// save the tickets drawn for the raffle and return the owner of each of them.
+    /// An account owning several winning tickets is returned once by ticket
+    fn set_winning_tickets(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        total_tickets: TicketIndex,
+        tickets: Vec<TicketIndex>,
+    ) -> Result<Vec<AccountId>, RaffleError> {
+        if self
+            .data::<Data>()
+            .winning_tickets
+            .contains(&(lottery_id, raffle_id))
+        {
+            return Err(ExistingResults);
+        }
+
+        // the tickets must be drawn among all tickets of the raffle
+        if total_tickets != self.get_total_tickets(lottery_id, raffle_id) {
+            return Err(IncorrectNbTickets);
+        }
+
+        let mut winners = Vec::with_capacity(tickets.len());
+        for (i, ticket) in tickets.iter().enumerate() {
+            // a ticket can win only once
+            if tickets[..i].contains(ticket) {
+                return Err(IncorrectWinningTickets);
+            }
+            let owner = self
+                .get_ticket_owner(lottery_id, raffle_id, *ticket)
+                .ok_or(IncorrectWinningTickets)?;
+            winners.push(owner);
+        }
+
+        self.data::<Data>()
+            .winning_tickets
+            .insert(&(lottery_id, raffle_id), &tickets);
+
+        Ok(winners)
+    }
+
+    /// return the tickets drawn for the classic raffle
+    #[ink(message)]
+    fn get_winning_tickets(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+    ) -> Option<Vec<TicketIndex>> {
+        self.data::<Data>()
+            .winning_tickets
+            .get(&(lottery_id, raffle_id))
+    }
+}
@@ -1,12 +1,28 @@
 use crate::traits::error::RaffleError;
 use crate::traits::error::RaffleError::*;
-use crate::traits::Number;
-use openbrush::traits::Storage;
+use crate::traits::keno::MAX_KENO_TICKETS;
+use crate::traits::referral::MAX_BASIS_POINTS;
+use crate::traits::{LotteryId, Number};
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::{Balance, Storage};
 
 #[derive(Default, Debug)]
 #[openbrush::storage_item]
 pub struct Data {
-    config: Option<Config>,
+    /// config saved before the support of several lotteries (moved to the default lottery by the migration)
+    config: Option<LegacyConfig>,
+    /// config of each lottery
+    configs: Mapping<LotteryId, Config>,
+    /// lotteries with a config
+    #[lazy]
+    lottery_ids: Vec<LotteryId>,
+    /// lotteries for which the numbers and the winners are requested in one round trip
+    draw_and_resolve: Mapping<LotteryId, bool>,
+    /// lotteries for which the participations are saved in the kv store (read by the offchain rollup)
+    onchain_participations: Mapping<LotteryId, bool>,
+    /// prize tiers of each lottery (none when the pot is shared between the tickets matching all numbers)
+    prize_tiers: Mapping<LotteryId, Vec<PrizeTier>>,
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
@@ -18,45 +34,382 @@ pub struct Config {
     pub nb_numbers: u8,
     pub min_number: Number,
     pub max_number: Number,
+    /// price paid for one ticket (0 for a free raffle)
+    pub ticket_price: Balance,
+    /// max number of tickets bought by an account for a raffle (None for no limit)
+    pub max_tickets_per_account: Option<u32>,
+    /// max number of tickets bought in one call (None for no limit)
+    pub max_batch_size: Option<u32>,
+    /// max number of tickets for a raffle (None for no limit)
+    pub max_tickets: Option<u32>,
+    /// second pool of numbers drawn from another range (None for no bonus numbers)
+    pub bonus_pool: Option<BonusPool>,
+    /// numbers lottery or classic raffle
+    pub game_mode: GameMode,
+}
+
+/// Config saved by the contracts deployed before the support of several lotteries.
+/// Its encoding must not change: it is read from the storage of these contracts by the migration
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct LegacyConfig {
+    pub nb_numbers: u8,
+    pub min_number: Number,
+    pub max_number: Number,
+}
+
+impl From<LegacyConfig> for Config {
+    fn from(config: LegacyConfig) -> Self {
+        Config {
+            nb_numbers: config.nb_numbers,
+            min_number: config.min_number,
+            max_number: config.max_number,
+            ticket_price: 0,
+            max_tickets_per_account: None,
+            max_batch_size: None,
+            max_tickets: None,
+            bonus_pool: None,
+            game_mode: GameMode::Numbers,
+        }
+    }
+}
+
+/// Way the winners of a raffle are selected
+#[derive(Default, Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum GameMode {
+    /// the players pick numbers and win if they match the drawn numbers
+    #[default]
+    Numbers,
+    /// classic raffle: each ticket is one entry and the given number of tickets are drawn
+    Tickets(u16),
+    /// keno: the players pick between 1 and the given number of numbers and win a multiple
+    /// of their stake depending on the number of matches (see the paytable)
+    Keno(u8),
+}
+
+/// Share of the pot won by the tickets matching exactly the given numbers of main and bonus numbers
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct PrizeTier {
+    pub nb_main_matches: u8,
+    pub nb_bonus_matches: u8,
+    /// share of the pot (in basis points) split between the winners of this tier
+    pub share: u16,
+}
+
+/// Second pool of numbers (ie the bonus ball), the bonus numbers follow the main numbers in a ticket.
+/// It is shared with the offchain rollup which draws the bonus numbers
+pub use lotto_protocol::BonusPool;
+
+impl Config {
+    /// check if the players pick the numbers of a lotto ticket (main and bonus numbers) with this config
+    pub fn ensure_numbers_mode(&self) -> Result<(), RaffleError> {
+        match self.game_mode {
+            GameMode::Numbers => Ok(()),
+            _ => Err(IncorrectGameMode),
+        }
+    }
+
+    /// check if the config is a classic raffle and return the number of winning tickets
+    pub fn ensure_tickets_mode(&self) -> Result<u16, RaffleError> {
+        match self.game_mode {
+            GameMode::Tickets(nb_winning_tickets) => Ok(nb_winning_tickets),
+            _ => Err(IncorrectGameMode),
+        }
+    }
+
+    /// check if the numbers respect this config
+    pub fn check_numbers(&self, numbers: &[Number]) -> Result<(), RaffleError> {
+        self.ensure_numbers_mode()?;
+        self.check_results(numbers)?;
+        Ok(())
+    }
+
+    /// check if the drawn numbers respect this config and split them between the main numbers
+    /// and the bonus numbers
+    pub fn check_results<'a>(
+        &self,
+        numbers: &'a [Number],
+    ) -> Result<(&'a [Number], &'a [Number]), RaffleError> {
+        let (main_numbers, bonus_numbers) = self.split_numbers(numbers)?;
+        check_range(main_numbers, self.min_number, self.max_number)?;
+        if let Some(bonus_pool) = self.bonus_pool {
+            check_range(bonus_numbers, bonus_pool.min_number, bonus_pool.max_number)?;
+        }
+        Ok((main_numbers, bonus_numbers))
+    }
+
+    /// check if the numbers picked for a keno ticket respect this config
+    pub fn check_keno_numbers(&self, numbers: &[Number]) -> Result<(), RaffleError> {
+        let max_picks = match self.game_mode {
+            GameMode::Keno(max_picks) => max_picks,
+            _ => return Err(IncorrectGameMode),
+        };
+        if numbers.is_empty() || numbers.len() > max_picks as usize {
+            return Err(IncorrectNbNumbers);
+        }
+        check_range(numbers, self.min_number, self.max_number)?;
+        // a number can be picked only once
+        for (i, number) in numbers.iter().enumerate() {
+            if numbers[..i].contains(number) {
+                return Err(IncorrectNumbers);
+            }
+        }
+        Ok(())
+    }
+
+    /// check if the prize tiers can be reached with this config
+    /// and if their shares do not exceed the pot
+    pub fn check_prize_tiers(&self, prize_tiers: &[PrizeTier]) -> Result<(), RaffleError> {
+        if prize_tiers.is_empty() {
+            return Ok(());
+        }
+        self.ensure_numbers_mode()?;
+
+        let nb_bonus_numbers = self
+            .bonus_pool
+            .map(|bonus_pool| bonus_pool.nb_numbers)
+            .unwrap_or_default();
+        let mut total_shares: u32 = 0;
+        for (i, tier) in prize_tiers.iter().enumerate() {
+            if tier.nb_main_matches > self.nb_numbers
+                || tier.nb_bonus_matches > nb_bonus_numbers
+                || (tier.nb_main_matches == 0 && tier.nb_bonus_matches == 0)
+                || tier.share == 0
+            {
+                return Err(IncorrectPrizeTiers);
+            }
+            // a ticket wins in one tier only
+            if prize_tiers[..i].iter().any(|t| {
+                t.nb_main_matches == tier.nb_main_matches
+                    && t.nb_bonus_matches == tier.nb_bonus_matches
+            }) {
+                return Err(IncorrectPrizeTiers);
+            }
+            total_shares += tier.share as u32;
+        }
+        if total_shares > MAX_BASIS_POINTS as u32 {
+            return Err(IncorrectPrizeTiers);
+        }
+        Ok(())
+    }
+
+    /// split the numbers of a ticket between the main numbers and the bonus numbers
+    pub fn split_numbers<'a>(
+        &self,
+        numbers: &'a [Number],
+    ) -> Result<(&'a [Number], &'a [Number]), RaffleError> {
+        let nb_bonus_numbers = self
+            .bonus_pool
+            .map(|bonus_pool| bonus_pool.nb_numbers)
+            .unwrap_or_default();
+        if numbers.len() != self.nb_numbers as usize + nb_bonus_numbers as usize {
+            return Err(IncorrectNbNumbers);
+        }
+        Ok(numbers.split_at(self.nb_numbers as usize))
+    }
+}
+
+/// check if the numbers are between min and max
+fn check_range(
+    numbers: &[Number],
+    min_number: Number,
+    max_number: Number,
+) -> Result<(), RaffleError> {
+    for number in numbers.iter() {
+        if *number > max_number || *number < min_number {
+            return Err(IncorrectNumbers);
+        }
+    }
+    Ok(())
 }
 
 #[openbrush::trait_definition]
 pub trait RaffleConfig: Storage<Data> {
-    fn set_config(&mut self, config: Config) -> Result<(), RaffleError> {
+    fn set_config(&mut self, lottery_id: LotteryId, config: Config) -> Result<(), RaffleError> {
         // check the config
-        if config.nb_numbers == 0 {
-            return Err(IncorrectConfig);
+        match config.game_mode {
+            GameMode::Numbers | GameMode::Keno(_) => {
+                if config.nb_numbers == 0 {
+                    return Err(IncorrectConfig);
+                }
+
+                if config.min_number >= config.max_number {
+                    return Err(IncorrectConfig);
+                }
+
+                // the numbers drawn are distinct numbers of the range
+                let range_size = (config.max_number - config.min_number) as u32 + 1;
+                if config.nb_numbers as u32 > range_size {
+                    return Err(IncorrectConfig);
+                }
+
+                if let Some(bonus_pool) = config.bonus_pool {
+                    if bonus_pool.nb_numbers == 0 || bonus_pool.min_number >= bonus_pool.max_number
+                    {
+                        return Err(IncorrectConfig);
+                    }
+                    let bonus_range_size =
+                        (bonus_pool.max_number - bonus_pool.min_number) as u32 + 1;
+                    if bonus_pool.nb_numbers as u32 > bonus_range_size {
+                        return Err(IncorrectConfig);
+                    }
+                }
+            }
+            GameMode::Tickets(nb_winning_tickets) => {
+                // no number is picked in a classic raffle
+                if nb_winning_tickets == 0 || config.bonus_pool.is_some() {
+                    return Err(IncorrectConfig);
+                }
+            }
         }
 
-        if config.min_number >= config.max_number {
+        if let GameMode::Keno(max_picks) = config.game_mode {
+            // the picks are distinct numbers of the range, without bonus numbers
+            let range_size = (config.max_number - config.min_number) as u32 + 1;
+            if max_picks == 0 || max_picks as u32 > range_size || config.bonus_pool.is_some() {
+                return Err(IncorrectConfig);
+            }
+            // all tickets are settled by one response, their number must be bounded
+            match config.max_tickets {
+                Some(max_tickets) if max_tickets <= MAX_KENO_TICKETS => {}
+                _ => return Err(IncorrectConfig),
+            }
+        }
+
+        // the limits can not be zero
+        if config.max_tickets_per_account == Some(0)
+            || config.max_batch_size == Some(0)
+            || config.max_tickets == Some(0)
+        {
             return Err(IncorrectConfig);
         }
 
-        self.data::<Data>().config = Some(config);
+        // the prize tiers must be reachable with the new config
+        config.check_prize_tiers(&self.get_prize_tiers(lottery_id))?;
+
+        if !self.data::<Data>().configs.contains(&lottery_id) {
+            let mut lottery_ids = self.get_lottery_ids();
+            lottery_ids.push(lottery_id);
+            self.data::<Data>().lottery_ids.set(&lottery_ids);
+        }
+        self.data::<Data>().configs.insert(&lottery_id, &config);
         Ok(())
     }
 
+    /// remove and return the config saved before the support of several lotteries
+    /// (the new parameters have their default value: free tickets, no limit, no bonus pool)
+    fn take_legacy_config(&mut self) -> Option<Config> {
+        self.data::<Data>().config.take().map(Config::from)
+    }
+
     #[ink(message)]
-    fn get_config(&self) -> Option<Config> {
-        self.data::<Data>().config
+    fn get_config(&self, lottery_id: LotteryId) -> Option<Config> {
+        self.data::<Data>().configs.get(&lottery_id)
     }
 
-    /// return the config and throw an error of the config is missing
-    fn ensure_config(&self) -> Result<Config, RaffleError> {
-        match self.data::<Data>().config {
-            None => Err(ConfigNotSet),
-            Some(config) => Ok(config),
+    /// set the prize tiers of the lottery (an empty list shares the pot between the tickets
+    /// matching all numbers)
+    fn set_prize_tiers(
+        &mut self,
+        lottery_id: LotteryId,
+        prize_tiers: Vec<PrizeTier>,
+    ) -> Result<(), RaffleError> {
+        self.ensure_config(lottery_id)?
+            .check_prize_tiers(&prize_tiers)?;
+        if prize_tiers.is_empty() {
+            self.data::<Data>().prize_tiers.remove(&lottery_id);
+        } else {
+            self.data::<Data>()
+                .prize_tiers
+                .insert(&lottery_id, &prize_tiers);
         }
+        Ok(())
+    }
+
+    /// return the prize tiers of the lottery
+    #[ink(message)]
+    fn get_prize_tiers(&self, lottery_id: LotteryId) -> Vec<PrizeTier> {
+        self.data::<Data>()
+            .prize_tiers
+            .get(&lottery_id)
+            .unwrap_or_default()
     }
 
-    /// check if the config is the same as the one given in parameter
-    fn ensure_same_config(&self, config: &Config) -> Result<(), RaffleError> {
+    /// return the lotteries with a config
+    #[ink(message)]
+    fn get_lottery_ids(&self) -> Vec<LotteryId> {
+        self.data::<Data>().lottery_ids.get().unwrap_or_default()
+    }
+
+    /// request the numbers and the winners in one round trip (only for the numbers mode).
+    /// The indexer must be up to date when the raffle is completed
+    fn set_draw_and_resolve(&mut self, lottery_id: LotteryId, draw_and_resolve: bool) {
+        self.data::<Data>()
+            .draw_and_resolve
+            .insert(&lottery_id, &draw_and_resolve);
+    }
+
+    /// return true if the numbers and the winners are requested in one round trip
+    #[ink(message)]
+    fn is_draw_and_resolve(&self, lottery_id: LotteryId) -> bool {
+        self.data::<Data>()
+            .draw_and_resolve
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+
+    /// save the participations in the kv store so the offchain rollup can read the winners
+    /// without indexer
+    fn set_onchain_participations(&mut self, lottery_id: LotteryId, onchain_participations: bool) {
+        self.data::<Data>()
+            .onchain_participations
+            .insert(&lottery_id, &onchain_participations);
+    }
+
+    /// return true if the participations are saved in the kv store
+    #[ink(message)]
+    fn is_onchain_participations(&self, lottery_id: LotteryId) -> bool {
+        self.data::<Data>()
+            .onchain_participations
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+
+    /// return the config and throw an error of the config is missing
+    fn ensure_config(&self, lottery_id: LotteryId) -> Result<Config, RaffleError> {
+        self.data::<Data>()
+            .configs
+            .get(&lottery_id)
+            .ok_or(ConfigNotSet)
+    }
+
+    /// check if the draw parameters are the same as the ones given in parameter
+    fn ensure_same_config(
+        &self,
+        lottery_id: LotteryId,
+        nb_numbers: u8,
+        min_number: Number,
+        max_number: Number,
+        bonus_pool: Option<BonusPool>,
+    ) -> Result<(), RaffleError> {
         // get the correct results for the given raffle
-        let this_config = self.ensure_config()?;
+        let this_config = self.ensure_config(lottery_id)?;
 
-        if this_config.nb_numbers != config.nb_numbers
-            || this_config.min_number != config.min_number
-            || this_config.max_number != config.max_number
+        if this_config.nb_numbers != nb_numbers
+            || this_config.min_number != min_number
+            || this_config.max_number != max_number
+            || this_config.bonus_pool != bonus_pool
         {
             return Err(DifferentConfig);
         }
@@ -65,20 +418,74 @@ pub trait RaffleConfig: Storage<Data> {
     }
 
     /// check if the numbers respect the config
-    fn check_numbers(&mut self, numbers: &[Number]) -> Result<(), RaffleError> {
+    fn check_numbers(
+        &mut self,
+        lottery_id: LotteryId,
+        numbers: &[Number],
+    ) -> Result<(), RaffleError> {
         // check if the config is set
-        let config = self.ensure_config()?;
+        let config = self.ensure_config(lottery_id)?;
 
         // check the numbers
-        let nb_numbers = numbers.len();
+        config.check_numbers(numbers)
+    }
 
-        if nb_numbers != config.nb_numbers as usize {
-            return Err(IncorrectNbNumbers);
+    /// check if the amount paid covers the price of the given number of tickets
+    /// and return the amount paid in excess
+    fn check_payment(
+        &self,
+        lottery_id: LotteryId,
+        nb_tickets: usize,
+        amount: Balance,
+    ) -> Result<Balance, RaffleError> {
+        let price = self.get_tickets_price(lottery_id, nb_tickets)?;
+        amount.checked_sub(price).ok_or(IncorrectPayment)
+    }
+
+    /// return the price for the given number of tickets
+    fn get_tickets_price(
+        &self,
+        lottery_id: LotteryId,
+        nb_tickets: usize,
+    ) -> Result<Balance, RaffleError> {
+        let config = self.ensure_config(lottery_id)?;
+        config
+            .ticket_price
+            .checked_mul(nb_tickets as Balance)
+            .ok_or(MulOverFlow)
+    }
+
+    /// check the number of tickets bought in one call respects the config
+    fn check_batch_size(
+        &self,
+        lottery_id: LotteryId,
+        nb_tickets: usize,
+    ) -> Result<(), RaffleError> {
+        let config = self.ensure_config(lottery_id)?;
+        match config.max_batch_size {
+            Some(max_batch_size) if nb_tickets > max_batch_size as usize => Err(BatchTooLarge),
+            _ => Ok(()),
         }
+    }
 
-        for number in numbers.iter() {
-            if *number > config.max_number || *number < config.min_number {
-                return Err(IncorrectNumbers);
+    /// check the number of tickets bought by an account and the number of tickets for the raffle respect the config
+    fn check_nb_tickets(
+        &self,
+        lottery_id: LotteryId,
+        nb_tickets_for_account: u32,
+        nb_tickets: u32,
+    ) -> Result<(), RaffleError> {
+        let config = self.ensure_config(lottery_id)?;
+
+        if let Some(max_tickets_per_account) = config.max_tickets_per_account {
+            if nb_tickets_for_account > max_tickets_per_account {
+                return Err(TooManyTicketsForAccount);
+            }
+        }
+
+        if let Some(max_tickets) = config.max_tickets {
+            if nb_tickets > max_tickets {
+                return Err(TooManyTickets);
             }
         }
 
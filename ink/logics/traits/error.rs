@@ -1,9 +1,11 @@
 use openbrush::contracts::access_control::AccessControlError;
+use openbrush::contracts::pausable::PausableError;
 
 #[derive(Debug, Eq, PartialEq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum RaffleError {
     AccessControlError(AccessControlError),
+    PausableError(PausableError),
     IncorrectRaffle,
     IncorrectStatus,
     IncorrectConfig,
@@ -18,7 +20,39 @@ pub enum RaffleError {
     AddOverFlow,
     SubOverFlow,
     DivByZero,
+    MulOverFlow,
     NoReward,
+    IncorrectPayment,
+    InsufficientAllowance,
+    InsufficientRewards,
+    RelayerDisabled,
+    IncorrectNbDraws,
+    ExistingSubscription,
+    NoSubscription,
+    IncorrectNbTickets,
+    UnknownQuickPickSlot,
+    PendingQuickPicks,
+    UnknownSyndicate,
+    NotSyndicateManager,
+    TooManyMembers,
+    InsufficientSyndicateBalance,
+    IncorrectReferralShare,
+    SelfReferral,
+    BatchTooLarge,
+    TooManyTicketsForAccount,
+    TooManyTickets,
+    UnknownOperation,
+    OperationNotReady,
+    IncorrectStorageVersion,
+    IncorrectGameMode,
+    IncorrectWinningTickets,
+    PaytableNotSet,
+    InsufficientReserve,
+    IncorrectMetaParticipation,
+    NotSyndicateMember,
+    ExistingSyndicateMember,
+    IncorrectDelay,
+    IncorrectPrizeTiers,
 }
 
 /// convertor from AccessControlError to RaffleError
@@ -27,3 +61,10 @@ impl From<AccessControlError> for RaffleError {
         RaffleError::AccessControlError(error)
     }
 }
+
+/// convertor from PausableError to RaffleError
+impl From<PausableError> for RaffleError {
+    fn from(error: PausableError) -> Self {
+        RaffleError::PausableError(error)
+    }
+}
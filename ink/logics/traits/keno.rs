@@ -0,0 +1,209 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use crate::traits::{LotteryId, RaffleId, TicketIndex};
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::{AccountId, Balance, Storage};
+
+/// Max number of keno tickets of a raffle (all of them are settled by one response of the
+/// offchain rollup, so the max number of tickets is mandatory and bounded for a keno)
+pub const MAX_KENO_TICKETS: u32 = 500;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    /// multipliers of the stake for a number of picks, indexed by the number of matches
+    paytables: Mapping<(LotteryId, u8), Vec<u32>>,
+    /// max prizes owed to the keno tickets not settled yet
+    liabilities: Mapping<LotteryId, Balance>,
+    /// number of keno tickets for a raffle (ie the index of the next ticket)
+    nb_tickets: Mapping<(LotteryId, RaffleId), TicketIndex>,
+    /// keno tickets not settled yet
+    tickets: Mapping<(LotteryId, RaffleId, TicketIndex), KenoTicket>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct KenoTicket {
+    pub owner: AccountId,
+    /// number of numbers picked by the owner
+    pub nb_picks: u8,
+    /// amount paid for this ticket, the prize is a multiple of it
+    pub stake: Balance,
+}
+
+#[openbrush::trait_definition]
+pub trait KenoManager: Storage<Data> {
+    /// set the multipliers of the stake for the given number of picks
+    /// (one multiplier by number of matches, from 0 to `nb_picks`)
+    fn set_paytable(
+        &mut self,
+        lottery_id: LotteryId,
+        nb_picks: u8,
+        multipliers: Vec<u32>,
+    ) -> Result<(), RaffleError> {
+        if nb_picks == 0 || multipliers.len() != nb_picks as usize + 1 {
+            return Err(IncorrectConfig);
+        }
+
+        // the prizes of the tickets not settled yet can not change
+        if self.get_liability(lottery_id) > 0 {
+            return Err(IncorrectStatus);
+        }
+
+        self.data::<Data>()
+            .paytables
+            .insert(&(lottery_id, nb_picks), &multipliers);
+        Ok(())
+    }
+
+    /// return the multipliers of the stake for the given number of picks
+    #[ink(message)]
+    fn get_paytable(&self, lottery_id: LotteryId, nb_picks: u8) -> Option<Vec<u32>> {
+        self.data::<Data>().paytables.get(&(lottery_id, nb_picks))
+    }
+
+    /// return the max prizes owed to the keno tickets of the lottery not settled yet
+    #[ink(message)]
+    fn get_liability(&self, lottery_id: LotteryId) -> Balance {
+        self.data::<Data>()
+            .liabilities
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+
+    /// return the keno ticket if it is not settled yet
+    #[ink(message)]
+    fn get_keno_ticket(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        ticket: TicketIndex,
+    ) -> Option<KenoTicket> {
+        self.data::<Data>()
+            .tickets
+            .get(&(lottery_id, raffle_id, ticket))
+    }
+
+    /// return the number of keno tickets for the raffle
+    #[ink(message)]
+    fn get_nb_keno_tickets(&self, lottery_id: LotteryId, raffle_id: RaffleId) -> TicketIndex {
+        self.data::<Data>()
+            .nb_tickets
+            .get(&(lottery_id, raffle_id))
+            .unwrap_or_default()
+    }
+
+    /// save the keno ticket and return its index.
+    /// The max prize of all tickets not settled yet can not exceed the reserve of the lottery
+    fn add_keno_ticket(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        owner: AccountId,
+        nb_picks: u8,
+        stake: Balance,
+        reserve: Balance,
+    ) -> Result<TicketIndex, RaffleError> {
+        let max_prize = self.get_max_prize(lottery_id, nb_picks, stake)?;
+        let liability = self
+            .get_liability(lottery_id)
+            .checked_add(max_prize)
+            .ok_or(AddOverFlow)?;
+        if liability > reserve {
+            return Err(InsufficientReserve);
+        }
+
+        let ticket = self.get_nb_keno_tickets(lottery_id, raffle_id);
+        self.data::<Data>().tickets.insert(
+            &(lottery_id, raffle_id, ticket),
+            &KenoTicket {
+                owner,
+                nb_picks,
+                stake,
+            },
+        );
+        self.data::<Data>().nb_tickets.insert(
+            &(lottery_id, raffle_id),
+            &ticket.checked_add(1).ok_or(AddOverFlow)?,
+        );
+        self.data::<Data>()
+            .liabilities
+            .insert(&lottery_id, &liability);
+
+        Ok(ticket)
+    }
+
+    /// settle all keno tickets of the raffle with their number of matches
+    /// and return the owner and the prize of each ticket
+    fn settle_keno_tickets(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        matches: Vec<(TicketIndex, u8)>,
+    ) -> Result<Vec<(AccountId, Balance)>, RaffleError> {
+        if matches.len() != self.get_nb_keno_tickets(lottery_id, raffle_id) as usize {
+            return Err(IncorrectNbTickets);
+        }
+
+        // all tickets are checked before any of them is settled
+        let mut liability = self.get_liability(lottery_id);
+        let mut prizes = Vec::with_capacity(matches.len());
+        for (i, (ticket, nb_matches)) in matches.iter().enumerate() {
+            // a ticket is settled only once: the tickets are sorted by index without duplicate
+            if i > 0 && matches[i - 1].0 >= *ticket {
+                return Err(IncorrectWinningTickets);
+            }
+            let keno_ticket = self
+                .get_keno_ticket(lottery_id, raffle_id, *ticket)
+                .ok_or(IncorrectWinningTickets)?;
+
+            let paytable = self
+                .get_paytable(lottery_id, keno_ticket.nb_picks)
+                .ok_or(PaytableNotSet)?;
+            let multiplier = *paytable
+                .get(*nb_matches as usize)
+                .ok_or(IncorrectWinningTickets)?;
+            let prize = keno_ticket
+                .stake
+                .checked_mul(multiplier as Balance)
+                .ok_or(MulOverFlow)?;
+            prizes.push((keno_ticket.owner, prize));
+
+            // the max prize of this ticket is not owed anymore
+            let max_prize =
+                self.get_max_prize(lottery_id, keno_ticket.nb_picks, keno_ticket.stake)?;
+            liability = liability.checked_sub(max_prize).ok_or(SubOverFlow)?;
+        }
+
+        for (ticket, _) in matches {
+            self.data::<Data>()
+                .tickets
+                .remove(&(lottery_id, raffle_id, ticket));
+        }
+        self.data::<Data>()
+            .liabilities
+            .insert(&lottery_id, &liability);
+
+        Ok(prizes)
+    }
+
+    /// return the biggest prize for a ticket with the given number of picks and stake
+    fn get_max_prize(
+        &self,
+        lottery_id: LotteryId,
+        nb_picks: u8,
+        stake: Balance,
+    ) -> Result<Balance, RaffleError> {
+        let paytable = self
+            .get_paytable(lottery_id, nb_picks)
+            .ok_or(PaytableNotSet)?;
+        let max_multiplier = paytable.into_iter().max().unwrap_or_default();
+        stake
+            .checked_mul(max_multiplier as Balance)
+            .ok_or(MulOverFlow)
+    }
+}
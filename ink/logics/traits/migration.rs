@@ -0,0 +1,46 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use openbrush::traits::Storage;
+
+/// Version of the storage layout expected by this code.
+/// Increment it when the layout of the storage changes and add the migration step in the contract
+pub const STORAGE_VERSION: u16 = 2;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    /// version of the layout used by the storage (0 for the contracts deployed before the versioning)
+    #[lazy]
+    storage_version: u16,
+}
+
+#[openbrush::trait_definition]
+pub trait MigrationManager: Storage<Data> {
+    #[ink(message)]
+    fn get_storage_version(&self) -> u16 {
+        self.data::<Data>()
+            .storage_version
+            .get()
+            .unwrap_or_default()
+    }
+
+    fn set_storage_version(&mut self, storage_version: u16) -> Result<(), RaffleError> {
+        if storage_version > STORAGE_VERSION {
+            return Err(IncorrectStorageVersion);
+        }
+        self.data::<Data>().storage_version.set(&storage_version);
+        Ok(())
+    }
+
+    /// return the next version to migrate to or None if the storage is up to date
+    fn next_storage_version(&self) -> Result<Option<u16>, RaffleError> {
+        let storage_version = self.get_storage_version();
+        if storage_version > STORAGE_VERSION {
+            return Err(IncorrectStorageVersion);
+        }
+        if storage_version == STORAGE_VERSION {
+            return Ok(None);
+        }
+        Ok(Some(storage_version + 1))
+    }
+}
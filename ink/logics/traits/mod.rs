@@ -1,11 +1,25 @@
 use openbrush::contracts::access_control::RoleType;
 
 pub const LOTTO_MANAGER_ROLE: RoleType = ink::selector_id!("LOTTO_MANAGER");
+pub const PAUSER_ROLE: RoleType = ink::selector_id!("PAUSER");
 
-pub type RaffleId = u32;
-pub type Number = u16;
+/// Types shared with the offchain rollup
+pub use lotto_protocol::{LotteryId, Number, RaffleId, TicketIndex};
 
+/// Lottery used by the contracts deployed before the support of several lotteries
+pub const DEFAULT_LOTTERY_ID: LotteryId = 0;
+
+pub mod classic_raffle;
 pub mod config;
 pub mod error;
+pub mod keno;
+pub mod migration;
+pub mod quick_pick;
 pub mod raffle;
+pub mod referral;
+pub mod relayer;
 pub mod reward;
+pub mod statistics;
+pub mod subscription;
+pub mod syndicate;
+pub mod timelock;
@@ -0,0 +1,165 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use crate::traits::{LotteryId, RaffleId};
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::{AccountId, Balance, Storage};
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    /// owner of the tickets waiting for the numbers (lottery id, raffle id, slot) -> account
+    slots: Mapping<(LotteryId, RaffleId, u32), AccountId>,
+    /// part of the price added in the pot for the ticket waiting for the numbers (lottery id, raffle id, slot) -> amount
+    amounts: Mapping<(LotteryId, RaffleId, u32), Balance>,
+    /// number of slots created for a raffle
+    nb_slots: Mapping<(LotteryId, RaffleId), u32>,
+    /// number of slots still waiting for the numbers for a lottery
+    nb_pending_slots: Mapping<LotteryId, u32>,
+}
+
+#[openbrush::trait_definition]
+pub trait QuickPickManager: Storage<Data> {
+    /// reserve some slots for the account and return the first one
+    /// (`amount` is the part of the price of each ticket added in the pot)
+    fn add_quick_pick_slots(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        account: AccountId,
+        nb_slots: u32,
+        amount: Balance,
+    ) -> Result<u32, RaffleError> {
+        if nb_slots == 0 {
+            return Err(IncorrectNbTickets);
+        }
+
+        let first_slot = self
+            .data::<Data>()
+            .nb_slots
+            .get(&(lottery_id, raffle_id))
+            .unwrap_or(0);
+        let next_slot = first_slot.checked_add(nb_slots).ok_or(AddOverFlow)?;
+
+        for slot in first_slot..next_slot {
+            self.data::<Data>()
+                .slots
+                .insert(&(lottery_id, raffle_id, slot), &account);
+            self.data::<Data>()
+                .amounts
+                .insert(&(lottery_id, raffle_id, slot), &amount);
+        }
+        self.data::<Data>()
+            .nb_slots
+            .insert(&(lottery_id, raffle_id), &next_slot);
+        let nb_pending_slots = self
+            .get_nb_pending_quick_picks(lottery_id)
+            .checked_add(nb_slots)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .nb_pending_slots
+            .insert(&lottery_id, &nb_pending_slots);
+
+        Ok(first_slot)
+    }
+
+    /// return the owner of the ticket waiting for the numbers in the slot
+    #[ink(message)]
+    fn get_quick_pick_slot(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        slot: u32,
+    ) -> Option<AccountId> {
+        self.data::<Data>()
+            .slots
+            .get(&(lottery_id, raffle_id, slot))
+    }
+
+    /// remove the slot (the numbers are received) and return the owner of the ticket
+    fn take_quick_pick_slot(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        slot: u32,
+    ) -> Result<AccountId, RaffleError> {
+        let account = self
+            .get_quick_pick_slot(lottery_id, raffle_id, slot)
+            .ok_or(UnknownQuickPickSlot)?;
+
+        self.data::<Data>()
+            .slots
+            .remove(&(lottery_id, raffle_id, slot));
+        self.data::<Data>()
+            .amounts
+            .remove(&(lottery_id, raffle_id, slot));
+        let nb_pending_slots = self
+            .get_nb_pending_quick_picks(lottery_id)
+            .checked_sub(1)
+            .ok_or(SubOverFlow)?;
+        self.data::<Data>()
+            .nb_pending_slots
+            .insert(&lottery_id, &nb_pending_slots);
+
+        Ok(account)
+    }
+
+    /// remove the slots of the raffle still waiting for the numbers and return the owners of the tickets
+    /// with the part of the price added in the pot
+    fn take_pending_quick_pick_slots(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+    ) -> Result<Vec<(AccountId, Balance)>, RaffleError> {
+        let nb_slots = self
+            .data::<Data>()
+            .nb_slots
+            .get(&(lottery_id, raffle_id))
+            .unwrap_or(0);
+
+        let mut accounts = Vec::new();
+        for slot in 0..nb_slots {
+            if let Some(account) = self.get_quick_pick_slot(lottery_id, raffle_id, slot) {
+                self.data::<Data>()
+                    .slots
+                    .remove(&(lottery_id, raffle_id, slot));
+                let amount = self
+                    .data::<Data>()
+                    .amounts
+                    .get(&(lottery_id, raffle_id, slot))
+                    .unwrap_or_default();
+                self.data::<Data>()
+                    .amounts
+                    .remove(&(lottery_id, raffle_id, slot));
+                accounts.push((account, amount));
+            }
+        }
+
+        let nb_pending_slots = self
+            .get_nb_pending_quick_picks(lottery_id)
+            .checked_sub(accounts.len() as u32)
+            .ok_or(SubOverFlow)?;
+        self.data::<Data>()
+            .nb_pending_slots
+            .insert(&lottery_id, &nb_pending_slots);
+
+        Ok(accounts)
+    }
+
+    /// throw an error if some tickets are still waiting for the numbers
+    fn ensure_no_pending_quick_pick(&self, lottery_id: LotteryId) -> Result<(), RaffleError> {
+        if self.get_nb_pending_quick_picks(lottery_id) > 0 {
+            return Err(PendingQuickPicks);
+        }
+        Ok(())
+    }
+
+    /// return the number of tickets waiting for the numbers
+    #[ink(message)]
+    fn get_nb_pending_quick_picks(&self, lottery_id: LotteryId) -> u32 {
+        self.data::<Data>()
+            .nb_pending_slots
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+}
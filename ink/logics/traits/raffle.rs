@@ -1,17 +1,35 @@
+use crate::traits::config::Config;
 use crate::traits::error::RaffleError;
 use crate::traits::error::RaffleError::*;
-use crate::traits::{Number, RaffleId};
+use crate::traits::{LotteryId, Number, RaffleId, DEFAULT_LOTTERY_ID};
 use ink::prelude::vec::Vec;
 use ink::storage::Mapping;
-use openbrush::traits::{AccountId, Storage};
+use openbrush::traits::{AccountId, Balance, BlockNumber, Storage};
+
+/// Max number of raffles returned by one call of `get_raffles`
+pub const MAX_RAFFLES_BY_PAGE: u32 = 50;
 
 #[derive(Default, Debug)]
 #[openbrush::storage_item]
 pub struct Data {
+    /// raffle saved before the support of several lotteries (moved to the default lottery by the migration)
     current_raffle_id: RaffleId,
     status: Status,
+    /// results and winners saved before the support of several lotteries (raffles of the default lottery)
     results: Mapping<RaffleId, Vec<Number>>,
     winners: Mapping<RaffleId, Vec<AccountId>>,
+    /// current raffle of each lottery
+    current_raffle_ids: Mapping<LotteryId, RaffleId>,
+    /// status of the current raffle of each lottery
+    statuses: Mapping<LotteryId, Status>,
+    lottery_results: Mapping<(LotteryId, RaffleId), Vec<Number>>,
+    lottery_winners: Mapping<(LotteryId, RaffleId), Vec<AccountId>>,
+    /// number of tickets for a raffle
+    nb_tickets: Mapping<(LotteryId, RaffleId), u32>,
+    /// number of tickets bought by an account for a raffle
+    nb_tickets_by_account: Mapping<(LotteryId, RaffleId, AccountId), u32>,
+    /// summary of the raffle
+    raffle_infos: Mapping<(LotteryId, RaffleId), RaffleInfo>,
 }
 
 #[derive(Default, Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
@@ -28,80 +46,339 @@ pub enum Status {
     Closed,
 }
 
+/// Summary of a raffle
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RaffleInfo {
+    pub lottery_id: LotteryId,
+    pub raffle_id: RaffleId,
+    /// config used for this raffle
+    pub config: Config,
+    pub status: Status,
+    /// block when the raffle started
+    pub started_at: BlockNumber,
+    /// block when the raffle was completed (no more participation)
+    pub completed_at: Option<BlockNumber>,
+    /// block when the results were received
+    pub results_at: Option<BlockNumber>,
+    /// block when the winners were received
+    pub winners_at: Option<BlockNumber>,
+    pub nb_tickets: u32,
+    pub nb_participants: u32,
+    pub nb_winners: u32,
+    /// amount shared between the winners
+    pub pot: Balance,
+    pub prize_per_winner: Balance,
+    /// index in the message queue of the request sent to draw the numbers
+    pub draw_request_id: Option<u32>,
+}
+
 #[openbrush::trait_definition]
 pub trait Raffle: Storage<Data> {
-    /// Start a new raffle
-    fn start_new_raffle(&mut self) -> Result<RaffleId, RaffleError> {
+    /// Start a new raffle for the lottery
+    fn start_new_raffle(
+        &mut self,
+        lottery_id: LotteryId,
+        config: Config,
+    ) -> Result<RaffleId, RaffleError> {
         // check the status
-        if self.data::<Data>().status != Status::NotStarted
-            && self.data::<Data>().status != Status::Closed
-        {
+        let status = self.get_current_status(lottery_id);
+        if status != Status::NotStarted && status != Status::Closed {
             return Err(RaffleError::IncorrectStatus);
         }
 
         // increment the raffle id
-        let new_raffle_id = self.data::<Data>().current_raffle_id + 1;
+        let new_raffle_id = self.get_current_raffle_id(lottery_id) + 1;
+
+        self.data::<Data>()
+            .current_raffle_ids
+            .insert(lottery_id, &new_raffle_id);
+        self.data::<Data>()
+            .statuses
+            .insert(lottery_id, &Status::Ongoing);
 
-        self.data::<Data>().current_raffle_id = new_raffle_id;
-        self.data::<Data>().status = Status::Ongoing;
+        let raffle_info = RaffleInfo {
+            lottery_id,
+            raffle_id: new_raffle_id,
+            config,
+            status: Status::Ongoing,
+            started_at: Self::env().block_number(),
+            completed_at: None,
+            results_at: None,
+            winners_at: None,
+            nb_tickets: 0,
+            nb_participants: 0,
+            nb_winners: 0,
+            pot: 0,
+            prize_per_winner: 0,
+            draw_request_id: None,
+        };
+        self.data::<Data>()
+            .raffle_infos
+            .insert((lottery_id, new_raffle_id), &raffle_info);
 
         Ok(new_raffle_id)
     }
 
-    /// Stop the current raffle
-    fn stop_current_raffle(&mut self) -> Result<(), RaffleError> {
+    /// Stop the current raffle of the lottery
+    fn stop_current_raffle(&mut self, lottery_id: LotteryId) -> Result<(), RaffleError> {
         // check the status
-        if self.data::<Data>().status != Status::Ongoing {
+        if self.get_current_status(lottery_id) != Status::Ongoing {
             return Err(RaffleError::IncorrectStatus);
         }
         // update the status
-        self.data::<Data>().status = Status::WaitingResults;
+        self.data::<Data>()
+            .statuses
+            .insert(lottery_id, &Status::WaitingResults);
+
+        let raffle_id = self.get_current_raffle_id(lottery_id);
+        if let Some(mut raffle_info) = self.get_raffle_info(lottery_id, raffle_id) {
+            raffle_info.status = Status::WaitingResults;
+            raffle_info.completed_at = Some(Self::env().block_number());
+            self.data::<Data>()
+                .raffle_infos
+                .insert((lottery_id, raffle_id), &raffle_info);
+        }
+        Ok(())
+    }
+
+    /// remove and return the raffle saved before the support of several lotteries
+    fn take_legacy_raffle(&mut self) -> (RaffleId, Status) {
+        let raffle_id = self.data::<Data>().current_raffle_id;
+        let status = self.data::<Data>().status;
+        self.data::<Data>().current_raffle_id = 0;
+        self.data::<Data>().status = Status::NotStarted;
+        (raffle_id, status)
+    }
+
+    /// set the current raffle of the lottery (used by the migration)
+    fn set_current_raffle(&mut self, lottery_id: LotteryId, raffle_id: RaffleId, status: Status) {
+        self.data::<Data>()
+            .current_raffle_ids
+            .insert(lottery_id, &raffle_id);
+        self.data::<Data>().statuses.insert(lottery_id, &status);
+    }
+
+    /// save the index of the request sent to draw the numbers
+    fn set_draw_request_id(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        draw_request_id: u32,
+    ) -> Result<(), RaffleError> {
+        let mut raffle_info = self
+            .get_raffle_info(lottery_id, raffle_id)
+            .ok_or(IncorrectRaffle)?;
+        raffle_info.draw_request_id = Some(draw_request_id);
+        self.data::<Data>()
+            .raffle_infos
+            .insert((lottery_id, raffle_id), &raffle_info);
+        Ok(())
+    }
+
+    /// save the pot shared between the winners and the prize for each of them
+    fn set_prize(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        pot: Balance,
+        prize_per_winner: Balance,
+    ) -> Result<(), RaffleError> {
+        let mut raffle_info = self
+            .get_raffle_info(lottery_id, raffle_id)
+            .ok_or(IncorrectRaffle)?;
+        raffle_info.pot = pot;
+        raffle_info.prize_per_winner = prize_per_winner;
+        self.data::<Data>()
+            .raffle_infos
+            .insert((lottery_id, raffle_id), &raffle_info);
         Ok(())
     }
 
     #[ink(message)]
-    fn get_current_raffle_id(&self) -> RaffleId {
-        self.data::<Data>().current_raffle_id
+    fn get_current_raffle_id(&self, lottery_id: LotteryId) -> RaffleId {
+        self.data::<Data>()
+            .current_raffle_ids
+            .get(lottery_id)
+            .unwrap_or_default()
     }
 
     #[ink(message)]
-    fn get_current_status(&self) -> Status {
-        self.data::<Data>().status
+    fn get_current_status(&self, lottery_id: LotteryId) -> Status {
+        self.data::<Data>()
+            .statuses
+            .get(lottery_id)
+            .unwrap_or_default()
     }
 
     #[ink(message)]
-    fn get_results(&self, raffle_id: RaffleId) -> Option<Vec<Number>> {
-        self.data::<Data>().results.get(raffle_id)
+    fn get_results(&self, lottery_id: LotteryId, raffle_id: RaffleId) -> Option<Vec<Number>> {
+        match self
+            .data::<Data>()
+            .lottery_results
+            .get((lottery_id, raffle_id))
+        {
+            None if lottery_id == DEFAULT_LOTTERY_ID => self.data::<Data>().results.get(raffle_id),
+            results => results,
+        }
     }
 
     #[ink(message)]
-    fn get_winners(&self, raffle_id: RaffleId) -> Option<Vec<AccountId>> {
-        self.data::<Data>().winners.get(raffle_id)
+    fn get_winners(&self, lottery_id: LotteryId, raffle_id: RaffleId) -> Option<Vec<AccountId>> {
+        match self
+            .data::<Data>()
+            .lottery_winners
+            .get((lottery_id, raffle_id))
+        {
+            None if lottery_id == DEFAULT_LOTTERY_ID => self.data::<Data>().winners.get(raffle_id),
+            winners => winners,
+        }
     }
 
-    /// save the results for the current raffle.
-    fn set_results(
+    /// return the summary of the given raffle
+    #[ink(message)]
+    fn get_raffle_info(&self, lottery_id: LotteryId, raffle_id: RaffleId) -> Option<RaffleInfo> {
+        self.data::<Data>()
+            .raffle_infos
+            .get((lottery_id, raffle_id))
+    }
+
+    /// return the summaries of the raffles of the lottery, starting from the given raffle id
+    /// (max `MAX_RAFFLES_BY_PAGE` raffles)
+    #[ink(message)]
+    fn get_raffles(&self, lottery_id: LotteryId, from: RaffleId, limit: u32) -> Vec<RaffleInfo> {
+        let limit = limit.min(MAX_RAFFLES_BY_PAGE);
+        let last_raffle_id = self.get_current_raffle_id(lottery_id);
+        let mut raffles = Vec::new();
+        let mut raffle_id = from;
+        while raffle_id <= last_raffle_id && (raffles.len() as u32) < limit {
+            if let Some(raffle_info) = self.get_raffle_info(lottery_id, raffle_id) {
+                raffles.push(raffle_info);
+            }
+            raffle_id = match raffle_id.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        raffles
+    }
+
+    /// return the number of tickets for the given raffle
+    #[ink(message)]
+    fn get_nb_tickets(&self, lottery_id: LotteryId, raffle_id: RaffleId) -> u32 {
+        self.data::<Data>()
+            .nb_tickets
+            .get((lottery_id, raffle_id))
+            .unwrap_or_default()
+    }
+
+    /// return the number of tickets bought by the account for the given raffle
+    #[ink(message)]
+    fn get_nb_tickets_for(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        account: AccountId,
+    ) -> u32 {
+        self.data::<Data>()
+            .nb_tickets_by_account
+            .get((lottery_id, raffle_id, account))
+            .unwrap_or_default()
+    }
+
+    /// count the tickets bought by the account for the current raffle of the lottery.
+    /// Return the number of tickets for this account and for the raffle
+    fn add_tickets(
         &mut self,
+        lottery_id: LotteryId,
+        account: AccountId,
+        nb_tickets: u32,
+    ) -> Result<(u32, u32), RaffleError> {
+        let raffle_id = self.get_current_raffle_id(lottery_id);
+
+        let previous_nb_tickets_for_account =
+            self.get_nb_tickets_for(lottery_id, raffle_id, account);
+        let nb_tickets_for_account = previous_nb_tickets_for_account
+            .checked_add(nb_tickets)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .nb_tickets_by_account
+            .insert((lottery_id, raffle_id, account), &nb_tickets_for_account);
+
+        let total_nb_tickets = self
+            .get_nb_tickets(lottery_id, raffle_id)
+            .checked_add(nb_tickets)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .nb_tickets
+            .insert((lottery_id, raffle_id), &total_nb_tickets);
+
+        if let Some(mut raffle_info) = self.get_raffle_info(lottery_id, raffle_id) {
+            raffle_info.nb_tickets = total_nb_tickets;
+            if previous_nb_tickets_for_account == 0 && nb_tickets > 0 {
+                raffle_info.nb_participants = raffle_info
+                    .nb_participants
+                    .checked_add(1)
+                    .ok_or(AddOverFlow)?;
+            }
+            self.data::<Data>()
+                .raffle_infos
+                .insert((lottery_id, raffle_id), &raffle_info);
+        }
+
+        Ok((nb_tickets_for_account, total_nb_tickets))
+    }
+
+    /// check if the raffle is the current raffle of the lottery and has the given status
+    fn ensure_current_status(
+        &self,
+        lottery_id: LotteryId,
         raffle_id: RaffleId,
-        results: Vec<Number>,
+        status: Status,
     ) -> Result<(), RaffleError> {
         // check the raffle number
-        if self.data::<Data>().current_raffle_id != raffle_id {
+        if self.get_current_raffle_id(lottery_id) != raffle_id {
             return Err(RaffleError::IncorrectRaffle);
         }
 
         // check the status
-        if self.data::<Data>().status != Status::WaitingResults {
+        if self.get_current_status(lottery_id) != status {
             return Err(RaffleError::IncorrectStatus);
         }
 
-        match self.data::<Data>().results.get(raffle_id) {
+        Ok(())
+    }
+
+    /// save the results for the current raffle of the lottery.
+    fn set_results(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        results: Vec<Number>,
+    ) -> Result<(), RaffleError> {
+        self.ensure_current_status(lottery_id, raffle_id, Status::WaitingResults)?;
+
+        match self.get_results(lottery_id, raffle_id) {
             Some(_) => Err(ExistingResults),
             None => {
                 // save the results
-                self.data::<Data>().results.insert(raffle_id, &results);
+                self.data::<Data>()
+                    .lottery_results
+                    .insert((lottery_id, raffle_id), &results);
                 // update the status
-                self.data::<Data>().status = Status::WaitingWinners;
+                self.data::<Data>()
+                    .statuses
+                    .insert(lottery_id, &Status::WaitingWinners);
+                if let Some(mut raffle_info) = self.get_raffle_info(lottery_id, raffle_id) {
+                    raffle_info.status = Status::WaitingWinners;
+                    raffle_info.results_at = Some(Self::env().block_number());
+                    self.data::<Data>()
+                        .raffle_infos
+                        .insert((lottery_id, raffle_id), &raffle_info);
+                }
                 Ok(())
             }
         }
@@ -109,15 +386,14 @@ pub trait Raffle: Storage<Data> {
 
     /// check if the saved results are the same as the ones given in parameter
     fn ensure_same_results(
-        &mut self,
+        &self,
+        lottery_id: LotteryId,
         raffle_id: RaffleId,
         numbers: &[Number],
     ) -> Result<(), RaffleError> {
         // get the correct results for the given raffle
         let result = self
-            .data::<Data>()
-            .results
-            .get(raffle_id)
+            .get_results(lottery_id, raffle_id)
             .ok_or(DifferentResults)?;
 
         if result.len() != numbers.len() {
@@ -133,38 +409,43 @@ pub trait Raffle: Storage<Data> {
         Ok(())
     }
 
-    /// save the winners for the current raffle.
+    /// save the winners for the current raffle of the lottery.
     fn set_winners(
         &mut self,
+        lottery_id: LotteryId,
         raffle_id: RaffleId,
         winners: Vec<AccountId>,
     ) -> Result<(), RaffleError> {
-        // check the raffle number
-        if self.data::<Data>().current_raffle_id != raffle_id {
-            return Err(RaffleError::IncorrectRaffle);
-        }
-
-        // check the status
-        if self.data::<Data>().status != Status::WaitingWinners {
-            return Err(RaffleError::IncorrectStatus);
-        }
+        self.ensure_current_status(lottery_id, raffle_id, Status::WaitingWinners)?;
 
-        match self.data::<Data>().winners.get(raffle_id) {
+        match self.get_winners(lottery_id, raffle_id) {
             Some(_) => Err(ExistingWinners),
             None => {
                 // save the result
-                self.data::<Data>().winners.insert(raffle_id, &winners);
+                self.data::<Data>()
+                    .lottery_winners
+                    .insert((lottery_id, raffle_id), &winners);
                 // update the status
-                self.data::<Data>().status = Status::Closed;
+                self.data::<Data>()
+                    .statuses
+                    .insert(lottery_id, &Status::Closed);
+                if let Some(mut raffle_info) = self.get_raffle_info(lottery_id, raffle_id) {
+                    raffle_info.status = Status::Closed;
+                    raffle_info.winners_at = Some(Self::env().block_number());
+                    raffle_info.nb_winners = winners.len() as u32;
+                    self.data::<Data>()
+                        .raffle_infos
+                        .insert((lottery_id, raffle_id), &raffle_info);
+                }
                 Ok(())
             }
         }
     }
 
-    /// check if the user can participate in the current raffle
-    fn can_participate(&mut self) -> Result<(), RaffleError> {
+    /// check if the user can participate in the current raffle of the lottery
+    fn can_participate(&self, lottery_id: LotteryId) -> Result<(), RaffleError> {
         // check the status
-        if self.data::<Data>().status != Status::Ongoing {
+        if self.get_current_status(lottery_id) != Status::Ongoing {
             return Err(RaffleError::IncorrectStatus);
         }
 
@@ -0,0 +1,126 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use openbrush::storage::Mapping;
+use openbrush::traits::{AccountId, Balance, Storage};
+
+/// Max value for a share expressed in basis points (100%)
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    /// share of the ticket price paid to the referrer (in basis points)
+    #[lazy]
+    referral_share: u16,
+    /// first referrer of the account (account -> referrer)
+    referrers: Mapping<AccountId, AccountId>,
+    /// number of accounts referred by the referrer
+    nb_referrals: Mapping<AccountId, u32>,
+    /// total rewards earned by the referrer
+    referral_rewards: Mapping<AccountId, Balance>,
+}
+
+#[openbrush::trait_definition]
+pub trait ReferralManager: Storage<Data> {
+    fn set_referral_share(&mut self, referral_share: u16) -> Result<(), RaffleError> {
+        if referral_share > MAX_BASIS_POINTS {
+            return Err(IncorrectReferralShare);
+        }
+        self.data::<Data>().referral_share.set(&referral_share);
+        Ok(())
+    }
+
+    /// return the share of the ticket price paid to the referrer (in basis points)
+    #[ink(message)]
+    fn get_referral_share(&self) -> u16 {
+        self.data::<Data>().referral_share.get().unwrap_or_default()
+    }
+
+    /// bind the account to the referrer if the account has no referrer yet.
+    /// Return true if the account is bound to this referrer
+    fn bind_referrer(
+        &mut self,
+        account: AccountId,
+        referrer: AccountId,
+    ) -> Result<bool, RaffleError> {
+        if account == referrer {
+            return Err(SelfReferral);
+        }
+
+        // the account is bound to its first referrer
+        if self.data::<Data>().referrers.contains(&account) {
+            return Ok(false);
+        }
+
+        self.data::<Data>().referrers.insert(&account, &referrer);
+        let nb_referrals = self
+            .data::<Data>()
+            .nb_referrals
+            .get(&referrer)
+            .unwrap_or_default()
+            .checked_add(1)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .nb_referrals
+            .insert(&referrer, &nb_referrals);
+
+        Ok(true)
+    }
+
+    /// compute the reward for the referrer of the account based on the amount paid
+    /// and save it in the total rewards of the referrer
+    fn add_referral_reward(
+        &mut self,
+        account: AccountId,
+        amount: Balance,
+    ) -> Result<Option<(AccountId, Balance)>, RaffleError> {
+        let referrer = match self.data::<Data>().referrers.get(&account) {
+            Some(referrer) => referrer,
+            None => return Ok(None),
+        };
+
+        let reward = amount
+            .checked_mul(self.get_referral_share() as Balance)
+            .ok_or(MulOverFlow)?
+            .checked_div(MAX_BASIS_POINTS as Balance)
+            .ok_or(DivByZero)?;
+        if reward == 0 {
+            return Ok(None);
+        }
+
+        let total_rewards = self
+            .data::<Data>()
+            .referral_rewards
+            .get(&referrer)
+            .unwrap_or_default()
+            .checked_add(reward)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .referral_rewards
+            .insert(&referrer, &total_rewards);
+
+        Ok(Some((referrer, reward)))
+    }
+
+    #[ink(message)]
+    fn get_referrer(&self, account: AccountId) -> Option<AccountId> {
+        self.data::<Data>().referrers.get(&account)
+    }
+
+    #[ink(message)]
+    fn get_nb_referrals(&self, referrer: AccountId) -> u32 {
+        self.data::<Data>()
+            .nb_referrals
+            .get(&referrer)
+            .unwrap_or_default()
+    }
+
+    /// return the total rewards earned by the referrer
+    #[ink(message)]
+    fn get_referral_rewards(&self, referrer: AccountId) -> Balance {
+        self.data::<Data>()
+            .referral_rewards
+            .get(&referrer)
+            .unwrap_or_default()
+    }
+}
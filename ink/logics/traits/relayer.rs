@@ -0,0 +1,48 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use openbrush::traits::{Balance, Storage};
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    #[lazy]
+    fee_policy: RelayerFeePolicy,
+}
+
+/// Fee paid by the player to the relayer who submits the participation on his behalf
+#[derive(Default, Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum RelayerFeePolicy {
+    /// the participations sent by a relayer are refused
+    #[default]
+    Disabled,
+    /// the relayer is not paid
+    Free,
+    /// the player pays a fixed fee to the relayer for each relayed participation
+    Fixed(Balance),
+}
+
+#[openbrush::trait_definition]
+pub trait RelayerFeeManager: Storage<Data> {
+    fn set_relayer_fee_policy(&mut self, fee_policy: RelayerFeePolicy) -> Result<(), RaffleError> {
+        self.data::<Data>().fee_policy.set(&fee_policy);
+        Ok(())
+    }
+
+    #[ink(message)]
+    fn get_relayer_fee_policy(&self) -> RelayerFeePolicy {
+        self.data::<Data>().fee_policy.get().unwrap_or_default()
+    }
+
+    /// return the fee paid to the relayer and throw an error if the relayers are not accepted
+    fn ensure_relayer_fee(&self) -> Result<Balance, RaffleError> {
+        match self.get_relayer_fee_policy() {
+            RelayerFeePolicy::Disabled => Err(RelayerDisabled),
+            RelayerFeePolicy::Free => Ok(0),
+            RelayerFeePolicy::Fixed(fee) => Ok(fee),
+        }
+    }
+}
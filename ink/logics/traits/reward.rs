@@ -1,6 +1,8 @@
 use crate::traits::error::RaffleError;
 use crate::traits::error::RaffleError::*;
+use crate::traits::{LotteryId, RaffleId};
 use ink::prelude::vec::Vec;
+use openbrush::contracts::pausable;
 use openbrush::storage::Mapping;
 use openbrush::traits::{AccountId, Balance, Storage};
 
@@ -9,51 +11,227 @@ use openbrush::traits::{AccountId, Balance, Storage};
 pub struct Data {
     pending_rewards: Mapping<AccountId, Balance>,
     total_pending_rewards: Balance,
+    /// funds kept by the contract but not available for the rewards (ie deposits)
+    #[lazy]
+    total_locked_funds: Balance,
+    /// amount available for the winners of each lottery
+    pots: Mapping<LotteryId, Balance>,
 }
 
 #[openbrush::trait_definition]
-pub trait RewardManager: Internal + Storage<Data> {
+pub trait RewardManager: Internal + Storage<Data> + Storage<pausable::Data> {
 
+    /// add the amount transferred in the pot of the lottery
     #[ink(message, payable)]
-    fn fund(&mut self) -> Result<(), RaffleError> {
+    fn fund(&mut self, lottery_id: LotteryId) -> Result<(), RaffleError> {
+        self.add_to_pot(lottery_id, Self::env().transferred_value())
+    }
+
+    /// add the given amount in the pot of the lottery
+    fn add_to_pot(&mut self, lottery_id: LotteryId, amount: Balance) -> Result<(), RaffleError> {
+        let pot = self
+            .get_pot(lottery_id)
+            .checked_add(amount)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>().pots.insert(&lottery_id, &pot);
         Ok(())
     }
 
-    fn add_winners(&mut self, accounts: Vec<AccountId>) -> Result<(), RaffleError> {
-        let mut total_pending_rewards = self.data::<Data>().total_pending_rewards;
+    /// return the amount available for the winners of the lottery
+    #[ink(message)]
+    fn get_pot(&self, lottery_id: LotteryId) -> Balance {
+        self.data::<Data>()
+            .pots
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+
+    /// pay the prize to the account with the pot of the lottery
+    fn pay_from_pot(
+        &mut self,
+        lottery_id: LotteryId,
+        account: AccountId,
+        amount: Balance,
+    ) -> Result<(), RaffleError> {
+        let pot = self
+            .get_pot(lottery_id)
+            .checked_sub(amount)
+            .ok_or(InsufficientReserve)?;
+        self.data::<Data>().pots.insert(&lottery_id, &pot);
+        self.add_pending_rewards(account, amount)
+    }
+
+    /// share the pot of the lottery between the winners of the raffle and return the reward for each winner
+    fn add_winners(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        accounts: Vec<AccountId>,
+    ) -> Result<Balance, RaffleError> {
+        let pot = self.get_pot(lottery_id);
+        self.share_prize(lottery_id, raffle_id, pot, accounts)
+    }
+
+    /// share the prize, taken from the pot of the lottery, between the winners of the raffle
+    /// and return the reward for each winner
+    fn share_prize(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        prize: Balance,
+        accounts: Vec<AccountId>,
+    ) -> Result<Balance, RaffleError> {
+        let nb_winners = accounts.len() as u128;
+        let reward = prize.checked_div(nb_winners).ok_or(DivByZero)?;
 
-        let reward = Self::env()
-            .balance()
-            .checked_sub(total_pending_rewards)
-            .ok_or(AddOverFlow)?
-            .checked_div(accounts.len() as u128)
-            .ok_or(DivByZero)?;
+        // the remainder of the division stays in the pot
+        let remaining_pot = self
+            .get_pot(lottery_id)
+            .checked_sub(reward.checked_mul(nb_winners).ok_or(MulOverFlow)?)
+            .ok_or(InsufficientReserve)?;
+        self.data::<Data>().pots.insert(&lottery_id, &remaining_pot);
 
         // iterate on the accounts (the winners)
         for account in accounts {
-            // compute the new rewards for this winner
-            let new_reward = match self.data::<Data>().pending_rewards.get(&account) {
-                Some(existing_reward) => existing_reward.checked_add(reward).ok_or(AddOverFlow)?,
-                _ => reward,
-            };
+            match self.get_reward_shares(lottery_id, raffle_id, account) {
+                // the reward is split between the members (ie syndicate)
+                Some(shares) => self.split_pending_rewards(lottery_id, shares, reward)?,
+                None => self.add_pending_rewards(account, reward)?,
+            }
+        }
+        Ok(reward)
+    }
+
+    /// split the amount between the accounts in proportion to their shares
+    /// The remainder of the division goes back to the pot of the lottery
+    fn split_pending_rewards(
+        &mut self,
+        lottery_id: LotteryId,
+        shares: Vec<(AccountId, Balance)>,
+        amount: Balance,
+    ) -> Result<(), RaffleError> {
+        let mut total_shares: Balance = 0;
+        for (_, s) in shares.iter() {
+            total_shares = total_shares.checked_add(*s).ok_or(AddOverFlow)?;
+        }
+
+        let mut remainder = amount;
+        for (account, s) in shares {
+            let reward = amount
+                .checked_mul(s)
+                .ok_or(MulOverFlow)?
+                .checked_div(total_shares)
+                .ok_or(DivByZero)?;
+            if reward > 0 {
+                self.add_pending_rewards(account, reward)?;
+                remainder = remainder.checked_sub(reward).ok_or(SubOverFlow)?;
+            }
+        }
+        if remainder > 0 {
+            self.add_to_pot(lottery_id, remainder)?;
+        }
+        Ok(())
+    }
+
+    /// add the given amount in the pending rewards of the account
+    fn add_pending_rewards(
+        &mut self,
+        account: AccountId,
+        amount: Balance,
+    ) -> Result<(), RaffleError> {
+        // compute the new rewards for this account
+        let new_reward = match self.data::<Data>().pending_rewards.get(&account) {
+            Some(existing_reward) => existing_reward.checked_add(amount).ok_or(AddOverFlow)?,
+            _ => amount,
+        };
+
+        // add the pending rewards for this account
+        self.data::<Data>()
+            .pending_rewards
+            .insert(&account, &new_reward);
+
+        self.emit_pending_reward_event(account, amount);
+
+        // update the total pending rewards
+        self.data::<Data>().total_pending_rewards = self
+            .data::<Data>()
+            .total_pending_rewards
+            .checked_add(amount)
+            .ok_or(AddOverFlow)?;
+        Ok(())
+    }
+
+    /// remove the given amount from the pending rewards of the account
+    /// The amount is not transferred and stays in the contract balance
+    fn debit_pending_rewards(
+        &mut self,
+        account: AccountId,
+        amount: Balance,
+    ) -> Result<(), RaffleError> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let pending_rewards = self
+            .data::<Data>()
+            .pending_rewards
+            .get(&account)
+            .ok_or(NoReward)?;
 
-            // add the pending rewards for this account
+        let remaining_rewards = pending_rewards
+            .checked_sub(amount)
+            .ok_or(InsufficientRewards)?;
+
+        if remaining_rewards == 0 {
+            self.data::<Data>().pending_rewards.remove(&account);
+        } else {
             self.data::<Data>()
                 .pending_rewards
-                .insert(&account, &new_reward);
+                .insert(&account, &remaining_rewards);
+        }
 
-            self.emit_pending_reward_event(account, reward);
+        // update the total pending rewards
+        self.data::<Data>().total_pending_rewards = self
+            .data::<Data>()
+            .total_pending_rewards
+            .checked_sub(amount)
+            .ok_or(SubOverFlow)?;
+        Ok(())
+    }
 
-            // update the total pending rewards
-            total_pending_rewards = total_pending_rewards
-                .checked_add(reward)
-                .ok_or(AddOverFlow)?;
-        }
-        // update the storage
-        self.data::<Data>().total_pending_rewards = total_pending_rewards;
+    /// lock the given amount, this amount will not be used for the rewards
+    fn lock_funds(&mut self, amount: Balance) -> Result<(), RaffleError> {
+        let total_locked_funds = self
+            .get_total_locked_funds()
+            .checked_add(amount)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .total_locked_funds
+            .set(&total_locked_funds);
         Ok(())
     }
 
+    /// unlock the given amount, this amount can be used for the rewards
+    fn unlock_funds(&mut self, amount: Balance) -> Result<(), RaffleError> {
+        let total_locked_funds = self
+            .get_total_locked_funds()
+            .checked_sub(amount)
+            .ok_or(SubOverFlow)?;
+        self.data::<Data>()
+            .total_locked_funds
+            .set(&total_locked_funds);
+        Ok(())
+    }
+
+    /// return the total locked funds
+    #[ink(message)]
+    fn get_total_locked_funds(&self) -> Balance {
+        self.data::<Data>()
+            .total_locked_funds
+            .get()
+            .unwrap_or_default()
+    }
+
     /// return the total pending reward
     #[ink(message)]
     fn get_total_pending_rewards(&mut self) -> Balance {
@@ -89,6 +267,7 @@ pub trait RewardManager: Internal + Storage<Data> {
     /// claim all pending rewards for the current account
     /// After claiming, there is not anymore pending rewards for this account
     #[ink(message)]
+    #[openbrush::modifiers(pausable::when_not_paused)]
     fn claim(&mut self) -> Result<(), RaffleError> {
         let from = Self::env().caller();
         self.inner_claim_from(from)
@@ -97,6 +276,7 @@ pub trait RewardManager: Internal + Storage<Data> {
     /// claim all pending rewards for the given account
     /// After claiming, there is not anymore pending rewards for this account
     #[ink(message)]
+    #[openbrush::modifiers(pausable::when_not_paused)]
     fn claim_from(&mut self, from: AccountId) -> Result<(), RaffleError> {
         self.inner_claim_from(from)
     }
@@ -132,4 +312,12 @@ pub trait RewardManager: Internal + Storage<Data> {
 pub trait Internal {
     fn emit_pending_reward_event(&self, account: AccountId, amount: Balance);
     fn emit_rewards_claimed_event(&self, account: AccountId, amount: Balance);
+    /// return the shares if the reward for the tickets of this account in the raffle must be split
+    /// between several accounts
+    fn get_reward_shares(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        account: AccountId,
+    ) -> Option<Vec<(AccountId, Balance)>>;
 }
@@ -0,0 +1,143 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use crate::traits::{LotteryId, Number, RaffleId};
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::Storage;
+
+/// Max number of counters returned by one call of the frequency queries
+pub const MAX_NUMBERS_BY_PAGE: u16 = 100;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    /// number of draws taken into account in the statistics of the lottery
+    nb_draws: Mapping<LotteryId, u32>,
+    /// how often each number was drawn in the past results of the lottery
+    drawn_counts: Mapping<(LotteryId, Number), u32>,
+    /// true if the numbers picked by the players are counted
+    #[lazy]
+    picks_tracking: bool,
+    /// how often each number was picked for a raffle of the lottery
+    picked_counts: Mapping<(LotteryId, RaffleId, Number), u32>,
+}
+
+#[openbrush::trait_definition]
+pub trait StatisticsManager: Storage<Data> {
+    fn set_picks_tracking(&mut self, picks_tracking: bool) {
+        self.data::<Data>().picks_tracking.set(&picks_tracking);
+    }
+
+    /// return true if the numbers picked by the players are counted
+    #[ink(message)]
+    fn is_picks_tracking(&self) -> bool {
+        self.data::<Data>().picks_tracking.get().unwrap_or_default()
+    }
+
+    /// count the numbers drawn for a raffle of the lottery
+    fn add_drawn_numbers(
+        &mut self,
+        lottery_id: LotteryId,
+        numbers: &[Number],
+    ) -> Result<(), RaffleError> {
+        for number in numbers {
+            let count = self
+                .get_drawn_count(lottery_id, *number)
+                .checked_add(1)
+                .ok_or(AddOverFlow)?;
+            self.data::<Data>()
+                .drawn_counts
+                .insert(&(lottery_id, *number), &count);
+        }
+        let nb_draws = self
+            .get_nb_draws(lottery_id)
+            .checked_add(1)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>().nb_draws.insert(&lottery_id, &nb_draws);
+        Ok(())
+    }
+
+    /// count the numbers picked by a player if the tracking is enabled
+    fn add_picked_numbers(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        numbers: &[Number],
+    ) -> Result<(), RaffleError> {
+        if !self.is_picks_tracking() {
+            return Ok(());
+        }
+        for number in numbers {
+            let count = self
+                .get_picked_count(lottery_id, raffle_id, *number)
+                .checked_add(1)
+                .ok_or(AddOverFlow)?;
+            self.data::<Data>()
+                .picked_counts
+                .insert(&(lottery_id, raffle_id, *number), &count);
+        }
+        Ok(())
+    }
+
+    /// return the number of draws taken into account in the statistics of the lottery
+    #[ink(message)]
+    fn get_nb_draws(&self, lottery_id: LotteryId) -> u32 {
+        self.data::<Data>()
+            .nb_draws
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+
+    /// return how often the number was drawn in the lottery
+    #[ink(message)]
+    fn get_drawn_count(&self, lottery_id: LotteryId, number: Number) -> u32 {
+        self.data::<Data>()
+            .drawn_counts
+            .get(&(lottery_id, number))
+            .unwrap_or_default()
+    }
+
+    /// return how often the number was picked for the raffle of the lottery
+    #[ink(message)]
+    fn get_picked_count(&self, lottery_id: LotteryId, raffle_id: RaffleId, number: Number) -> u32 {
+        self.data::<Data>()
+            .picked_counts
+            .get(&(lottery_id, raffle_id, number))
+            .unwrap_or_default()
+    }
+
+    /// return how often the numbers were drawn in the lottery, starting from the given number
+    /// (max `MAX_NUMBERS_BY_PAGE` numbers)
+    #[ink(message)]
+    fn get_drawn_frequencies(
+        &self,
+        lottery_id: LotteryId,
+        from: Number,
+        limit: u16,
+    ) -> Vec<(Number, u32)> {
+        numbers_page(from, limit)
+            .map(|number| (number, self.get_drawn_count(lottery_id, number)))
+            .collect()
+    }
+
+    /// return how often the numbers were picked for the raffle of the lottery,
+    /// starting from the given number (max `MAX_NUMBERS_BY_PAGE` numbers)
+    #[ink(message)]
+    fn get_picked_frequencies(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        from: Number,
+        limit: u16,
+    ) -> Vec<(Number, u32)> {
+        numbers_page(from, limit)
+            .map(|number| (number, self.get_picked_count(lottery_id, raffle_id, number)))
+            .collect()
+    }
+}
+
+/// numbers in the page starting from the given number
+fn numbers_page(from: Number, limit: u16) -> impl Iterator<Item = Number> {
+    let limit = limit.min(MAX_NUMBERS_BY_PAGE);
+    (from..=Number::MAX).take(limit as usize)
+}
@@ -0,0 +1,264 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use crate::traits::{LotteryId, Number, RaffleId};
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::{AccountId, Balance, Storage};
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    subscriptions: Mapping<(LotteryId, AccountId), Subscription>,
+    /// list of subscribers for a lottery ((lottery, index) -> account)
+    subscribers: Mapping<(LotteryId, u32), AccountId>,
+    /// position of the subscriber in the list ((lottery, account) -> index)
+    subscriber_indexes: Mapping<(LotteryId, AccountId), u32>,
+    nb_subscribers: Mapping<LotteryId, u32>,
+    /// position of the next subscriber to process for the current raffle of the lottery
+    next_subscriber: Mapping<LotteryId, u32>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Subscription {
+    /// numbers played for each draw
+    pub numbers: Vec<Number>,
+    /// number of draws not played yet
+    pub remaining_draws: u32,
+    /// price of the ticket paid for each draw
+    pub price_per_draw: Balance,
+    /// unused deposit
+    pub deposit: Balance,
+    /// last raffle for which the ticket has been registered
+    pub last_raffle_id: Option<RaffleId>,
+}
+
+/// Ticket registered for a subscriber
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SubscribedTicket {
+    pub subscriber: AccountId,
+    pub numbers: Vec<Number>,
+    pub price: Balance,
+}
+
+#[openbrush::trait_definition]
+pub trait SubscriptionManager: Storage<Data> {
+    /// save a new subscription to the lottery for the given account
+    fn add_subscription(
+        &mut self,
+        lottery_id: LotteryId,
+        subscriber: AccountId,
+        numbers: Vec<Number>,
+        nb_draws: u32,
+        price_per_draw: Balance,
+    ) -> Result<Subscription, RaffleError> {
+        if nb_draws == 0 {
+            return Err(IncorrectNbDraws);
+        }
+
+        if self
+            .data::<Data>()
+            .subscriptions
+            .contains(&(lottery_id, subscriber))
+        {
+            return Err(ExistingSubscription);
+        }
+
+        let deposit = price_per_draw
+            .checked_mul(nb_draws as Balance)
+            .ok_or(MulOverFlow)?;
+
+        let subscription = Subscription {
+            numbers,
+            remaining_draws: nb_draws,
+            price_per_draw,
+            deposit,
+            last_raffle_id: None,
+        };
+        self.data::<Data>()
+            .subscriptions
+            .insert(&(lottery_id, subscriber), &subscription);
+
+        // add the subscriber at the end of the list
+        let index = self.get_nb_subscribers(lottery_id);
+        self.data::<Data>()
+            .subscribers
+            .insert(&(lottery_id, index), &subscriber);
+        self.data::<Data>()
+            .subscriber_indexes
+            .insert(&(lottery_id, subscriber), &index);
+        let nb_subscribers = index.checked_add(1).ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .nb_subscribers
+            .insert(&lottery_id, &nb_subscribers);
+
+        Ok(subscription)
+    }
+
+    /// remove the subscription to the lottery for the given account and return it
+    fn remove_subscription(
+        &mut self,
+        lottery_id: LotteryId,
+        subscriber: AccountId,
+    ) -> Result<Subscription, RaffleError> {
+        let subscription = self
+            .data::<Data>()
+            .subscriptions
+            .get(&(lottery_id, subscriber))
+            .ok_or(NoSubscription)?;
+        let index = self
+            .data::<Data>()
+            .subscriber_indexes
+            .get(&(lottery_id, subscriber))
+            .ok_or(NoSubscription)?;
+
+        // move the last subscriber in the free position
+        let last_index = self
+            .get_nb_subscribers(lottery_id)
+            .checked_sub(1)
+            .ok_or(SubOverFlow)?;
+        if index != last_index {
+            let last_subscriber = self
+                .data::<Data>()
+                .subscribers
+                .get(&(lottery_id, last_index))
+                .ok_or(NoSubscription)?;
+            self.data::<Data>()
+                .subscribers
+                .insert(&(lottery_id, index), &last_subscriber);
+            self.data::<Data>()
+                .subscriber_indexes
+                .insert(&(lottery_id, last_subscriber), &index);
+        }
+        self.data::<Data>()
+            .subscribers
+            .remove(&(lottery_id, last_index));
+        self.data::<Data>()
+            .subscriber_indexes
+            .remove(&(lottery_id, subscriber));
+        self.data::<Data>()
+            .subscriptions
+            .remove(&(lottery_id, subscriber));
+        self.data::<Data>()
+            .nb_subscribers
+            .insert(&lottery_id, &last_index);
+
+        // the moved subscriber may not have been processed yet
+        if index < self.get_next_subscriber(lottery_id) {
+            self.data::<Data>()
+                .next_subscriber
+                .insert(&lottery_id, &index);
+        }
+
+        Ok(subscription)
+    }
+
+    /// restart the processing of the subscriptions for a new raffle of the lottery
+    fn reset_subscriptions_processing(&mut self, lottery_id: LotteryId) {
+        self.data::<Data>().next_subscriber.insert(&lottery_id, &0);
+    }
+
+    /// return the position of the next subscriber to process for the current raffle of the lottery
+    fn get_next_subscriber(&self, lottery_id: LotteryId) -> u32 {
+        self.data::<Data>()
+            .next_subscriber
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+
+    /// register the tickets of the next subscribers (up to `limit`) for the given raffle of the lottery
+    /// The subscriptions without remaining draw are removed
+    fn next_subscribed_tickets(
+        &mut self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        limit: u32,
+    ) -> Result<Vec<SubscribedTicket>, RaffleError> {
+        let mut tickets = Vec::new();
+        let mut nb_processed = 0;
+
+        while nb_processed < limit
+            && self.get_next_subscriber(lottery_id) < self.get_nb_subscribers(lottery_id)
+        {
+            nb_processed += 1;
+            let index = self.get_next_subscriber(lottery_id);
+            let subscriber = self
+                .data::<Data>()
+                .subscribers
+                .get(&(lottery_id, index))
+                .ok_or(NoSubscription)?;
+            let mut subscription = self
+                .data::<Data>()
+                .subscriptions
+                .get(&(lottery_id, subscriber))
+                .ok_or(NoSubscription)?;
+
+            if subscription.last_raffle_id == Some(raffle_id) {
+                // the ticket is already registered for this raffle
+                self.data::<Data>()
+                    .next_subscriber
+                    .insert(&lottery_id, &(index + 1));
+                continue;
+            }
+
+            subscription.remaining_draws = subscription
+                .remaining_draws
+                .checked_sub(1)
+                .ok_or(SubOverFlow)?;
+            subscription.deposit = subscription
+                .deposit
+                .checked_sub(subscription.price_per_draw)
+                .ok_or(SubOverFlow)?;
+            subscription.last_raffle_id = Some(raffle_id);
+
+            tickets.push(SubscribedTicket {
+                subscriber,
+                numbers: subscription.numbers.clone(),
+                price: subscription.price_per_draw,
+            });
+
+            if subscription.remaining_draws == 0 {
+                // the last subscriber is moved at this position and will be processed in the next iteration
+                self.remove_subscription(lottery_id, subscriber)?;
+            } else {
+                self.data::<Data>()
+                    .subscriptions
+                    .insert(&(lottery_id, subscriber), &subscription);
+                self.data::<Data>()
+                    .next_subscriber
+                    .insert(&lottery_id, &(index + 1));
+            }
+        }
+
+        Ok(tickets)
+    }
+
+    #[ink(message)]
+    fn get_subscription(
+        &self,
+        lottery_id: LotteryId,
+        subscriber: AccountId,
+    ) -> Option<Subscription> {
+        self.data::<Data>()
+            .subscriptions
+            .get(&(lottery_id, subscriber))
+    }
+
+    #[ink(message)]
+    fn get_nb_subscribers(&self, lottery_id: LotteryId) -> u32 {
+        self.data::<Data>()
+            .nb_subscribers
+            .get(&lottery_id)
+            .unwrap_or_default()
+    }
+
+    /// return the number of subscribers not processed yet for the current raffle of the lottery
+    #[ink(message)]
+    fn get_nb_pending_subscribers(&self, lottery_id: LotteryId) -> u32 {
+        self.get_nb_subscribers(lottery_id)
+            .saturating_sub(self.get_next_subscriber(lottery_id))
+    }
+}
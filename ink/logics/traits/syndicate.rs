@@ -0,0 +1,310 @@
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use crate::traits::{LotteryId, RaffleId};
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::{AccountId, Balance, Storage};
+
+pub type SyndicateId = u32;
+
+/// Max number of members in a syndicate
+pub const MAX_SYNDICATE_MEMBERS: usize = 50;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    #[lazy]
+    next_syndicate_id: SyndicateId,
+    syndicates: Mapping<SyndicateId, Syndicate>,
+    /// account registered as participant for the tickets of the syndicate (account -> syndicate)
+    syndicate_ids: Mapping<AccountId, SyndicateId>,
+    /// contributions of the members not used yet to buy tickets (syndicate, member) -> balance
+    balances: Mapping<(SyndicateId, AccountId), Balance>,
+    /// part of each member in the tickets bought for a raffle (syndicate, lottery, raffle) -> shares
+    raffle_shares: Mapping<(SyndicateId, LotteryId, RaffleId), Vec<(AccountId, Balance)>>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Syndicate {
+    /// member allowed to add the members and to buy the tickets with the pooled balance
+    pub manager: AccountId,
+    /// account registered as participant for the tickets of the syndicate
+    pub account: AccountId,
+    /// accounts allowed to contribute (the manager is the first member)
+    pub members: Vec<AccountId>,
+    /// pooled balance not used yet to buy tickets
+    pub balance: Balance,
+}
+
+#[openbrush::trait_definition]
+pub trait SyndicateManager: Storage<Data> {
+    /// create a new syndicate managed by the given account
+    fn add_syndicate(&mut self, manager: AccountId) -> Result<Syndicate, RaffleError> {
+        let syndicate_id = self
+            .data::<Data>()
+            .next_syndicate_id
+            .get()
+            .unwrap_or_default();
+
+        // build the account registered as participant for the tickets of the syndicate
+        let mut output =
+            <ink::env::hash::Blake2x256 as ink::env::hash::HashOutput>::Type::default();
+        ink::env::hash_encoded::<ink::env::hash::Blake2x256, _>(
+            &(b"syndicate", Self::env().account_id(), syndicate_id),
+            &mut output,
+        );
+        let account = AccountId::from(output);
+
+        let syndicate = Syndicate {
+            manager,
+            account,
+            members: ink::prelude::vec![manager],
+            balance: 0,
+        };
+        self.data::<Data>()
+            .syndicates
+            .insert(syndicate_id, &syndicate);
+        self.data::<Data>()
+            .syndicate_ids
+            .insert(&account, &syndicate_id);
+        let next_syndicate_id = syndicate_id.checked_add(1).ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .next_syndicate_id
+            .set(&next_syndicate_id);
+
+        Ok(syndicate)
+    }
+
+    /// allow the account to contribute to the syndicate (syndicate manager only)
+    fn add_syndicate_member(
+        &mut self,
+        syndicate_id: SyndicateId,
+        manager: AccountId,
+        member: AccountId,
+    ) -> Result<(), RaffleError> {
+        let mut syndicate = self.ensure_syndicate(syndicate_id)?;
+
+        if syndicate.manager != manager {
+            return Err(NotSyndicateManager);
+        }
+        if syndicate.members.contains(&member) {
+            return Err(ExistingSyndicateMember);
+        }
+        if syndicate.members.len() >= MAX_SYNDICATE_MEMBERS {
+            return Err(TooManyMembers);
+        }
+
+        syndicate.members.push(member);
+        self.data::<Data>()
+            .syndicates
+            .insert(syndicate_id, &syndicate);
+
+        Ok(())
+    }
+
+    /// add the amount in the pooled balance and in the balance of the member
+    fn add_syndicate_contribution(
+        &mut self,
+        syndicate_id: SyndicateId,
+        member: AccountId,
+        amount: Balance,
+    ) -> Result<(), RaffleError> {
+        if amount == 0 {
+            return Err(IncorrectPayment);
+        }
+
+        let mut syndicate = self.ensure_syndicate(syndicate_id)?;
+        if !syndicate.members.contains(&member) {
+            return Err(NotSyndicateMember);
+        }
+
+        let balance = self
+            .get_syndicate_member_balance(syndicate_id, member)
+            .checked_add(amount)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .balances
+            .insert((syndicate_id, member), &balance);
+
+        syndicate.balance = syndicate.balance.checked_add(amount).ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .syndicates
+            .insert(syndicate_id, &syndicate);
+
+        Ok(())
+    }
+
+    /// remove the amount from the pooled balance and from the balance of the member
+    fn withdraw_syndicate_contribution(
+        &mut self,
+        syndicate_id: SyndicateId,
+        member: AccountId,
+        amount: Balance,
+    ) -> Result<(), RaffleError> {
+        let mut syndicate = self.ensure_syndicate(syndicate_id)?;
+
+        let balance = self
+            .get_syndicate_member_balance(syndicate_id, member)
+            .checked_sub(amount)
+            .ok_or(InsufficientSyndicateBalance)?;
+        self.data::<Data>()
+            .balances
+            .insert((syndicate_id, member), &balance);
+
+        syndicate.balance = syndicate
+            .balance
+            .checked_sub(amount)
+            .ok_or(InsufficientSyndicateBalance)?;
+        self.data::<Data>()
+            .syndicates
+            .insert(syndicate_id, &syndicate);
+
+        Ok(())
+    }
+
+    /// remove the amount from the pooled balance to buy the tickets of the raffle and return the syndicate.
+    /// Each member pays in proportion to his balance and his part in the tickets of the raffle is saved
+    fn spend_syndicate_balance(
+        &mut self,
+        syndicate_id: SyndicateId,
+        manager: AccountId,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        amount: Balance,
+    ) -> Result<Syndicate, RaffleError> {
+        let mut syndicate = self.ensure_syndicate(syndicate_id)?;
+
+        if syndicate.manager != manager {
+            return Err(NotSyndicateManager);
+        }
+
+        // the shares of the members are given by the price paid
+        if amount == 0 {
+            return Err(IncorrectPayment);
+        }
+
+        let total_balance = syndicate.balance;
+        syndicate.balance = total_balance
+            .checked_sub(amount)
+            .ok_or(InsufficientSyndicateBalance)?;
+
+        // each member pays in proportion to his balance
+        let mut payments = Vec::new();
+        let mut total_paid: Balance = 0;
+        for member in syndicate.members.iter() {
+            let balance = self.get_syndicate_member_balance(syndicate_id, *member);
+            let paid = amount
+                .checked_mul(balance)
+                .ok_or(MulOverFlow)?
+                .checked_div(total_balance)
+                .ok_or(DivByZero)?;
+            total_paid = total_paid.checked_add(paid).ok_or(AddOverFlow)?;
+            payments.push((*member, balance, paid));
+        }
+
+        // the remainder of the division is paid by the first members with enough balance
+        let mut remainder = amount.checked_sub(total_paid).ok_or(SubOverFlow)?;
+        for (_, balance, paid) in payments.iter_mut() {
+            if remainder == 0 {
+                break;
+            }
+            if *balance > *paid {
+                *paid = paid.checked_add(1).ok_or(AddOverFlow)?;
+                remainder -= 1;
+            }
+        }
+
+        let mut shares = self
+            .data::<Data>()
+            .raffle_shares
+            .get((syndicate_id, lottery_id, raffle_id))
+            .unwrap_or_default();
+        for (member, balance, paid) in payments {
+            if paid == 0 {
+                continue;
+            }
+            let balance = balance
+                .checked_sub(paid)
+                .ok_or(InsufficientSyndicateBalance)?;
+            self.data::<Data>()
+                .balances
+                .insert((syndicate_id, member), &balance);
+
+            match shares.iter_mut().find(|(account, _)| *account == member) {
+                Some((_, member_shares)) => {
+                    *member_shares = member_shares.checked_add(paid).ok_or(AddOverFlow)?
+                }
+                None => shares.push((member, paid)),
+            }
+        }
+        self.data::<Data>()
+            .raffle_shares
+            .insert((syndicate_id, lottery_id, raffle_id), &shares);
+
+        self.data::<Data>()
+            .syndicates
+            .insert(syndicate_id, &syndicate);
+
+        Ok(syndicate)
+    }
+
+    /// return the syndicate or throw an error if it does not exist
+    fn ensure_syndicate(&self, syndicate_id: SyndicateId) -> Result<Syndicate, RaffleError> {
+        self.data::<Data>()
+            .syndicates
+            .get(syndicate_id)
+            .ok_or(UnknownSyndicate)
+    }
+
+    /// return the shares of the members in the tickets of the raffle if the account is used by a syndicate
+    fn get_syndicate_shares(
+        &self,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        account: AccountId,
+    ) -> Option<Vec<(AccountId, Balance)>> {
+        let syndicate_id = self.data::<Data>().syndicate_ids.get(&account)?;
+        self.get_syndicate_raffle_shares(syndicate_id, lottery_id, raffle_id)
+    }
+
+    #[ink(message)]
+    fn get_syndicate(&self, syndicate_id: SyndicateId) -> Option<Syndicate> {
+        self.data::<Data>().syndicates.get(syndicate_id)
+    }
+
+    /// return the syndicate using this account to register its tickets
+    #[ink(message)]
+    fn get_syndicate_id(&self, account: AccountId) -> Option<SyndicateId> {
+        self.data::<Data>().syndicate_ids.get(&account)
+    }
+
+    /// return the contributions of the member not used yet to buy tickets
+    #[ink(message)]
+    fn get_syndicate_member_balance(
+        &self,
+        syndicate_id: SyndicateId,
+        member: AccountId,
+    ) -> Balance {
+        self.data::<Data>()
+            .balances
+            .get((syndicate_id, member))
+            .unwrap_or_default()
+    }
+
+    /// return the part of each member in the tickets bought by the syndicate for the raffle
+    #[ink(message)]
+    fn get_syndicate_raffle_shares(
+        &self,
+        syndicate_id: SyndicateId,
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+    ) -> Option<Vec<(AccountId, Balance)>> {
+        self.data::<Data>()
+            .raffle_shares
+            .get((syndicate_id, lottery_id, raffle_id))
+    }
+}
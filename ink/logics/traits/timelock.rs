@@ -0,0 +1,177 @@
+use crate::traits::config::{Config, PrizeTier};
+use crate::traits::error::RaffleError;
+use crate::traits::error::RaffleError::*;
+use crate::traits::relayer::RelayerFeePolicy;
+use crate::traits::LotteryId;
+use ink::prelude::vec::Vec;
+use openbrush::contracts::access_control::RoleType;
+use openbrush::storage::Mapping;
+use openbrush::traits::{AccountId, Balance, Hash, Storage, Timestamp};
+
+pub type OperationId = u32;
+
+/// Delay (in milliseconds) set by the migration for the contracts deployed before the timelock (one day)
+pub const DEFAULT_MIN_DELAY: Timestamp = 86_400_000;
+
+#[derive(Default, Debug)]
+#[openbrush::storage_item]
+pub struct Data {
+    /// delay (in milliseconds) between the scheduling and the execution of an operation
+    #[lazy]
+    min_delay: Timestamp,
+    #[lazy]
+    next_operation_id: OperationId,
+    /// operations scheduled and not executed or cancelled yet
+    operations: Mapping<OperationId, ScheduledOperation>,
+}
+
+/// Sensitive operation executed after a delay
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Operation {
+    /// update the config of the lottery
+    SetConfig(LotteryId, Config),
+    /// update the multipliers of the stake for a number of picks of the keno lottery
+    SetPaytable(LotteryId, u8, Vec<u32>),
+    /// grant the role to the account
+    GrantRole(RoleType, Option<AccountId>),
+    /// revoke the role of the account
+    RevokeRole(RoleType, Option<AccountId>),
+    /// transfer the amount to the account
+    Withdraw(AccountId, Balance),
+    /// upgrade the code of the contract
+    SetCodeHash(Hash),
+    /// update the delay of the timelock
+    SetMinDelay(Timestamp),
+    /// update the prize tiers of the lottery
+    SetPrizeTiers(LotteryId, Vec<PrizeTier>),
+    /// update the share of the ticket price paid to the referrer (in basis points)
+    SetReferralShare(u16),
+    /// update the fee paid to the relayers
+    SetRelayerFeePolicy(RelayerFeePolicy),
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ScheduledOperation {
+    pub operation: Operation,
+    pub scheduled_by: AccountId,
+    /// timestamp from which the operation can be executed
+    pub ready_at: Timestamp,
+}
+
+#[openbrush::trait_definition]
+pub trait TimelockManager: Storage<Data> {
+    /// the delay can not be zero, so the operations are never executed without notice
+    fn set_min_delay(&mut self, min_delay: Timestamp) -> Result<(), RaffleError> {
+        if min_delay == 0 {
+            return Err(IncorrectDelay);
+        }
+        self.data::<Data>().min_delay.set(&min_delay);
+        Ok(())
+    }
+
+    /// return the delay (in milliseconds) before a scheduled operation can be executed
+    #[ink(message)]
+    fn get_min_delay(&self) -> Timestamp {
+        self.data::<Data>().min_delay.get().unwrap_or_default()
+    }
+
+    /// save the operation in the queue, it will be ready after the min delay
+    fn schedule_operation(
+        &mut self,
+        operation: Operation,
+        scheduled_by: AccountId,
+    ) -> Result<(OperationId, ScheduledOperation), RaffleError> {
+        let ready_at = Self::env()
+            .block_timestamp()
+            .checked_add(self.get_min_delay())
+            .ok_or(AddOverFlow)?;
+
+        let operation_id = self.get_next_operation_id();
+        let next_operation_id = operation_id.checked_add(1).ok_or(AddOverFlow)?;
+        self.data::<Data>()
+            .next_operation_id
+            .set(&next_operation_id);
+
+        let scheduled_operation = ScheduledOperation {
+            operation,
+            scheduled_by,
+            ready_at,
+        };
+        self.data::<Data>()
+            .operations
+            .insert(&operation_id, &scheduled_operation);
+
+        Ok((operation_id, scheduled_operation))
+    }
+
+    /// remove the operation from the queue
+    fn cancel_operation(
+        &mut self,
+        operation_id: OperationId,
+    ) -> Result<ScheduledOperation, RaffleError> {
+        let scheduled_operation = self
+            .data::<Data>()
+            .operations
+            .get(&operation_id)
+            .ok_or(UnknownOperation)?;
+        self.data::<Data>().operations.remove(&operation_id);
+        Ok(scheduled_operation)
+    }
+
+    /// remove the operation from the queue if the delay is over
+    fn take_ready_operation(
+        &mut self,
+        operation_id: OperationId,
+    ) -> Result<ScheduledOperation, RaffleError> {
+        let scheduled_operation = self
+            .data::<Data>()
+            .operations
+            .get(&operation_id)
+            .ok_or(UnknownOperation)?;
+        if scheduled_operation.ready_at > Self::env().block_timestamp() {
+            return Err(OperationNotReady);
+        }
+        self.cancel_operation(operation_id)
+    }
+
+    #[ink(message)]
+    fn get_operation(&self, operation_id: OperationId) -> Option<ScheduledOperation> {
+        self.data::<Data>().operations.get(&operation_id)
+    }
+
+    /// return the identifier of the next scheduled operation
+    #[ink(message)]
+    fn get_next_operation_id(&self) -> OperationId {
+        self.data::<Data>()
+            .next_operation_id
+            .get()
+            .unwrap_or_default()
+    }
+
+    /// return the operations scheduled and not executed or cancelled yet,
+    /// among the `limit` operations from the identifier `from`
+    #[ink(message)]
+    fn get_pending_operations(
+        &self,
+        from: OperationId,
+        limit: u32,
+    ) -> Vec<(OperationId, ScheduledOperation)> {
+        let to = from.saturating_add(limit).min(self.get_next_operation_id());
+        (from..to)
+            .filter_map(|id| {
+                self.data::<Data>()
+                    .operations
+                    .get(&id)
+                    .map(|operation| (id, operation))
+            })
+            .collect()
+    }
+}
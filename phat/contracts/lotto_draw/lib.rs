@@ -5,8 +5,10 @@ extern crate core;
 
 #[ink::contract(env = pink_extension::PinkEnvironment)]
 mod lotto_draw {
+    use alloc::boxed::Box;
     use alloc::vec::Vec;
     use ink::prelude::{format, string::String};
+    use phat_offchain_rollup::clients::evm::{Action as EvmAction, EvmRollupClient};
     use phat_offchain_rollup::clients::ink::{Action, ContractId, InkRollupClient};
     use pink_extension::chain_extension::signing;
     use pink_extension::{debug, error, http_post, info, vrf, ResultExt};
@@ -15,58 +17,60 @@ mod lotto_draw {
     use serde_json_core;
     use sp_core::crypto::{AccountId32, Ss58Codec};
 
-    pub type RaffleId = u32;
-    pub type Number = u16;
+    /// Messages exchanged with the Ink! smart contract
+    pub use lotto_protocol::{
+        BonusPool, LotteryId, LottoRequestMessage, LottoResponseMessage, Number, ProtocolError,
+        RaffleId, Request, Response, TicketIndex,
+    };
 
-    /// Message to request the lotto lotto_draw or the list of winners
-    /// message pushed in the queue by the Ink! smart contract and read by the offchain rollup
-    #[derive(Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub struct LottoRequestMessage {
-        /// lotto_draw number
-        raffle_id: RaffleId,
-        /// request
-        request: Request,
+    /// Messages exchanged with the EVM smart contract
+    pub use lotto_protocol::evm::{EvmResponse, EvmResponseMessage, H160};
+
+    /// Address of the rollup anchor on an EVM blockchain
+    pub type EvmContractId = [u8; 20];
+
+    /// Lottery used by the contracts deployed before the support of several lotteries
+    pub const DEFAULT_LOTTERY_ID: LotteryId = 0;
+
+    /// Prefix of the key used by the Ink! smart contract to save why a response was rejected
+    const MESSAGE_REJECTED: u32 = ink::selector_id!("MESSAGE_REJECTED");
+
+    /// Prefix of the keys used by the Ink! smart contract to save the participations on chain
+    const PARTICIPATION: u32 = ink::selector_id!("PARTICIPATION");
+    /// Prefix of the key used by the Ink! smart contract to save the number of participations of a raffle
+    const NB_PARTICIPATIONS: u32 = ink::selector_id!("NB_PARTICIPATIONS");
+    /// Prefix of the keys used by the Ink! smart contract to save the numbers of the keno tickets
+    const KENO_TICKET: u32 = ink::selector_id!("KENO_TICKET");
+    /// Max number of participations (or keno tickets) of a raffle read in the kv store, same limit
+    /// as the Ink! smart contract. Each of them is read with one call to the RPC node
+    const MAX_ONCHAIN_PARTICIPATIONS: u32 = 100;
+
+    /// Max number of requests answered in one rollup transaction
+    pub const MAX_REQUESTS_BY_BATCH: u32 = 10;
+    /// Prefix of the key used in the local cache to save the first request of the last batch of a target
+    const LAST_BATCH: u32 = ink::selector_id!("LAST_BATCH");
+
+    /// Summary of the requests answered in one rollup transaction
+    #[derive(Encode, Decode, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AnswerSummary {
+        /// lottery id and raffle id of the answered requests
+        pub processed: Vec<(LotteryId, RaffleId)>,
+        /// lottery id, raffle id and error of the requests not answered
+        /// (sent back with an error response, so the Ink! smart contract queues them again)
+        pub failed: Vec<(LotteryId, RaffleId, ContractError)>,
+        /// id of the rollup transaction (None if nothing is submitted)
+        pub tx_id: Option<Vec<u8>>,
     }
 
-    #[derive(Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub enum Request {
-        /// request to lotto_draw the n number between min and max values
-        /// arg1: number of numbers for the lotto_draw
-        /// arg2:  smallest number for the lotto_draw
-        /// arg2:  biggest number for the lotto_draw
-        DrawNumbers(u8, Number, Number),
-        /// request to check if there is a winner for the given numbers
-        CheckWinners(Vec<Number>),
-    }
-
-    /// Message sent to provide the lotto lotto_draw or the list of winners
-    /// response pushed in the queue by the offchain rollup and read by the Ink! smart contract
-    #[derive(Encode, Decode)]
-    struct LottoResponseMessage {
-        /// initial request
-        request: LottoRequestMessage,
-        /// response
-        response: Response,
-    }
-
-    #[derive(Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub enum Response {
-        /// list of numbers
-        Numbers(Vec<Number>),
-        /// list of winners
-        Winners(Vec<AccountId>),
+    /// Response computed for the next request without submitting the rollup transaction
+    #[derive(Encode, Decode, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct DryRunAnswer {
+        /// response which would be sent to the Ink! smart contract
+        pub response: LottoResponseMessage,
+        /// reply action (encoded) which would be attached to the transaction
+        pub action: Vec<u8>,
     }
 
     /// DTO use for serializing and deserializing the json
@@ -95,13 +99,104 @@ mod lotto_draw {
         accountId: &'a str,
     }
 
+    /// DTO use for deserializing the tickets with their numbers
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct TicketsIndexerResponse<'a> {
+        #[serde(borrow)]
+        data: TicketsIndexerResponseData<'a>,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct TicketsIndexerResponseData<'a> {
+        #[serde(borrow)]
+        participations: Tickets<'a>,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct Tickets<'a> {
+        #[serde(borrow)]
+        nodes: Vec<TicketNode<'a>>,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    #[allow(non_snake_case)]
+    struct TicketNode<'a> {
+        accountId: &'a str,
+        numbers: Vec<Number>,
+        /// missing for the participations without bonus numbers
+        #[serde(default)]
+        bonusNumbers: Vec<Number>,
+    }
+
+    /// DTO use for deserializing the keno tickets
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct KenoIndexerResponse {
+        data: KenoIndexerResponseData,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    #[allow(non_snake_case)]
+    struct KenoIndexerResponseData {
+        kenoTickets: KenoTickets,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct KenoTickets {
+        nodes: Vec<KenoTicketNode>,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    #[allow(non_snake_case)]
+    struct KenoTicketNode {
+        ticketId: TicketIndex,
+        numbers: Vec<Number>,
+    }
+
+    /// DTO use for deserializing the participations returned by SubSquid (list without nodes)
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct SubSquidResponse<'a> {
+        #[serde(borrow)]
+        data: SubSquidResponseData<'a>,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct SubSquidResponseData<'a> {
+        #[serde(borrow)]
+        participations: Vec<ParticipationNode<'a>>,
+    }
+
+    /// DTO use for deserializing the tickets returned by SubSquid
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct SubSquidTicketsResponse<'a> {
+        #[serde(borrow)]
+        data: SubSquidTicketsResponseData<'a>,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct SubSquidTicketsResponseData<'a> {
+        #[serde(borrow)]
+        participations: Vec<TicketNode<'a>>,
+    }
+
+    /// DTO use for deserializing the keno tickets returned by SubSquid
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    struct SubSquidKenoResponse {
+        data: SubSquidKenoResponseData,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    #[allow(non_snake_case)]
+    struct SubSquidKenoResponseData {
+        kenoTickets: Vec<KenoTicketNode>,
+    }
+
     #[ink(storage)]
     pub struct Lotto {
         owner: AccountId,
-        /// config to send the data to the ink! smart contract
-        consumer_config: Option<Config>,
-        /// indexer endpoint
-        indexer_url: Option<String>,
+        /// config of each target (ink! smart contract), in the order of their registration
+        targets: Vec<Config>,
+        /// config of each EVM target (EVM smart contract), in the order of their registration
+        evm_targets: Vec<EvmConfig>,
         /// Key for signing the rollup tx.
         attest_key: [u8; 32],
     }
@@ -120,6 +215,464 @@ mod lotto_draw {
         contract_id: ContractId,
         /// Key for sending out the rollup meta-tx. None to fallback to the wallet based auth.
         sender_key: Option<[u8; 32]>,
+        /// source of the participations used to find the winners
+        winner_source: Option<WinnerSource>,
+    }
+
+    #[derive(Encode, Decode, Debug)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    struct EvmConfig {
+        /// The RPC endpoint of the target blockchain
+        rpc: String,
+        /// The rollup anchor address on the target blockchain
+        contract_id: EvmContractId,
+        /// Key for sending out the rollup meta-tx. None to fallback to the wallet based auth.
+        sender_key: Option<[u8; 32]>,
+        /// source of the participations used to find the winners
+        winner_source: Option<WinnerSource>,
+    }
+
+    /// Source of the participations used to find the winners and to count the keno matches
+    #[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum WinnerSource {
+        /// endpoint of a SubQuery indexer
+        SubQuery(String),
+        /// endpoint of a SubSquid indexer
+        SubSquid(String),
+        /// participations saved by the Ink! smart contract in the kv store, read with the rollup client
+        /// (only for the Ink! smart contracts with the onchain participations enabled)
+        OnChain,
+    }
+
+    impl WinnerSource {
+        /// endpoint of the indexer (None when the participations are read on chain)
+        fn indexer_url(&self) -> Option<&String> {
+            match self {
+                WinnerSource::SubQuery(url) | WinnerSource::SubSquid(url) => Some(url),
+                WinnerSource::OnChain => None,
+            }
+        }
+    }
+
+    /// Config of a target used to draw the numbers and to read its participations
+    trait TargetConfig {
+        /// Address of the participants in the list of winners
+        type Participant: core::fmt::Debug;
+
+        /// Address of the rollup anchor, added in the salt so each target draws its own numbers
+        fn anchor_address(&self) -> &[u8];
+
+        /// Reader of the participations for the winner source of the target
+        fn participation_reader(
+            &self,
+        ) -> Result<Box<dyn ParticipationReader<Self::Participant> + '_>>;
+
+        /// Parses the address of a participant saved by the indexer
+        fn parse_participant(address: &str) -> Result<Self::Participant>;
+    }
+
+    impl TargetConfig for Config {
+        type Participant = AccountId;
+
+        fn anchor_address(&self) -> &[u8] {
+            &self.contract_id
+        }
+
+        fn participation_reader(&self) -> Result<Box<dyn ParticipationReader<AccountId> + '_>> {
+            match &self.winner_source {
+                Some(WinnerSource::SubQuery(url)) => Ok(Box::new(SubQueryIndexer {
+                    url,
+                    parse_participant: Self::parse_participant,
+                })),
+                Some(WinnerSource::SubSquid(url)) => Ok(Box::new(SubSquidIndexer {
+                    url,
+                    parse_participant: Self::parse_participant,
+                })),
+                Some(WinnerSource::OnChain) => Ok(Box::new(RollupStorage { config: self })),
+                None => Err(ContractError::IndexerNotConfigured),
+            }
+        }
+
+        fn parse_participant(address: &str) -> Result<AccountId> {
+            // build the accountId from the string address
+            let account_id =
+                AccountId32::from_ss58check(address).or(Err(ContractError::InvalidSs58Address))?;
+            let address_hex: [u8; 32] = scale::Encode::encode(&account_id)
+                .try_into()
+                .or(Err(ContractError::InvalidKeyLength))?;
+            Ok(AccountId::from(address_hex))
+        }
+    }
+
+    impl TargetConfig for EvmConfig {
+        type Participant = H160;
+
+        fn anchor_address(&self) -> &[u8] {
+            &self.contract_id
+        }
+
+        fn participation_reader(&self) -> Result<Box<dyn ParticipationReader<H160> + '_>> {
+            match &self.winner_source {
+                Some(WinnerSource::SubQuery(url)) => Ok(Box::new(SubQueryIndexer {
+                    url,
+                    parse_participant: Self::parse_participant,
+                })),
+                Some(WinnerSource::SubSquid(url)) => Ok(Box::new(SubSquidIndexer {
+                    url,
+                    parse_participant: Self::parse_participant,
+                })),
+                // the participations are saved on chain only by the Ink! smart contract
+                Some(WinnerSource::OnChain) => Err(ContractError::UnsupportedWinnerSource),
+                None => Err(ContractError::IndexerNotConfigured),
+            }
+        }
+
+        fn parse_participant(address: &str) -> Result<H160> {
+            // the indexer saves the EVM address in hex
+            let mut address_hex = [0u8; 20];
+            hex::decode_to_slice(address.trim_start_matches("0x"), &mut address_hex)
+                .or(Err(ContractError::InvalidEvmAddress))?;
+            Ok(H160::from(address_hex))
+        }
+    }
+
+    /// Response computed for a target, with the address of its participants in the lists of winners.
+    /// It is converted into the response sent to the Ink! or the EVM smart contract
+    enum TargetResponse<P> {
+        Numbers(Vec<Number>),
+        Winners(Vec<P>),
+        QuickPickNumbers(Vec<Vec<Number>>),
+        Tickets(Vec<TicketIndex>),
+        KenoMatches(Vec<(TicketIndex, u8)>),
+        NumbersAndWinners(Vec<Number>, Vec<P>),
+        TierWinners(Vec<Vec<P>>),
+    }
+
+    impl From<TargetResponse<AccountId>> for Response {
+        fn from(response: TargetResponse<AccountId>) -> Self {
+            match response {
+                TargetResponse::Numbers(numbers) => Response::Numbers(numbers),
+                TargetResponse::Winners(winners) => Response::Winners(winners),
+                TargetResponse::QuickPickNumbers(tickets) => Response::QuickPickNumbers(tickets),
+                TargetResponse::Tickets(tickets) => Response::Tickets(tickets),
+                TargetResponse::KenoMatches(matches) => Response::KenoMatches(matches),
+                TargetResponse::NumbersAndWinners(numbers, winners) => {
+                    Response::NumbersAndWinners(numbers, winners)
+                }
+                TargetResponse::TierWinners(winners) => Response::TierWinners(winners),
+            }
+        }
+    }
+
+    impl From<TargetResponse<H160>> for EvmResponse {
+        fn from(response: TargetResponse<H160>) -> Self {
+            match response {
+                TargetResponse::Numbers(numbers) => EvmResponse::Numbers(numbers),
+                TargetResponse::Winners(winners) => EvmResponse::Winners(winners),
+                TargetResponse::QuickPickNumbers(tickets) => EvmResponse::QuickPickNumbers(tickets),
+                TargetResponse::Tickets(tickets) => EvmResponse::Tickets(tickets),
+                TargetResponse::KenoMatches(matches) => EvmResponse::KenoMatches(matches),
+                TargetResponse::NumbersAndWinners(numbers, winners) => {
+                    EvmResponse::NumbersAndWinners(numbers, winners)
+                }
+                TargetResponse::TierWinners(winners) => EvmResponse::TierWinners(winners),
+            }
+        }
+    }
+
+    /// Reads the participations of a raffle from a winner source
+    trait ParticipationReader<P> {
+        /// Returns the participants who picked all the drawn numbers and bonus numbers
+        fn get_winners(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            numbers: &[Number],
+            bonus_numbers: &[Number],
+        ) -> Result<Vec<P>>;
+
+        /// Returns the participant, the picked numbers and the picked bonus numbers
+        /// of each ticket of the raffle
+        fn get_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(P, Vec<Number>, Vec<Number>)>>;
+
+        /// Returns the index and the picked numbers of each keno ticket of the raffle
+        fn get_keno_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(TicketIndex, Vec<Number>)>>;
+    }
+
+    /// Participations indexed by SubQuery
+    struct SubQueryIndexer<'a, P> {
+        url: &'a str,
+        parse_participant: fn(&str) -> Result<P>,
+    }
+
+    impl<P> ParticipationReader<P> for SubQueryIndexer<'_, P> {
+        fn get_winners(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            numbers: &[Number],
+            bonus_numbers: &[Number],
+        ) -> Result<Vec<P>> {
+            let body = subquery_winners_query(lottery_id, raffle_id, numbers, bonus_numbers);
+
+            // query the indexer
+            let resp_body = query_indexer(self.url, body)?;
+
+            // parse the result
+            let result: IndexerResponse = serde_json_core::from_slice(resp_body.as_slice())
+                .or(Err(ContractError::InvalidResponseBody))?
+                .0;
+
+            result
+                .data
+                .participations
+                .nodes
+                .iter()
+                .map(|w| (self.parse_participant)(w.accountId))
+                .collect()
+        }
+
+        fn get_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(P, Vec<Number>, Vec<Number>)>> {
+            let body = subquery_tickets_query(lottery_id, raffle_id);
+
+            // query the indexer
+            let resp_body = query_indexer(self.url, body)?;
+
+            // parse the result
+            let result: TicketsIndexerResponse = serde_json_core::from_slice(resp_body.as_slice())
+                .or(Err(ContractError::InvalidResponseBody))?
+                .0;
+
+            result
+                .data
+                .participations
+                .nodes
+                .into_iter()
+                .map(|t| {
+                    Ok((
+                        (self.parse_participant)(t.accountId)?,
+                        t.numbers,
+                        t.bonusNumbers,
+                    ))
+                })
+                .collect()
+        }
+
+        fn get_keno_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(TicketIndex, Vec<Number>)>> {
+            let body = subquery_keno_query(lottery_id, raffle_id);
+
+            // query the indexer
+            let resp_body = query_indexer(self.url, body)?;
+
+            // parse the result
+            let result: KenoIndexerResponse = serde_json_core::from_slice(resp_body.as_slice())
+                .or(Err(ContractError::InvalidResponseBody))?
+                .0;
+
+            Ok(result
+                .data
+                .kenoTickets
+                .nodes
+                .into_iter()
+                .map(|ticket| (ticket.ticketId, ticket.numbers))
+                .collect())
+        }
+    }
+
+    /// Participations indexed by SubSquid
+    struct SubSquidIndexer<'a, P> {
+        url: &'a str,
+        parse_participant: fn(&str) -> Result<P>,
+    }
+
+    impl<P> ParticipationReader<P> for SubSquidIndexer<'_, P> {
+        fn get_winners(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            numbers: &[Number],
+            bonus_numbers: &[Number],
+        ) -> Result<Vec<P>> {
+            let body = subsquid_winners_query(lottery_id, raffle_id, numbers, bonus_numbers);
+
+            // query the indexer
+            let resp_body = query_indexer(self.url, body)?;
+
+            // parse the result
+            let result: SubSquidResponse = serde_json_core::from_slice(resp_body.as_slice())
+                .or(Err(ContractError::InvalidResponseBody))?
+                .0;
+
+            result
+                .data
+                .participations
+                .iter()
+                .map(|w| (self.parse_participant)(w.accountId))
+                .collect()
+        }
+
+        fn get_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(P, Vec<Number>, Vec<Number>)>> {
+            let body = subsquid_tickets_query(lottery_id, raffle_id);
+
+            // query the indexer
+            let resp_body = query_indexer(self.url, body)?;
+
+            // parse the result
+            let result: SubSquidTicketsResponse = serde_json_core::from_slice(resp_body.as_slice())
+                .or(Err(ContractError::InvalidResponseBody))?
+                .0;
+
+            result
+                .data
+                .participations
+                .into_iter()
+                .map(|t| {
+                    Ok((
+                        (self.parse_participant)(t.accountId)?,
+                        t.numbers,
+                        t.bonusNumbers,
+                    ))
+                })
+                .collect()
+        }
+
+        fn get_keno_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(TicketIndex, Vec<Number>)>> {
+            let body = subsquid_keno_query(lottery_id, raffle_id);
+
+            // query the indexer
+            let resp_body = query_indexer(self.url, body)?;
+
+            // parse the result
+            let result: SubSquidKenoResponse = serde_json_core::from_slice(resp_body.as_slice())
+                .or(Err(ContractError::InvalidResponseBody))?
+                .0;
+
+            Ok(result
+                .data
+                .kenoTickets
+                .into_iter()
+                .map(|ticket| (ticket.ticketId, ticket.numbers))
+                .collect())
+        }
+    }
+
+    /// Participations saved by the Ink! smart contract in the kv store
+    struct RollupStorage<'a> {
+        config: &'a Config,
+    }
+
+    impl ParticipationReader<AccountId> for RollupStorage<'_> {
+        fn get_winners(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            numbers: &[Number],
+            bonus_numbers: &[Number],
+        ) -> Result<Vec<AccountId>> {
+            // same condition as the filter of the indexers
+            let winners = self
+                .get_tickets(lottery_id, raffle_id)?
+                .into_iter()
+                .filter(|(_, picked_numbers, picked_bonus_numbers)| {
+                    numbers.iter().all(|n| picked_numbers.contains(n))
+                        && bonus_numbers
+                            .iter()
+                            .all(|n| picked_bonus_numbers.contains(n))
+                })
+                .map(|(participant, _, _)| participant)
+                .collect();
+
+            Ok(winners)
+        }
+
+        fn get_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(AccountId, Vec<Number>, Vec<Number>)>> {
+            let mut client = connect(self.config)?;
+
+            let key = (NB_PARTICIPATIONS, lottery_id, raffle_id).encode();
+            let nb_participations: u32 = client
+                .get(&key)
+                .log_err("get tickets: failed to read the number of participations")?
+                .unwrap_or_default();
+            // the reads are bounded, a raffle with more participations must use an indexer
+            if nb_participations > MAX_ONCHAIN_PARTICIPATIONS {
+                return Err(ContractError::TooManyParticipations);
+            }
+
+            let mut tickets = Vec::new();
+            for index in 0..nb_participations {
+                let key = (PARTICIPATION, lottery_id, raffle_id, index).encode();
+                let ticket: (AccountId, Vec<Number>, Vec<Number>) = client
+                    .get(&key)
+                    .log_err("get tickets: failed to read the participation")?
+                    .ok_or(ContractError::ParticipationNotFound)?;
+                tickets.push(ticket);
+            }
+
+            Ok(tickets)
+        }
+
+        fn get_keno_tickets(
+            &self,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Vec<(TicketIndex, Vec<Number>)>> {
+            let mut client = connect(self.config)?;
+
+            // the keno tickets are saved with consecutive indexes from 0
+            let mut tickets = Vec::new();
+            loop {
+                let ticket = tickets.len() as TicketIndex;
+                let key = (KENO_TICKET, lottery_id, raffle_id, ticket).encode();
+                let numbers: Option<Vec<Number>> = client
+                    .get(&key)
+                    .log_err("get keno tickets: failed to read the ticket")?;
+                match numbers {
+                    // the reads are bounded, a raffle with more tickets must use an indexer
+                    Some(_) if ticket >= MAX_ONCHAIN_PARTICIPATIONS => {
+                        return Err(ContractError::TooManyParticipations)
+                    }
+                    Some(numbers) => tickets.push((ticket, numbers)),
+                    None => break,
+                }
+            }
+
+            Ok(tickets)
+        }
     }
 
     #[derive(Encode, Decode, Debug, PartialEq, Eq)]
@@ -144,10 +697,24 @@ mod lotto_draw {
         MinGreaterThanMax,
         AddOverFlow,
         SubOverFlow,
+        NotEnoughTickets,
         // error when verify the numbers
         InvalidContractId,
         CurrentRaffleUnknown,
         UnauthorizedRaffle,
+        // error when reading the request
+        UnsupportedProtocolVersion,
+        // error when answering several requests
+        QueueChanged,
+        // error when targeting an EVM smart contract
+        InvalidEvmAddress,
+        InvalidMessageEncoding,
+        // error when reading the participations
+        UnsupportedWinnerSource,
+        ParticipationNotFound,
+        TooManyParticipations,
+        // error when the response reverts the transaction of the Ink! smart contract
+        ResponseReverted,
     }
 
     type Result<T> = core::result::Result<T, ContractError>;
@@ -159,6 +726,16 @@ mod lotto_draw {
         }
     }
 
+    impl From<ProtocolError> for ContractError {
+        fn from(error: ProtocolError) -> Self {
+            error!("error in the message: {:?}", error);
+            match error {
+                ProtocolError::UnsupportedVersion(_) => ContractError::UnsupportedProtocolVersion,
+                ProtocolError::InvalidEncoding => ContractError::InvalidMessageEncoding,
+            }
+        }
+    }
+
     impl Lotto {
         #[ink(constructor)]
         pub fn default() -> Self {
@@ -168,8 +745,8 @@ mod lotto_draw {
             Self {
                 owner: Self::env().caller(),
                 attest_key: private_key[..32].try_into().expect("Invalid Key Length"),
-                consumer_config: None,
-                indexer_url: None,
+                targets: Vec::new(),
+                evm_targets: Vec::new(),
             }
         }
 
@@ -195,11 +772,11 @@ mod lotto_draw {
             output.to_vec()
         }
 
-        /// Gets the sender address used by this rollup (in case of meta-transaction)
+        /// Gets the sender address used by this rollup for the target contract (in case of meta-transaction)
         #[ink(message)]
-        pub fn get_sender_address(&self) -> Option<Vec<u8>> {
+        pub fn get_sender_address(&self, contract_id: ContractId) -> Option<Vec<u8>> {
             if let Some(Some(sender_key)) =
-                self.consumer_config.as_ref().map(|c| c.sender_key.as_ref())
+                self.get_target(&contract_id).map(|c| c.sender_key.as_ref())
             {
                 let sender_key = signing::get_public_key(sender_key, signing::SigType::Sr25519);
                 Some(sender_key)
@@ -208,15 +785,24 @@ mod lotto_draw {
             }
         }
 
+        /// Gets the ids of the target consumer contracts
+        #[ink(message)]
+        pub fn get_target_contracts(&self) -> Vec<ContractId> {
+            self.targets.iter().map(|c| c.contract_id).collect()
+        }
+
         /// Gets the config of the target consumer contract
         #[ink(message)]
-        pub fn get_target_contract(&self) -> Option<(String, u8, u8, ContractId)> {
-            self.consumer_config
-                .as_ref()
+        pub fn get_target_contract(
+            &self,
+            contract_id: ContractId,
+        ) -> Option<(String, u8, u8, ContractId)> {
+            self.get_target(&contract_id)
                 .map(|c| (c.rpc.clone(), c.pallet_id, c.call_id, c.contract_id))
         }
 
-        /// Configures the target consumer contract (admin only)
+        /// Adds or updates a target consumer contract (admin only).
+        /// The winner source of an existing target is kept
         #[ink(message)]
         pub fn config_target_contract(
             &mut self,
@@ -227,107 +813,602 @@ mod lotto_draw {
             sender_key: Option<Vec<u8>>,
         ) -> Result<()> {
             self.ensure_owner()?;
-            self.consumer_config = Some(Config {
-                rpc,
-                pallet_id,
-                call_id,
-                contract_id: contract_id
-                    .try_into()
-                    .or(Err(ContractError::InvalidAddressLength))?,
-                sender_key: match sender_key {
-                    Some(key) => Some(key.try_into().or(Err(ContractError::InvalidKeyLength))?),
-                    None => None,
-                },
-            });
+            let contract_id: ContractId = contract_id
+                .try_into()
+                .or(Err(ContractError::InvalidAddressLength))?;
+            let sender_key = match sender_key {
+                Some(key) => Some(key.try_into().or(Err(ContractError::InvalidKeyLength))?),
+                None => None,
+            };
+
+            match self
+                .targets
+                .iter_mut()
+                .find(|c| c.contract_id == contract_id)
+            {
+                Some(config) => {
+                    config.rpc = rpc;
+                    config.pallet_id = pallet_id;
+                    config.call_id = call_id;
+                    config.sender_key = sender_key;
+                }
+                None => self.targets.push(Config {
+                    rpc,
+                    pallet_id,
+                    call_id,
+                    contract_id,
+                    sender_key,
+                    winner_source: None,
+                }),
+            }
             Ok(())
         }
 
-        /// Gets the config to target the indexer
+        /// Removes a target consumer contract (admin only)
         #[ink(message)]
-        pub fn get_indexer_url(&self) -> Option<String> {
-            self.indexer_url.clone()
+        pub fn remove_target_contract(&mut self, contract_id: ContractId) -> Result<()> {
+            self.ensure_owner()?;
+            self.ensure_client_configured(&contract_id)?;
+            self.targets.retain(|c| c.contract_id != contract_id);
+            Ok(())
         }
 
-        /// Configures the indexer (admin only)
+        /// Gets the config to target the indexer of the target contract
         #[ink(message)]
-        pub fn config_indexer(&mut self, indexer_url: String) -> Result<()> {
-            self.ensure_owner()?;
-            self.indexer_url = Some(indexer_url);
-            Ok(())
+        pub fn get_indexer_url(&self, contract_id: ContractId) -> Option<String> {
+            self.get_target(&contract_id)
+                .and_then(|c| c.winner_source.as_ref())
+                .and_then(|source| source.indexer_url().cloned())
         }
 
-        /// Transfers the ownership of the contract (admin only)
+        /// Configures the SubQuery indexer of the target contract (admin only)
         #[ink(message)]
-        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
-            self.ensure_owner()?;
+        pub fn config_indexer(
+            &mut self,
+            contract_id: ContractId,
+            indexer_url: String,
+        ) -> Result<()> {
+            self.config_winner_source(contract_id, WinnerSource::SubQuery(indexer_url))
+        }
+
+        /// Gets the source of the participations used to find the winners of the target contract
+        #[ink(message)]
+        pub fn get_winner_source(&self, contract_id: ContractId) -> Option<WinnerSource> {
+            self.get_target(&contract_id)
+                .and_then(|c| c.winner_source.clone())
+        }
+
+        /// Configures the source of the participations used to find the winners of the target contract (admin only)
+        #[ink(message)]
+        pub fn config_winner_source(
+            &mut self,
+            contract_id: ContractId,
+            winner_source: WinnerSource,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            let config = self
+                .targets
+                .iter_mut()
+                .find(|c| c.contract_id == contract_id)
+                .ok_or(ContractError::ClientNotConfigured)?;
+            config.winner_source = Some(winner_source);
+            Ok(())
+        }
+
+        /// Gets the addresses of the target EVM contracts
+        #[ink(message)]
+        pub fn get_evm_target_contracts(&self) -> Vec<EvmContractId> {
+            self.evm_targets.iter().map(|c| c.contract_id).collect()
+        }
+
+        /// Gets the config of the target EVM contract
+        #[ink(message)]
+        pub fn get_evm_target_contract(
+            &self,
+            contract_id: EvmContractId,
+        ) -> Option<(String, EvmContractId)> {
+            self.get_evm_target(&contract_id)
+                .map(|c| (c.rpc.clone(), c.contract_id))
+        }
+
+        /// Adds or updates a target EVM contract (admin only).
+        /// The winner source of an existing target is kept
+        #[ink(message)]
+        pub fn config_evm_target_contract(
+            &mut self,
+            rpc: String,
+            contract_id: Vec<u8>,
+            sender_key: Option<Vec<u8>>,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            let contract_id: EvmContractId = contract_id
+                .try_into()
+                .or(Err(ContractError::InvalidAddressLength))?;
+            let sender_key = match sender_key {
+                Some(key) => Some(key.try_into().or(Err(ContractError::InvalidKeyLength))?),
+                None => None,
+            };
+
+            match self
+                .evm_targets
+                .iter_mut()
+                .find(|c| c.contract_id == contract_id)
+            {
+                Some(config) => {
+                    config.rpc = rpc;
+                    config.sender_key = sender_key;
+                }
+                None => self.evm_targets.push(EvmConfig {
+                    rpc,
+                    contract_id,
+                    sender_key,
+                    winner_source: None,
+                }),
+            }
+            Ok(())
+        }
+
+        /// Removes a target EVM contract (admin only)
+        #[ink(message)]
+        pub fn remove_evm_target_contract(&mut self, contract_id: EvmContractId) -> Result<()> {
+            self.ensure_owner()?;
+            self.ensure_evm_client_configured(&contract_id)?;
+            self.evm_targets.retain(|c| c.contract_id != contract_id);
+            Ok(())
+        }
+
+        /// Gets the config to target the indexer of the target EVM contract
+        #[ink(message)]
+        pub fn get_evm_indexer_url(&self, contract_id: EvmContractId) -> Option<String> {
+            self.get_evm_target(&contract_id)
+                .and_then(|c| c.winner_source.as_ref())
+                .and_then(|source| source.indexer_url().cloned())
+        }
+
+        /// Configures the SubQuery indexer of the target EVM contract (admin only)
+        #[ink(message)]
+        pub fn config_evm_indexer(
+            &mut self,
+            contract_id: EvmContractId,
+            indexer_url: String,
+        ) -> Result<()> {
+            self.config_evm_winner_source(contract_id, WinnerSource::SubQuery(indexer_url))
+        }
+
+        /// Gets the source of the participations used to find the winners of the target EVM contract
+        #[ink(message)]
+        pub fn get_evm_winner_source(&self, contract_id: EvmContractId) -> Option<WinnerSource> {
+            self.get_evm_target(&contract_id)
+                .and_then(|c| c.winner_source.clone())
+        }
+
+        /// Configures the source of the participations used to find the winners of the target EVM contract (admin only).
+        /// The participations are not saved on chain by the EVM smart contract
+        #[ink(message)]
+        pub fn config_evm_winner_source(
+            &mut self,
+            contract_id: EvmContractId,
+            winner_source: WinnerSource,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            if winner_source == WinnerSource::OnChain {
+                return Err(ContractError::UnsupportedWinnerSource);
+            }
+            let config = self
+                .evm_targets
+                .iter_mut()
+                .find(|c| c.contract_id == contract_id)
+                .ok_or(ContractError::ClientNotConfigured)?;
+            config.winner_source = Some(winner_source);
+            Ok(())
+        }
+
+        /// Transfers the ownership of the contract (admin only)
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.ensure_owner()?;
             self.owner = new_owner;
             Ok(())
         }
 
-        /// Processes a request by a rollup transaction
+        /// Processes a request of the target contract by a rollup transaction
+        #[ink(message)]
+        pub fn answer_request(&self, contract_id: ContractId) -> Result<Option<Vec<u8>>> {
+            let config = self.ensure_client_configured(&contract_id)?;
+            self.inner_answer_and_submit(config)
+        }
+
+        /// Processes a request of the next target having one by a rollup transaction
+        /// and returns the id of this target with the id of the transaction.
+        /// The targets are tried in turn, starting from a different one at each block
+        #[ink(message)]
+        pub fn answer_next_request(&self) -> Result<(ContractId, Option<Vec<u8>>)> {
+            if self.targets.is_empty() {
+                return Err(ContractError::ClientNotConfigured);
+            }
+
+            // the state is not saved by a query, the first target depends on the block
+            let first = self.env().block_number() as usize % self.targets.len();
+            let mut error = ContractError::NoRequestInQueue;
+            for i in 0..self.targets.len() {
+                let config = &self.targets[(first + i) % self.targets.len()];
+                match self.inner_answer_and_submit(config) {
+                    Ok(tx_id) => return Ok((config.contract_id, tx_id)),
+                    Err(ContractError::NoRequestInQueue) => {}
+                    Err(e) => {
+                        // a target in error does not prevent the others from being answered
+                        error!(
+                            "failed to answer the request of the target {:02x?}: {e:?}",
+                            config.contract_id
+                        );
+                        if error == ContractError::NoRequestInQueue {
+                            error = e;
+                        }
+                    }
+                }
+            }
+            Err(error)
+        }
+
+        /// Pops the next request of the target, computes the response and submits it
+        fn inner_answer_and_submit(&self, config: &Config) -> Result<Option<Vec<u8>>> {
+            let mut client = connect(config)?;
+
+            let response = self.inner_answer_request(config, &mut client)?;
+            // Attach an action to the tx by:
+            client.action(Action::Reply(response.encode()));
+
+            maybe_submit_tx(client, &self.attest_key, config.sender_key.as_ref())
+        }
+
+        /// Computes the response to the next request (indexer call included) without submitting
+        /// the transaction, and returns it with the encoded reply action
+        #[ink(message)]
+        pub fn dry_run_answer(&self, contract_id: ContractId) -> Result<DryRunAnswer> {
+            let config = self.ensure_client_configured(&contract_id)?;
+            // the client is not committed, the request stays in the queue
+            let mut client = connect(config)?;
+
+            let response = self.inner_answer_request(config, &mut client)?;
+            // same action as the one attached to the transaction by inner_answer_and_submit
+            let action = Action::Reply(response.encode()).encode();
+            Ok(DryRunAnswer { response, action })
+        }
+
+        /// Returns the next requests of the queue (max `MAX_REQUESTS_BY_BATCH`) without removing them
         #[ink(message)]
-        pub fn answer_request(&self) -> Result<Option<Vec<u8>>> {
-            let config = self.ensure_client_configured()?;
+        pub fn peek_requests(
+            &self,
+            contract_id: ContractId,
+            limit: u32,
+        ) -> Result<Vec<LottoRequestMessage>> {
+            let config = self.ensure_client_configured(&contract_id)?;
+            // the client is not committed, the requests stay in the queue
             let mut client = connect(config)?;
 
+            let mut requests = Vec::new();
+            while requests.len() < limit.min(MAX_REQUESTS_BY_BATCH) as usize {
+                match client
+                    .pop()
+                    .log_err("peek_requests: failed to read queue")?
+                {
+                    Some(request) => requests.push(request),
+                    None => break,
+                }
+            }
+            Ok(requests)
+        }
+
+        /// Pops the next request and computes the response
+        fn inner_answer_request(
+            &self,
+            config: &Config,
+            client: &mut InkRollupClient,
+        ) -> Result<LottoResponseMessage> {
             // Get a request if presents
             let request: LottoRequestMessage = client
                 .pop()
                 .log_err("answer_request: failed to read queue")?
                 .ok_or(ContractError::NoRequestInQueue)?;
 
-            let response = self.handle_request(request)?;
-            // Attach an action to the tx by:
-            client.action(Action::Reply(response.encode()));
+            log_rejection(client, &request);
 
-            maybe_submit_tx(client, &self.attest_key, config.sender_key.as_ref())
+            self.handle_request(config, request)
         }
 
-        fn handle_request(&self, message: LottoRequestMessage) -> Result<LottoResponseMessage> {
+        /// Processes up to `max_requests` requests (max `MAX_REQUESTS_BY_BATCH`) by one rollup transaction.
+        /// The requests are answered in the order of the queue. A failed request does not stop the batch:
+        /// it is answered with an error response and the Ink! smart contract queues it again.
+        /// When the first request is still at the head of the queue, the last batch was reverted
+        /// (ie a response failed when the Ink! smart contract applied it): this request is answered alone,
+        /// then with an error response so it does not block the queue
+        #[ink(message)]
+        pub fn answer_requests(
+            &self,
+            contract_id: ContractId,
+            max_requests: u32,
+        ) -> Result<AnswerSummary> {
+            let config = self.ensure_client_configured(&contract_id)?;
+            let mut max_requests = max_requests.min(MAX_REQUESTS_BY_BATCH) as usize;
+
+            // answer the requests until the queue is empty, each popped request gets a reply
+            let mut client = connect(config)?;
+            let cache_key = (LAST_BATCH, contract_id).encode();
+            let mut head = None;
+            let mut processed = Vec::new();
+            let mut failed = Vec::new();
+            while processed.len() + failed.len() < max_requests {
+                let request: LottoRequestMessage = match client
+                    .pop()
+                    .log_err("answer_requests: failed to read queue")?
+                {
+                    Some(request) => request,
+                    None => break,
+                };
+
+                log_rejection(&mut client, &request);
+
+                let (lottery_id, raffle_id) = (request.lottery_id, request.raffle_id);
+                let result = if head.is_none() {
+                    // if the transaction is not included yet, the new one fails on the queue head
+                    let nb_attempts = get_nb_attempts(&cache_key, &request);
+                    head = Some((request.clone(), nb_attempts + 1));
+                    match nb_attempts {
+                        0 => self.handle_request(config, request.clone()),
+                        1 => {
+                            max_requests = 1;
+                            self.handle_request(config, request.clone())
+                        }
+                        _ => {
+                            max_requests = 1;
+                            Err(ContractError::ResponseReverted)
+                        }
+                    }
+                } else {
+                    self.handle_request(config, request.clone())
+                };
+
+                let response = match result {
+                    Ok(response) => {
+                        processed.push((lottery_id, raffle_id));
+                        response
+                    }
+                    Err(e) => {
+                        error!(
+                            "failed to answer the request for lottery {lottery_id} and raffle {raffle_id}: {e:?}"
+                        );
+                        let response =
+                            LottoResponseMessage::new(request, Response::Error(e.encode()));
+                        failed.push((lottery_id, raffle_id, e));
+                        response
+                    }
+                };
+                client.action(Action::Reply(response.encode()));
+            }
+
+            let tx_id = if processed.is_empty() && failed.is_empty() {
+                None
+            } else {
+                maybe_submit_tx(client, &self.attest_key, config.sender_key.as_ref())?
+            };
+
+            // the first request is saved with the number of attempts to answer it
+            match head {
+                Some((request, nb_attempts)) if nb_attempts <= 2 => {
+                    pink_extension::ext()
+                        .cache_set(&cache_key, &(request, nb_attempts).encode())
+                        .log_err("answer_requests: failed to save the batch in the cache")
+                        .ok();
+                }
+                _ => {
+                    pink_extension::ext().cache_remove(&cache_key);
+                }
+            }
+
+            Ok(AnswerSummary {
+                processed,
+                failed,
+                tx_id,
+            })
+        }
+
+        fn handle_request(
+            &self,
+            config: &Config,
+            message: LottoRequestMessage,
+        ) -> Result<LottoResponseMessage> {
+            // the request must be built with the same version of the protocol
+            message.ensure_version()?;
+
+            let response = self.inner_compute_response(config, &message)?.into();
+            Ok(LottoResponseMessage::new(message, response))
+        }
+
+        /// Computes the response to the request for the Ink! or the EVM target
+        fn inner_compute_response<C: TargetConfig>(
+            &self,
+            config: &C,
+            message: &LottoRequestMessage,
+        ) -> Result<TargetResponse<C::Participant>> {
             let response = match message.request {
-                Request::DrawNumbers(nb_numbers, smallest_number, biggest_number) => self
-                    .inner_get_numbers(
+                Request::DrawNumbers(nb_numbers, smallest_number, biggest_number, bonus_pool) => {
+                    self.inner_get_numbers(
+                        config,
+                        message.lottery_id,
+                        message.raffle_id,
+                        nb_numbers,
+                        smallest_number,
+                        biggest_number,
+                        bonus_pool,
+                    )
+                    .map(TargetResponse::Numbers)?
+                }
+                Request::CheckWinners(ref numbers, ref bonus_numbers) => self
+                    .inner_get_winners(
+                        config,
+                        message.lottery_id,
+                        message.raffle_id,
+                        numbers,
+                        bonus_numbers,
+                    )
+                    .map(TargetResponse::Winners)?,
+                Request::QuickPick(
+                    first_slot,
+                    nb_slots,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    bonus_pool,
+                ) => self
+                    .inner_get_quick_picks(
+                        config,
+                        message.lottery_id,
+                        message.raffle_id,
+                        first_slot,
+                        nb_slots,
+                        nb_numbers,
+                        smallest_number,
+                        biggest_number,
+                        bonus_pool,
+                    )
+                    .map(TargetResponse::QuickPickNumbers)?,
+                Request::DrawTickets(nb_tickets, total_tickets) => self
+                    .inner_get_tickets(
+                        config,
+                        message.lottery_id,
+                        message.raffle_id,
+                        nb_tickets,
+                        total_tickets,
+                    )
+                    .map(TargetResponse::Tickets)?,
+                Request::CheckKenoWinners(ref numbers) => self
+                    .inner_get_keno_matches(config, message.lottery_id, message.raffle_id, numbers)
+                    .map(TargetResponse::KenoMatches)?,
+                Request::DrawAndResolve(
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    bonus_pool,
+                ) => {
+                    let (numbers, winners) = self.inner_draw_and_resolve(
+                        config,
+                        message.lottery_id,
                         message.raffle_id,
                         nb_numbers,
                         smallest_number,
                         biggest_number,
+                        bonus_pool,
+                    )?;
+                    TargetResponse::NumbersAndWinners(numbers, winners)
+                }
+                Request::CheckTierWinners(ref numbers, ref bonus_numbers, ref tiers) => self
+                    .inner_get_tier_winners(
+                        config,
+                        message.lottery_id,
+                        message.raffle_id,
+                        numbers,
+                        bonus_numbers,
+                        tiers,
                     )
-                    .map(Response::Numbers)?,
-                Request::CheckWinners(ref numbers) => self
-                    .inner_get_winners(message.raffle_id, numbers)
-                    .map(Response::Winners)?,
+                    .map(TargetResponse::TierWinners)?,
             };
 
-            Ok(LottoResponseMessage {
-                request: message,
-                response,
-            })
+            Ok(response)
         }
 
-        /// Verify if the winning numbers for a raffle are valid (only for past raffles)
+        /// Processes a request of the target EVM contract by a rollup transaction.
+        /// A failed request is answered with an error response
         #[ink(message)]
+        pub fn answer_evm_request(&self, contract_id: EvmContractId) -> Result<Option<Vec<u8>>> {
+            let config = self.ensure_evm_client_configured(&contract_id)?;
+            let mut client = connect_evm(config)?;
+
+            // Get a request if presents (encoded in ABI by the EVM smart contract)
+            let raw_request = client
+                .session()
+                .pop()
+                .log_err("answer_evm_request: failed to read queue")?
+                .ok_or(ContractError::NoRequestInQueue)?;
+            let request = LottoRequestMessage::decode_abi(&raw_request)?;
+
+            // a failed request is answered with an error response so it does not block the queue
+            let response = match self.handle_evm_request(config, request.clone()) {
+                Ok(response) => response,
+                Err(e) => {
+                    error!(
+                        "failed to answer the request for lottery {} and raffle {}: {e:?}",
+                        request.lottery_id, request.raffle_id
+                    );
+                    EvmResponseMessage::new(request, EvmResponse::Error(e.encode()))
+                }
+            };
+            // Attach an action to the tx by:
+            client.action(EvmAction::Reply(response.encode_abi()));
+
+            maybe_submit_evm_tx(client, &self.attest_key, config.sender_key.as_ref())
+        }
+
+        /// Same as `handle_request` with the EVM addresses of the winners.
+        /// The version of the request is checked when it is decoded
+        fn handle_evm_request(
+            &self,
+            config: &EvmConfig,
+            message: LottoRequestMessage,
+        ) -> Result<EvmResponseMessage> {
+            let response = self.inner_compute_response(config, &message)?.into();
+            Ok(EvmResponseMessage::new(message, response))
+        }
+
+        /// Return why the last response for the raffle was rejected by the Ink! smart contract
+        /// (the error of the smart contract encoded in SCALE)
+        #[ink(message)]
+        pub fn get_message_rejection(
+            &self,
+            contract_id: ContractId,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+        ) -> Result<Option<Vec<u8>>> {
+            let config = self.ensure_client_configured(&contract_id)?;
+            let mut client = connect(config)?;
+
+            let key = (MESSAGE_REJECTED, lottery_id, raffle_id).encode();
+            let reason = client
+                .get(&key)
+                .log_err("get message rejection: failed to read the kv store")?;
+            Ok(reason)
+        }
+
+        /// Verify if the winning numbers for a raffle of the lottery are valid (only for past raffles)
+        #[ink(message)]
+        #[allow(clippy::too_many_arguments)]
         pub fn verify_numbers(
             &self,
             contract_id: ContractId,
+            lottery_id: LotteryId,
             raffle_id: RaffleId,
             nb_numbers: u8,
             smallest_number: Number,
             biggest_number: Number,
+            bonus_pool: Option<BonusPool>,
             numbers: Vec<Number>,
         ) -> Result<bool> {
-            let config = self.ensure_client_configured()?;
-
-            // check if the target contract is correct
-            if contract_id != config.contract_id {
-                return Err(ContractError::InvalidContractId);
-            }
+            // check if the target contract is registered
+            let config = self
+                .get_target(&contract_id)
+                .ok_or(ContractError::InvalidContractId)?;
 
             let mut client = connect(config)?;
 
             const LAST_RAFFLE_FOR_VERIF: u32 = ink::selector_id!("LAST_RAFFLE_FOR_VERIF");
+            // the key of the default lottery is the one used before the support of several lotteries
+            let key = if lottery_id == DEFAULT_LOTTERY_ID {
+                LAST_RAFFLE_FOR_VERIF.encode()
+            } else {
+                (LAST_RAFFLE_FOR_VERIF, lottery_id).encode()
+            };
 
             let last_raffle: RaffleId = client
-                .get(&LAST_RAFFLE_FOR_VERIF)
+                .get(&key)
                 .log_err("verify numbers: last raffle unknown")?
                 .ok_or(ContractError::CurrentRaffleUnknown)?;
 
@@ -337,30 +1418,55 @@ mod lotto_draw {
             }
 
             self.inner_verify_numbers(
+                config,
+                lottery_id,
                 raffle_id,
                 nb_numbers,
                 smallest_number,
                 biggest_number,
+                bonus_pool,
                 numbers,
             )
         }
 
-        pub fn inner_verify_numbers(
+        #[allow(clippy::too_many_arguments)]
+        fn inner_verify_numbers(
             &self,
+            config: &Config,
+            lottery_id: LotteryId,
             raffle_id: RaffleId,
             nb_numbers: u8,
             smallest_number: Number,
             biggest_number: Number,
+            bonus_pool: Option<BonusPool>,
             numbers: Vec<Number>,
         ) -> Result<bool> {
-            let winning_numbers =
-                self.inner_get_numbers(raffle_id, nb_numbers, smallest_number, biggest_number)?;
+            let winning_numbers = self.inner_get_numbers(
+                config,
+                lottery_id,
+                raffle_id,
+                nb_numbers,
+                smallest_number,
+                biggest_number,
+                bonus_pool,
+            )?;
             if winning_numbers.len() != numbers.len() {
                 return Ok(false);
             }
 
-            for n in &numbers {
-                if !winning_numbers.contains(n) {
+            // the main numbers and the bonus numbers are checked separately
+            let (winning_main_numbers, winning_bonus_numbers) =
+                winning_numbers.split_at(nb_numbers as usize);
+            let (main_numbers, bonus_numbers) = numbers.split_at(nb_numbers as usize);
+
+            for n in main_numbers {
+                if !winning_main_numbers.contains(n) {
+                    return Ok(false);
+                }
+            }
+
+            for n in bonus_numbers {
+                if !winning_bonus_numbers.contains(n) {
                     return Ok(false);
                 }
             }
@@ -368,19 +1474,174 @@ mod lotto_draw {
             Ok(true)
         }
 
+        #[allow(clippy::too_many_arguments)]
         fn inner_get_numbers(
             &self,
+            config: &impl TargetConfig,
+            lottery_id: LotteryId,
             raffle_id: RaffleId,
             nb_numbers: u8,
             smallest_number: Number,
             biggest_number: Number,
+            bonus_pool: Option<BonusPool>,
         ) -> Result<Vec<Number>> {
             info!(
-                "Request received for raffle {raffle_id} - draw {nb_numbers} numbers between {smallest_number} and {biggest_number}"
+                "Request received for lottery {lottery_id} and raffle {raffle_id} - draw {nb_numbers} numbers between {smallest_number} and {biggest_number} (bonus pool: {bonus_pool:?})"
+            );
+
+            let salt_prefix = lottery_salt_prefix(lottery_id);
+            let mut numbers = self.inner_draw_numbers(
+                config,
+                &salt_prefix,
+                raffle_id,
+                nb_numbers,
+                smallest_number,
+                biggest_number,
+            )?;
+            numbers.extend(self.inner_draw_bonus_numbers(
+                config,
+                &salt_prefix,
+                raffle_id,
+                bonus_pool,
+            )?);
+
+            info!("Numbers: {numbers:?}");
+
+            Ok(numbers)
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn inner_get_quick_picks(
+            &self,
+            config: &impl TargetConfig,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            first_slot: u32,
+            nb_slots: u32,
+            nb_numbers: u8,
+            smallest_number: Number,
+            biggest_number: Number,
+            bonus_pool: Option<BonusPool>,
+        ) -> Result<Vec<Vec<Number>>> {
+            info!(
+                "Request received for lottery {lottery_id} and raffle {raffle_id} - quick pick {nb_slots} tickets from slot {first_slot}"
+            );
+
+            let last_slot = first_slot
+                .checked_add(nb_slots)
+                .ok_or(ContractError::AddOverFlow)?;
+
+            let mut tickets = Vec::new();
+            for slot in first_slot..last_slot {
+                // the salt is different for each slot and from the one used for the winning numbers
+                let mut salt_prefix = lottery_salt_prefix(lottery_id);
+                salt_prefix.extend_from_slice(b"quick_pick");
+                salt_prefix.extend_from_slice(&slot.to_be_bytes());
+
+                let mut numbers = self.inner_draw_numbers(
+                    config,
+                    &salt_prefix,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                )?;
+                numbers.extend(self.inner_draw_bonus_numbers(
+                    config,
+                    &salt_prefix,
+                    raffle_id,
+                    bonus_pool,
+                )?);
+                tickets.push(numbers);
+            }
+
+            info!("Quick picks: {tickets:?}");
+
+            Ok(tickets)
+        }
+
+        fn inner_get_tickets(
+            &self,
+            config: &impl TargetConfig,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            nb_tickets: u32,
+            total_tickets: TicketIndex,
+        ) -> Result<Vec<TicketIndex>> {
+            info!(
+                "Request received for lottery {lottery_id} and raffle {raffle_id} - draw {nb_tickets} tickets among {total_tickets}"
             );
 
-            let contract_id = self.ensure_client_configured()?.contract_id;
+            // the tickets are distinct
+            if nb_tickets > total_tickets {
+                return Err(ContractError::NotEnoughTickets);
+            }
+
+            // the salt is different from the one used for the winning numbers
+            let mut salt_prefix = lottery_salt_prefix(lottery_id);
+            salt_prefix.extend_from_slice(b"tickets");
+
+            let mut tickets = Vec::new();
+            let mut i: u32 = 0;
+
+            while tickets.len() < nb_tickets as usize {
+                // build a salt for this ticket
+                let mut salt: Vec<u8> = Vec::new();
+                salt.extend_from_slice(&salt_prefix);
+                salt.extend_from_slice(&i.to_be_bytes());
+                salt.extend_from_slice(&raffle_id.to_be_bytes());
+                salt.extend_from_slice(config.anchor_address());
+
+                // draw the ticket
+                let ticket = self.inner_get_random(salt, 0, total_tickets as u64 - 1)?;
+                let ticket = ticket as TicketIndex;
+                // check if the ticket has already been drawn
+                if !tickets.contains(&ticket) {
+                    tickets.push(ticket);
+                }
+                i = i.checked_add(1).ok_or(ContractError::AddOverFlow)?;
+            }
+
+            info!("Tickets: {tickets:?}");
+
+            Ok(tickets)
+        }
 
+        /// draw the bonus numbers with a salt different from the one used for the main numbers
+        fn inner_draw_bonus_numbers(
+            &self,
+            config: &impl TargetConfig,
+            salt_prefix: &[u8],
+            raffle_id: RaffleId,
+            bonus_pool: Option<BonusPool>,
+        ) -> Result<Vec<Number>> {
+            let bonus_pool = match bonus_pool {
+                Some(bonus_pool) => bonus_pool,
+                None => return Ok(Vec::new()),
+            };
+
+            let mut bonus_salt_prefix = salt_prefix.to_vec();
+            bonus_salt_prefix.extend_from_slice(b"bonus");
+
+            self.inner_draw_numbers(
+                config,
+                &bonus_salt_prefix,
+                raffle_id,
+                bonus_pool.nb_numbers,
+                bonus_pool.min_number,
+                bonus_pool.max_number,
+            )
+        }
+
+        fn inner_draw_numbers(
+            &self,
+            config: &impl TargetConfig,
+            salt_prefix: &[u8],
+            raffle_id: RaffleId,
+            nb_numbers: u8,
+            smallest_number: Number,
+            biggest_number: Number,
+        ) -> Result<Vec<Number>> {
             if smallest_number > biggest_number {
                 return Err(ContractError::MinGreaterThanMax);
             }
@@ -391,9 +1652,10 @@ mod lotto_draw {
             while numbers.len() < nb_numbers as usize {
                 // build a salt for this lotto_draw number
                 let mut salt: Vec<u8> = Vec::new();
+                salt.extend_from_slice(salt_prefix);
                 salt.extend_from_slice(&i.to_be_bytes());
                 salt.extend_from_slice(&raffle_id.to_be_bytes());
-                salt.extend_from_slice(&contract_id);
+                salt.extend_from_slice(config.anchor_address());
 
                 // lotto_draw the number
                 let number = self.inner_get_number(salt, smallest_number, biggest_number)?;
@@ -405,12 +1667,15 @@ mod lotto_draw {
                 i += 1;
             }
 
-            info!("Numbers: {numbers:?}");
-
             Ok(numbers)
         }
 
         fn inner_get_number(&self, salt: Vec<u8>, min: Number, max: Number) -> Result<Number> {
+            let number = self.inner_get_random(salt, min as u64, max as u64)?;
+            Ok(number as Number)
+        }
+
+        fn inner_get_random(&self, salt: Vec<u8>, min: u64, max: u64) -> Result<u64> {
             let output = vrf(&salt);
             // keep only 8 bytes to compute the random u64
             let mut arr = [0x00; 8];
@@ -429,101 +1694,348 @@ mod lotto_draw {
                 .checked_add(min as u128)
                 .ok_or(ContractError::AddOverFlow)?;
 
-            Ok(r as Number)
+            Ok(r as u64)
+        }
+
+        /// draw the numbers and check the winners in one round trip
+        #[allow(clippy::too_many_arguments)]
+        fn inner_draw_and_resolve<C: TargetConfig>(
+            &self,
+            config: &C,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            nb_numbers: u8,
+            smallest_number: Number,
+            biggest_number: Number,
+            bonus_pool: Option<BonusPool>,
+        ) -> Result<(Vec<Number>, Vec<C::Participant>)> {
+            // same numbers as for a `DrawNumbers` request, so they can be verified the same way
+            let numbers = self.inner_get_numbers(
+                config,
+                lottery_id,
+                raffle_id,
+                nb_numbers,
+                smallest_number,
+                biggest_number,
+                bonus_pool,
+            )?;
+
+            // the bonus numbers follow the main numbers
+            let (main_numbers, bonus_numbers) =
+                numbers.split_at(numbers.len().min(nb_numbers as usize));
+            let winners = self.inner_get_winners(
+                config,
+                lottery_id,
+                raffle_id,
+                &main_numbers.to_vec(),
+                &bonus_numbers.to_vec(),
+            )?;
+
+            Ok((numbers, winners))
         }
 
-        fn inner_get_winners(
+        fn inner_get_winners<C: TargetConfig>(
             &self,
+            config: &C,
+            lottery_id: LotteryId,
             raffle_id: RaffleId,
             numbers: &Vec<Number>,
-        ) -> Result<Vec<AccountId>> {
+            bonus_numbers: &Vec<Number>,
+        ) -> Result<Vec<C::Participant>> {
             info!(
-                "Request received to get the winners for raffle id {raffle_id} and numbers {numbers:?} "
+                "Request received to get the winners for lottery {lottery_id}, raffle id {raffle_id}, numbers {numbers:?} and bonus numbers {bonus_numbers:?} "
             );
 
             if numbers.is_empty() {
                 return Err(ContractError::NoNumber);
             }
 
-            // check if the endpoint is configured
-            let indexer_endpoint = self.ensure_indexer_configured()?;
+            // read the participations from the winner source of the target
+            let winners = config.participation_reader()?.get_winners(
+                lottery_id,
+                raffle_id,
+                numbers,
+                bonus_numbers,
+            )?;
 
-            // build the headers
-            let headers = alloc::vec![
-                ("Content-Type".into(), "application/json".into()),
-                ("Accept".into(), "application/json".into())
-            ];
-            // build the filter
-            let mut filter = format!(
-                r#"filter:{{and:[{{numRaffle:{{equalTo:\"{}\"}}}}"#,
-                raffle_id
+            info!("Winners: {winners:02x?}");
+
+            Ok(winners)
+        }
+
+        fn inner_get_tier_winners<C: TargetConfig>(
+            &self,
+            config: &C,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            numbers: &[Number],
+            bonus_numbers: &[Number],
+            tiers: &[(u8, u8)],
+        ) -> Result<Vec<Vec<C::Participant>>> {
+            info!(
+                "Request received to get the winners of the prize tiers {tiers:?} for lottery {lottery_id}, raffle id {raffle_id}, numbers {numbers:?} and bonus numbers {bonus_numbers:?} "
             );
-            for n in numbers {
-                let f = format!(r#",{{numbers:{{contains:\"{}\"}}}}"#, n);
-                filter.push_str(&f);
+
+            if numbers.is_empty() {
+                return Err(ContractError::NoNumber);
+            }
+
+            // the matches of all tickets of the raffle are counted here
+            let tickets = config
+                .participation_reader()?
+                .get_tickets(lottery_id, raffle_id)?;
+            let winners = tier_winners(numbers, bonus_numbers, tiers, tickets);
+
+            info!("Winners by tier: {winners:02x?}");
+
+            Ok(winners)
+        }
+
+        fn inner_get_keno_matches(
+            &self,
+            config: &impl TargetConfig,
+            lottery_id: LotteryId,
+            raffle_id: RaffleId,
+            numbers: &[Number],
+        ) -> Result<Vec<(TicketIndex, u8)>> {
+            info!(
+                "Request received to count the keno matches for lottery {lottery_id}, raffle id {raffle_id} and numbers {numbers:?} "
+            );
+
+            if numbers.is_empty() {
+                return Err(ContractError::NoNumber);
+            }
+
+            // all tickets of the raffle are settled, the matches are counted here
+            let tickets = config
+                .participation_reader()?
+                .get_keno_tickets(lottery_id, raffle_id)?;
+
+            let mut matches: Vec<(TicketIndex, u8)> = tickets
+                .iter()
+                .map(|(ticket, picked_numbers)| (*ticket, count_matches(numbers, picked_numbers)))
+                .collect();
+            // the contract expects the tickets sorted by index
+            matches.sort_unstable_by_key(|(ticket, _)| *ticket);
+            matches.dedup_by_key(|(ticket, _)| *ticket);
+
+            info!("Keno matches: {matches:?}");
+
+            Ok(matches)
+        }
+
+        /// Returns BadOrigin error if the caller is not the owner
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() == self.owner {
+                Ok(())
+            } else {
+                Err(ContractError::BadOrigin)
             }
-            filter.push_str("]}");
+        }
+
+        /// Returns the config reference of the target EVM contract if it is registered
+        fn get_evm_target(&self, contract_id: &EvmContractId) -> Option<&EvmConfig> {
+            self.evm_targets
+                .iter()
+                .find(|c| &c.contract_id == contract_id)
+        }
+
+        /// Returns the config reference of the target EVM contract or raise the error `ClientNotConfigured`
+        fn ensure_evm_client_configured(&self, contract_id: &EvmContractId) -> Result<&EvmConfig> {
+            self.get_evm_target(contract_id)
+                .ok_or(ContractError::ClientNotConfigured)
+        }
+
+        /// Returns the config reference of the target contract if it is registered
+        fn get_target(&self, contract_id: &ContractId) -> Option<&Config> {
+            self.targets.iter().find(|c| &c.contract_id == contract_id)
+        }
+
+        /// Returns the config reference of the target contract or raise the error `ClientNotConfigured`
+        fn ensure_client_configured(&self, contract_id: &ContractId) -> Result<&Config> {
+            self.get_target(contract_id)
+                .ok_or(ContractError::ClientNotConfigured)
+        }
+    }
+
+    /// salt prefix used to draw different numbers for each lottery.
+    /// The default lottery has no prefix to keep the numbers drawn before the support of several lotteries
+    fn lottery_salt_prefix(lottery_id: LotteryId) -> Vec<u8> {
+        let mut salt_prefix: Vec<u8> = Vec::new();
+        if lottery_id != DEFAULT_LOTTERY_ID {
+            salt_prefix.extend_from_slice(b"lottery");
+            salt_prefix.extend_from_slice(&lottery_id.to_be_bytes());
+        }
+        salt_prefix
+    }
+
+    /// filter of the indexer on the raffle of the lottery (the list of conditions is not closed)
+    fn raffle_filter(lottery_id: LotteryId, raffle_id: RaffleId) -> String {
+        let mut filter = format!(
+            r#"filter:{{and:[{{numRaffle:{{equalTo:\"{}\"}}}}"#,
+            raffle_id
+        );
+        // no filter for the default lottery, the indexers without the lottery id keep working
+        if lottery_id != DEFAULT_LOTTERY_ID {
+            let f = format!(r#",{{numLottery:{{equalTo:\"{}\"}}}}"#, lottery_id);
+            filter.push_str(&f);
+        }
+        filter
+    }
+
+    /// query of the winners for the SubQuery schema
+    fn subquery_winners_query(
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        numbers: &[Number],
+        bonus_numbers: &[Number],
+    ) -> String {
+        // build the filter
+        let mut filter = raffle_filter(lottery_id, raffle_id);
+        for n in numbers {
+            let f = format!(r#",{{numbers:{{contains:\"{}\"}}}}"#, n);
+            filter.push_str(&f);
+        }
+        for n in bonus_numbers {
+            let f = format!(r#",{{bonusNumbers:{{contains:\"{}\"}}}}"#, n);
+            filter.push_str(&f);
+        }
+        filter.push_str("]}");
+
+        // build the body
+        format!(
+            r#"{{"query" : "{{participations({}){{ nodes {{ accountId }} }} }}"}}"#,
+            filter
+        )
+    }
+
+    /// query of the tickets with their numbers for the SubQuery schema
+    fn subquery_tickets_query(lottery_id: LotteryId, raffle_id: RaffleId) -> String {
+        let mut filter = raffle_filter(lottery_id, raffle_id);
+        filter.push_str("]}");
+
+        format!(
+            r#"{{"query" : "{{participations({}){{ nodes {{ accountId numbers bonusNumbers }} }} }}"}}"#,
+            filter
+        )
+    }
+
+    /// query of the keno tickets for the SubQuery schema
+    fn subquery_keno_query(lottery_id: LotteryId, raffle_id: RaffleId) -> String {
+        let mut filter = raffle_filter(lottery_id, raffle_id);
+        filter.push_str("]}");
+
+        format!(
+            r#"{{"query" : "{{kenoTickets({}){{ nodes {{ ticketId numbers }} }} }}"}}"#,
+            filter
+        )
+    }
+
+    /// filter of SubSquid on the raffle of the lottery (the list of conditions is not closed)
+    fn subsquid_filter(lottery_id: LotteryId, raffle_id: RaffleId) -> String {
+        let mut filter = format!("where:{{numRaffle_eq:{}", raffle_id);
+        // no filter for the default lottery, the indexers without the lottery id keep working
+        if lottery_id != DEFAULT_LOTTERY_ID {
+            let f = format!(",numLottery_eq:{}", lottery_id);
+            filter.push_str(&f);
+        }
+        filter
+    }
+
+    /// query of the winners for the SubSquid schema
+    fn subsquid_winners_query(
+        lottery_id: LotteryId,
+        raffle_id: RaffleId,
+        numbers: &[Number],
+        bonus_numbers: &[Number],
+    ) -> String {
+        // build the filter
+        let mut filter = subsquid_filter(lottery_id, raffle_id);
+        filter.push_str(&format!(",numbers_containsAll:{:?}", numbers));
+        if !bonus_numbers.is_empty() {
+            filter.push_str(&format!(",bonusNumbers_containsAll:{:?}", bonus_numbers));
+        }
+        filter.push('}');
 
-            // build the body
-            let body = format!(
-                r#"{{"query" : "{{participations({}){{ nodes {{ accountId }} }} }}"}}"#,
-                filter
-            );
+        // build the body
+        format!(
+            r#"{{"query" : "{{participations({}){{ accountId }} }}"}}"#,
+            filter
+        )
+    }
 
-            debug!("body: {body}");
+    /// query of the tickets with their numbers for the SubSquid schema
+    fn subsquid_tickets_query(lottery_id: LotteryId, raffle_id: RaffleId) -> String {
+        let mut filter = subsquid_filter(lottery_id, raffle_id);
+        filter.push('}');
 
-            // query the indexer
-            let resp = http_post!(indexer_endpoint, body, headers);
+        format!(
+            r#"{{"query" : "{{participations({}){{ accountId numbers bonusNumbers }} }}"}}"#,
+            filter
+        )
+    }
 
-            // check the result
-            if resp.status_code != 200 {
-                ink::env::debug_println!("status code {}", resp.status_code);
-                return Err(ContractError::HttpRequestFailed);
-            }
+    /// query of the keno tickets for the SubSquid schema
+    fn subsquid_keno_query(lottery_id: LotteryId, raffle_id: RaffleId) -> String {
+        let mut filter = subsquid_filter(lottery_id, raffle_id);
+        filter.push('}');
 
-            // parse the result
-            let result: IndexerResponse = serde_json_core::from_slice(resp.body.as_slice())
-                .or(Err(ContractError::InvalidResponseBody))?
-                .0;
+        format!(
+            r#"{{"query" : "{{kenoTickets({}){{ ticketId numbers }} }}"}}"#,
+            filter
+        )
+    }
 
-            // add the winners
-            let mut winners = Vec::new();
-            for w in result.data.participations.nodes.iter() {
-                // build the accountId from the string address
-                let account_id = AccountId32::from_ss58check(w.accountId)
-                    .or(Err(ContractError::InvalidSs58Address))?;
-                let address_hex: [u8; 32] = scale::Encode::encode(&account_id)
-                    .try_into()
-                    .or(Err(ContractError::InvalidKeyLength))?;
-                winners.push(AccountId::from(address_hex));
-            }
+    /// send the query to the indexer and return the body of the response
+    fn query_indexer(indexer_endpoint: &str, body: String) -> Result<Vec<u8>> {
+        // build the headers
+        let headers = alloc::vec![
+            ("Content-Type".into(), "application/json".into()),
+            ("Accept".into(), "application/json".into())
+        ];
 
-            info!("Winners: {winners:02x?}");
+        debug!("body: {body}");
 
-            Ok(winners)
-        }
+        // query the indexer
+        let resp = http_post!(indexer_endpoint, body, headers);
 
-        /// Returns BadOrigin error if the caller is not the owner
-        fn ensure_owner(&self) -> Result<()> {
-            if self.env().caller() == self.owner {
-                Ok(())
-            } else {
-                Err(ContractError::BadOrigin)
-            }
+        // check the result
+        if resp.status_code != 200 {
+            ink::env::debug_println!("status code {}", resp.status_code);
+            return Err(ContractError::HttpRequestFailed);
         }
 
-        /// Returns the config reference or raise the error `ClientNotConfigured`
-        fn ensure_client_configured(&self) -> Result<&Config> {
-            self.consumer_config
-                .as_ref()
-                .ok_or(ContractError::ClientNotConfigured)
-        }
+        Ok(resp.body)
+    }
+
+    /// number of picked numbers in the drawn numbers
+    fn count_matches(drawn_numbers: &[Number], picked_numbers: &[Number]) -> u8 {
+        picked_numbers
+            .iter()
+            .filter(|n| drawn_numbers.contains(n))
+            .count() as u8
+    }
 
-        fn ensure_indexer_configured(&self) -> Result<&String> {
-            self.indexer_url
-                .as_ref()
-                .ok_or(ContractError::IndexerNotConfigured)
+    /// split the participants of the tickets between the prize tiers (number of main matches,
+    /// number of bonus matches), in the order of the tiers. A ticket wins in the tier matching
+    /// exactly its numbers of matches
+    fn tier_winners<P>(
+        numbers: &[Number],
+        bonus_numbers: &[Number],
+        tiers: &[(u8, u8)],
+        tickets: Vec<(P, Vec<Number>, Vec<Number>)>,
+    ) -> Vec<Vec<P>> {
+        let mut winners: Vec<Vec<P>> = tiers.iter().map(|_| Vec::new()).collect();
+        for (participant, picked_numbers, picked_bonus_numbers) in tickets {
+            let matches = (
+                count_matches(numbers, &picked_numbers),
+                count_matches(bonus_numbers, &picked_bonus_numbers),
+            );
+            if let Some(tier) = tiers.iter().position(|tier| *tier == matches) {
+                winners[tier].push(participant);
+            }
         }
+        winners
     }
 
     fn connect(config: &Config) -> Result<InkRollupClient> {
@@ -544,6 +2056,28 @@ mod lotto_draw {
         }
     }
 
+    /// return how many times the request was answered as first request of a batch and is still
+    /// at the head of the queue (the transaction was reverted or is not included yet)
+    fn get_nb_attempts(cache_key: &[u8], request: &LottoRequestMessage) -> u8 {
+        pink_extension::ext()
+            .cache_get(cache_key)
+            .and_then(|value| <(LottoRequestMessage, u8)>::decode(&mut &value[..]).ok())
+            .filter(|(head, _)| head == request)
+            .map(|(_, nb_attempts)| nb_attempts)
+            .unwrap_or_default()
+    }
+
+    /// log why the last response for the raffle of the request was rejected by the Ink! smart contract
+    fn log_rejection(client: &mut InkRollupClient, request: &LottoRequestMessage) {
+        let key = (MESSAGE_REJECTED, request.lottery_id, request.raffle_id).encode();
+        if let Ok(Some(reason)) = client.get::<Vec<u8>>(&key) {
+            info!(
+                "last response for lottery {} and raffle {} was rejected: {:?}",
+                request.lottery_id, request.raffle_id, reason
+            );
+        }
+    }
+
     fn maybe_submit_tx(
         client: InkRollupClient,
         attest_key: &[u8; 32],
@@ -571,6 +2105,46 @@ mod lotto_draw {
         Ok(None)
     }
 
+    fn connect_evm(config: &EvmConfig) -> Result<EvmRollupClient> {
+        let result = EvmRollupClient::new(&config.rpc, config.contract_id.into())
+            .log_err("failed to create evm rollup client");
+
+        match result {
+            Ok(client) => Ok(client),
+            Err(e) => {
+                error!("Error : {:?}", e);
+                Err(ContractError::FailedToCreateClient)
+            }
+        }
+    }
+
+    fn maybe_submit_evm_tx(
+        client: EvmRollupClient,
+        attest_key: &[u8; 32],
+        sender_key: Option<&[u8; 32]>,
+    ) -> Result<Option<Vec<u8>>> {
+        let maybe_submittable = client
+            .commit()
+            .log_err("failed to commit")
+            .map_err(|_| ContractError::FailedToCommitTx)?;
+
+        if let Some(submittable) = maybe_submittable {
+            let tx_id = if let Some(sender_key) = sender_key {
+                // Prefer to meta-tx
+                submittable
+                    .submit_meta_tx(attest_key, sender_key)
+                    .log_err("failed to submit rollup meta-tx")?
+            } else {
+                // Fallback to account-based authentication
+                submittable
+                    .submit(attest_key)
+                    .log_err("failed to submit rollup tx")?
+            };
+            return Ok(Some(tx_id));
+        }
+        Ok(None)
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -632,7 +2206,10 @@ mod lotto_draw {
                 .unwrap();
 
             lotto
-                .config_indexer("https://query.substrate.fi/lotto-subquery-shibuya".to_string())
+                .config_indexer(
+                    contract_id,
+                    "https://query.substrate.fi/lotto-subquery-shibuya".to_string(),
+                )
                 .unwrap();
             //lotto.set_attest_key(Some(attest_key)).unwrap();
 
@@ -645,6 +2222,7 @@ mod lotto_draw {
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let raffle_id = 1;
             let nb_numbers = 5;
@@ -652,7 +2230,15 @@ mod lotto_draw {
             let biggest_number = 50;
 
             let result = lotto
-                .inner_get_numbers(raffle_id, nb_numbers, smallest_number, biggest_number)
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
                 .unwrap();
             assert_eq!(nb_numbers as usize, result.len());
             for &n in result.iter() {
@@ -669,6 +2255,7 @@ mod lotto_draw {
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let raffle_id = 1;
             let nb_numbers = 5;
@@ -676,7 +2263,15 @@ mod lotto_draw {
             let biggest_number = 5;
 
             let result = lotto
-                .inner_get_numbers(raffle_id, nb_numbers, smallest_number, biggest_number)
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
                 .unwrap();
             assert_eq!(nb_numbers as usize, result.len());
             for &n in result.iter() {
@@ -693,6 +2288,7 @@ mod lotto_draw {
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let nb_numbers = 5;
             let smallest_number = 1;
@@ -702,14 +2298,30 @@ mod lotto_draw {
 
             for i in 0..100 {
                 let result = lotto
-                    .inner_get_numbers(i, nb_numbers, smallest_number, biggest_number)
+                    .inner_get_numbers(
+                        target,
+                        DEFAULT_LOTTERY_ID,
+                        i,
+                        nb_numbers,
+                        smallest_number,
+                        biggest_number,
+                        None,
+                    )
                     .unwrap();
                 // this result must be different from the previous ones
                 results.iter().for_each(|r| assert_ne!(result, *r));
 
                 // same request message means same result
                 let result_2 = lotto
-                    .inner_get_numbers(i, nb_numbers, smallest_number, biggest_number)
+                    .inner_get_numbers(
+                        target,
+                        DEFAULT_LOTTERY_ID,
+                        i,
+                        nb_numbers,
+                        smallest_number,
+                        biggest_number,
+                        None,
+                    )
                     .unwrap();
                 assert_eq!(result, result_2);
 
@@ -717,12 +2329,299 @@ mod lotto_draw {
             }
         }
 
+        #[ink::test]
+        fn test_with_different_lotteries() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let target = &lotto.targets[0];
+
+            let raffle_id = 1;
+            let nb_numbers = 5;
+            let smallest_number = 1;
+            let biggest_number = 50;
+
+            let result = lotto
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
+                .unwrap();
+
+            // the same raffle of another lottery has different numbers
+            let result_2 = lotto
+                .inner_get_numbers(
+                    target,
+                    1,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
+                .unwrap();
+            assert_ne!(result, result_2);
+
+            // the numbers can be verified only for the right lottery
+            assert_eq!(
+                Ok(true),
+                lotto.inner_verify_numbers(
+                    target,
+                    1,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                    result_2.clone()
+                )
+            );
+            assert_eq!(
+                Ok(false),
+                lotto.inner_verify_numbers(
+                    target,
+                    2,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                    result_2
+                )
+            );
+        }
+
+        #[ink::test]
+        fn test_get_numbers_with_bonus_pool() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let target = &lotto.targets[0];
+
+            let raffle_id = 1;
+            let nb_numbers = 5;
+            let smallest_number = 1;
+            let biggest_number = 50;
+            let bonus_pool = BonusPool {
+                nb_numbers: 2,
+                min_number: 1,
+                max_number: 12,
+            };
+
+            let result = lotto
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    Some(bonus_pool),
+                )
+                .unwrap();
+            assert_eq!(7, result.len());
+            let (main_numbers, bonus_numbers) = result.split_at(nb_numbers as usize);
+            for &n in bonus_numbers.iter() {
+                assert!(n >= bonus_pool.min_number);
+                assert!(n <= bonus_pool.max_number);
+            }
+
+            // the main numbers are the same as without bonus pool
+            let result_without_bonus = lotto
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(result_without_bonus, main_numbers.to_vec());
+
+            assert_eq!(
+                Ok(true),
+                lotto.inner_verify_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    Some(bonus_pool),
+                    result.clone()
+                )
+            );
+
+            // the bonus numbers must be the winning bonus numbers
+            let mut wrong_numbers = main_numbers.to_vec();
+            wrong_numbers.extend_from_slice(&[13, 14]);
+            assert_eq!(
+                Ok(false),
+                lotto.inner_verify_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    Some(bonus_pool),
+                    wrong_numbers
+                )
+            );
+        }
+
+        #[ink::test]
+        fn test_get_quick_picks() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let target = &lotto.targets[0];
+
+            let raffle_id = 1;
+            let nb_numbers = 5;
+            let smallest_number = 1;
+            let biggest_number = 50;
+
+            let tickets = lotto
+                .inner_get_quick_picks(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    3,
+                    4,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(4, tickets.len());
+            for numbers in tickets.iter() {
+                assert_eq!(nb_numbers as usize, numbers.len());
+                for &n in numbers.iter() {
+                    assert!(n >= smallest_number);
+                    assert!(n <= biggest_number);
+                }
+            }
+
+            // the tickets are different from each other and from the winning numbers
+            let winning_numbers = lotto
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
+                .unwrap();
+            for (i, numbers) in tickets.iter().enumerate() {
+                assert_ne!(winning_numbers, *numbers);
+                tickets[i + 1..]
+                    .iter()
+                    .for_each(|other| assert_ne!(numbers, other));
+            }
+
+            // same slots means same numbers
+            let tickets_2 = lotto
+                .inner_get_quick_picks(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    3,
+                    4,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(tickets, tickets_2);
+        }
+
+        #[ink::test]
+        fn test_get_tickets() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let target = &lotto.targets[0];
+
+            let raffle_id = 1;
+            let nb_tickets = 3;
+            let total_tickets = 10;
+
+            let tickets = lotto
+                .inner_get_tickets(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_tickets,
+                    total_tickets,
+                )
+                .unwrap();
+            assert_eq!(nb_tickets as usize, tickets.len());
+            for (i, ticket) in tickets.iter().enumerate() {
+                assert!(*ticket < total_tickets);
+                assert!(!tickets[i + 1..].contains(ticket));
+            }
+
+            // same request message means same result
+            let tickets_2 = lotto
+                .inner_get_tickets(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_tickets,
+                    total_tickets,
+                )
+                .unwrap();
+            assert_eq!(tickets, tickets_2);
+
+            // all tickets can be drawn
+            let mut all_tickets = lotto
+                .inner_get_tickets(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    total_tickets,
+                    total_tickets,
+                )
+                .unwrap();
+            all_tickets.sort();
+            assert_eq!((0..total_tickets).collect::<Vec<_>>(), all_tickets);
+
+            // but no more
+            assert_eq!(
+                Err(ContractError::NotEnoughTickets),
+                lotto.inner_get_tickets(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    total_tickets + 1,
+                    total_tickets
+                )
+            );
+        }
+
         #[ink::test]
         fn test_verify_numbers() {
             let _ = env_logger::try_init();
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let raffle_id = 1;
             let nb_numbers = 5;
@@ -730,16 +2629,27 @@ mod lotto_draw {
             let biggest_number = 50;
 
             let numbers = lotto
-                .inner_get_numbers(raffle_id, nb_numbers, smallest_number, biggest_number)
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
                 .unwrap();
 
             assert_eq!(
                 Ok(true),
                 lotto.inner_verify_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
                     raffle_id,
                     nb_numbers,
                     smallest_number,
                     biggest_number,
+                    None,
                     numbers.clone()
                 )
             );
@@ -747,10 +2657,13 @@ mod lotto_draw {
             assert_eq!(
                 Ok(false),
                 lotto.inner_verify_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
                     raffle_id + 1,
                     nb_numbers,
                     smallest_number,
                     biggest_number,
+                    None,
                     numbers.clone()
                 )
             );
@@ -762,6 +2675,7 @@ mod lotto_draw {
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let mut lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let raffle_id = 1;
             let nb_numbers = 5;
@@ -769,22 +2683,34 @@ mod lotto_draw {
             let biggest_number = 50;
 
             let numbers = lotto
-                .inner_get_numbers(raffle_id, nb_numbers, smallest_number, biggest_number)
+                .inner_get_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
+                    raffle_id,
+                    nb_numbers,
+                    smallest_number,
+                    biggest_number,
+                    None,
+                )
                 .unwrap();
 
             assert_eq!(
                 Ok(true),
                 lotto.inner_verify_numbers(
+                    target,
+                    DEFAULT_LOTTERY_ID,
                     raffle_id,
                     nb_numbers,
                     smallest_number,
                     biggest_number,
+                    None,
                     numbers.clone()
                 )
             );
 
-            let target_contract = lotto.get_target_contract().unwrap();
+            let target_contract = lotto.get_target_contract(target.contract_id).unwrap();
 
+            // the numbers drawn for another target are different
             let bad_contract_id: ContractId = [0; 32];
             lotto
                 .config_target_contract(
@@ -795,14 +2721,18 @@ mod lotto_draw {
                     None,
                 )
                 .unwrap();
+            let bad_target = lotto.ensure_client_configured(&bad_contract_id).unwrap();
 
             assert_eq!(
                 Ok(false),
                 lotto.inner_verify_numbers(
+                    bad_target,
+                    DEFAULT_LOTTERY_ID,
                     raffle_id,
                     nb_numbers,
                     smallest_number,
                     biggest_number,
+                    None,
                     numbers.clone()
                 )
             );
@@ -814,25 +2744,52 @@ mod lotto_draw {
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let draw_num = 2;
             let numbers = vec![15, 1, 44, 28];
 
-            let winners = lotto.inner_get_winners(draw_num, &numbers).unwrap();
+            let winners = lotto
+                .inner_get_winners(target, DEFAULT_LOTTERY_ID, draw_num, &numbers, &vec![])
+                .unwrap();
             ink::env::debug_println!("winners: {winners:?}");
         }
 
+        #[ink::test]
+        fn test_draw_and_resolve() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let target = &lotto.targets[0];
+
+            let raffle_id = 1;
+            let (numbers, winners) = lotto
+                .inner_draw_and_resolve(target, DEFAULT_LOTTERY_ID, raffle_id, 4, 1, 50, None)
+                .unwrap();
+
+            // the numbers are the same as the ones drawn in two round trips
+            let drawn_numbers = lotto
+                .inner_get_numbers(target, DEFAULT_LOTTERY_ID, raffle_id, 4, 1, 50, None)
+                .unwrap();
+            assert_eq!(drawn_numbers, numbers);
+            ink::env::debug_println!("numbers: {numbers:?} - winners: {winners:?}");
+        }
+
         #[ink::test]
         fn test_no_winner() {
             let _ = env_logger::try_init();
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let draw_num = 0;
             let numbers = vec![150, 1, 44, 2800];
 
-            let winners = lotto.inner_get_winners(draw_num, &numbers).unwrap();
+            let winners = lotto
+                .inner_get_winners(target, DEFAULT_LOTTERY_ID, draw_num, &numbers, &vec![])
+                .unwrap();
             assert_eq!(0, winners.len());
         }
 
@@ -842,14 +2799,390 @@ mod lotto_draw {
             pink_extension_runtime::mock_ext::mock_all_ext();
 
             let lotto = init_contract();
+            let target = &lotto.targets[0];
 
             let draw_num = 0;
             let numbers = vec![];
 
-            let result = lotto.inner_get_winners(draw_num, &numbers);
+            let result =
+                lotto.inner_get_winners(target, DEFAULT_LOTTERY_ID, draw_num, &numbers, &vec![]);
+            assert_eq!(Err(ContractError::NoNumber), result);
+        }
+
+        #[ink::test]
+        fn test_count_keno_matches() {
+            let drawn_numbers = vec![5, 12, 40, 8, 2, 77];
+
+            assert_eq!(0, count_matches(&drawn_numbers, &[1, 3]));
+            assert_eq!(1, count_matches(&drawn_numbers, &[5]));
+            assert_eq!(3, count_matches(&drawn_numbers, &[77, 6, 12, 2]));
+        }
+
+        #[ink::test]
+        fn test_tier_winners() {
+            let numbers = vec![5, 12, 40, 8];
+            let bonus_numbers = vec![7];
+            let tiers = vec![(4, 1), (4, 0), (3, 1)];
+            let tickets = vec![
+                (1, vec![5, 12, 40, 8], vec![7]),
+                (2, vec![5, 12, 40, 8], vec![3]),
+                (3, vec![8, 40, 5, 12], vec![3]),
+                (4, vec![5, 12, 40, 9], vec![7]),
+                // no tier for 3 main matches without bonus match
+                (5, vec![5, 12, 40, 9], vec![3]),
+            ];
+
+            assert_eq!(
+                vec![vec![1], vec![2, 3], vec![4]],
+                tier_winners(&numbers, &bonus_numbers, &tiers, tickets)
+            );
+        }
+
+        #[ink::test]
+        fn test_no_number_for_tiers() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let target = &lotto.targets[0];
+
+            let result =
+                lotto.inner_get_tier_winners(target, DEFAULT_LOTTERY_ID, 1, &[], &[], &[(1, 0)]);
+            assert_eq!(Err(ContractError::NoNumber), result);
+        }
+
+        #[ink::test]
+        fn test_no_number_for_keno() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let target = &lotto.targets[0];
+
+            let result = lotto.inner_get_keno_matches(target, DEFAULT_LOTTERY_ID, 1, &[]);
             assert_eq!(Err(ContractError::NoNumber), result);
         }
 
+        #[ink::test]
+        fn test_several_targets() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let mut lotto = init_contract();
+            let contract_id = config().contract_id;
+            let indexer_url = lotto.get_indexer_url(contract_id);
+            assert!(indexer_url.is_some());
+
+            // add a second target with its own indexer
+            let other_contract_id: ContractId = [1; 32];
+            lotto
+                .config_target_contract(
+                    "http://127.0.0.1:9944".to_string(),
+                    70,
+                    0,
+                    other_contract_id.to_vec(),
+                    None,
+                )
+                .unwrap();
+            assert_eq!(None, lotto.get_indexer_url(other_contract_id));
+            lotto
+                .config_indexer(other_contract_id, "http://127.0.0.1:3000".to_string())
+                .unwrap();
+            assert_eq!(
+                Some("http://127.0.0.1:3000".to_string()),
+                lotto.get_indexer_url(other_contract_id)
+            );
+            assert_eq!(
+                vec![contract_id, other_contract_id],
+                lotto.get_target_contracts()
+            );
+
+            // the indexer is kept when the target is updated
+            let (rpc, pallet_id, call_id, _) = lotto.get_target_contract(contract_id).unwrap();
+            lotto
+                .config_target_contract(rpc, pallet_id, call_id, contract_id.to_vec(), None)
+                .unwrap();
+            assert_eq!(indexer_url, lotto.get_indexer_url(contract_id));
+            assert_eq!(2, lotto.get_target_contracts().len());
+
+            // the targets draw different numbers
+            let numbers = lotto
+                .inner_get_numbers(&lotto.targets[0], DEFAULT_LOTTERY_ID, 1, 5, 1, 50, None)
+                .unwrap();
+            let other_numbers = lotto
+                .inner_get_numbers(&lotto.targets[1], DEFAULT_LOTTERY_ID, 1, 5, 1, 50, None)
+                .unwrap();
+            assert_ne!(numbers, other_numbers);
+
+            // a removed target is not served anymore
+            lotto.remove_target_contract(other_contract_id).unwrap();
+            assert_eq!(vec![contract_id], lotto.get_target_contracts());
+            assert_eq!(
+                Err(ContractError::ClientNotConfigured),
+                lotto.answer_request(other_contract_id)
+            );
+            assert_eq!(
+                Err(ContractError::ClientNotConfigured),
+                lotto.config_indexer(other_contract_id, "http://127.0.0.1:3000".to_string())
+            );
+            assert_eq!(
+                Err(ContractError::InvalidContractId),
+                lotto.verify_numbers(
+                    other_contract_id,
+                    DEFAULT_LOTTERY_ID,
+                    1,
+                    5,
+                    1,
+                    50,
+                    None,
+                    numbers
+                )
+            );
+        }
+
+        #[ink::test]
+        fn test_evm_target() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let mut lotto = init_contract();
+
+            // an EVM address has 20 bytes
+            assert_eq!(
+                Err(ContractError::InvalidAddressLength),
+                lotto.config_evm_target_contract(
+                    "http://127.0.0.1:8545".to_string(),
+                    [1; 32].to_vec(),
+                    None
+                )
+            );
+
+            let evm_contract_id: EvmContractId = [1; 20];
+            lotto
+                .config_evm_target_contract(
+                    "http://127.0.0.1:8545".to_string(),
+                    evm_contract_id.to_vec(),
+                    None,
+                )
+                .unwrap();
+            lotto
+                .config_evm_indexer(evm_contract_id, "http://127.0.0.1:3000".to_string())
+                .unwrap();
+            assert_eq!(vec![evm_contract_id], lotto.get_evm_target_contracts());
+            assert_eq!(
+                Some("http://127.0.0.1:3000".to_string()),
+                lotto.get_evm_indexer_url(evm_contract_id)
+            );
+
+            let evm_target = lotto
+                .ensure_evm_client_configured(&evm_contract_id)
+                .unwrap();
+            let request = LottoRequestMessage::new(
+                DEFAULT_LOTTERY_ID,
+                1,
+                Request::DrawNumbers(5, 1, 50, None),
+            );
+            let response = lotto
+                .handle_evm_request(evm_target, request.clone())
+                .unwrap();
+            assert_eq!(request, response.request);
+
+            // the numbers drawn for the EVM target are different from the ones of the ink! target
+            let numbers = lotto
+                .inner_get_numbers(evm_target, DEFAULT_LOTTERY_ID, 1, 5, 1, 50, None)
+                .unwrap();
+            assert_eq!(EvmResponse::Numbers(numbers.clone()), response.response);
+            let ink_numbers = lotto
+                .inner_get_numbers(&lotto.targets[0], DEFAULT_LOTTERY_ID, 1, 5, 1, 50, None)
+                .unwrap();
+            assert_ne!(numbers, ink_numbers);
+
+            // the response is sent encoded in ABI
+            assert_eq!(
+                Ok(response.clone()),
+                EvmResponseMessage::decode_abi(&response.encode_abi())
+            );
+
+            lotto.remove_evm_target_contract(evm_contract_id).unwrap();
+            assert_eq!(
+                Err(ContractError::ClientNotConfigured),
+                lotto.answer_evm_request(evm_contract_id)
+            );
+        }
+
+        #[ink::test]
+        fn test_parse_participants() {
+            assert_eq!(
+                Ok(AccountId::from(hex_literal::hex!(
+                    "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+                ))),
+                Config::parse_participant("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")
+            );
+            assert_eq!(
+                Err(ContractError::InvalidSs58Address),
+                Config::parse_participant("0x1212121212121212121212121212121212121212")
+            );
+
+            assert_eq!(
+                Ok(H160::from([0x12; 20])),
+                EvmConfig::parse_participant("0x1212121212121212121212121212121212121212")
+            );
+            assert_eq!(
+                Err(ContractError::InvalidEvmAddress),
+                EvmConfig::parse_participant("0x1212")
+            );
+        }
+
+        #[ink::test]
+        fn test_winner_source() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let mut lotto = init_contract();
+            let target = &lotto.targets[0];
+            let contract_id = target.contract_id;
+
+            // the indexer configured with `config_indexer` is a SubQuery indexer
+            let indexer_url = lotto.get_indexer_url(contract_id).unwrap();
+            assert_eq!(
+                Some(WinnerSource::SubQuery(indexer_url)),
+                lotto.get_winner_source(contract_id)
+            );
+
+            lotto
+                .config_winner_source(
+                    contract_id,
+                    WinnerSource::SubSquid("http://127.0.0.1:4350/graphql".to_string()),
+                )
+                .unwrap();
+            assert_eq!(
+                Some("http://127.0.0.1:4350/graphql".to_string()),
+                lotto.get_indexer_url(contract_id)
+            );
+
+            // no indexer when the participations are read on chain
+            lotto
+                .config_winner_source(contract_id, WinnerSource::OnChain)
+                .unwrap();
+            assert_eq!(
+                Some(WinnerSource::OnChain),
+                lotto.get_winner_source(contract_id)
+            );
+            assert_eq!(None, lotto.get_indexer_url(contract_id));
+            assert_eq!(
+                Err(ContractError::ClientNotConfigured),
+                lotto.config_winner_source([2; 32], WinnerSource::OnChain)
+            );
+
+            // the EVM smart contracts do not save the participations on chain
+            let evm_contract_id: EvmContractId = [1; 20];
+            lotto
+                .config_evm_target_contract(
+                    "http://127.0.0.1:8545".to_string(),
+                    evm_contract_id.to_vec(),
+                    None,
+                )
+                .unwrap();
+            let evm_target = lotto
+                .ensure_evm_client_configured(&evm_contract_id)
+                .unwrap();
+            assert_eq!(
+                Some(ContractError::IndexerNotConfigured),
+                evm_target.participation_reader().err()
+            );
+            assert_eq!(
+                Err(ContractError::UnsupportedWinnerSource),
+                lotto.config_evm_winner_source(evm_contract_id, WinnerSource::OnChain)
+            );
+            lotto
+                .config_evm_winner_source(
+                    evm_contract_id,
+                    WinnerSource::SubSquid("http://127.0.0.1:4350/graphql".to_string()),
+                )
+                .unwrap();
+            assert_eq!(
+                Some(WinnerSource::SubSquid(
+                    "http://127.0.0.1:4350/graphql".to_string()
+                )),
+                lotto.get_evm_winner_source(evm_contract_id)
+            );
+        }
+
+        #[ink::test]
+        fn test_indexer_queries() {
+            // the query of the existing SubQuery schema does not change
+            assert_eq!(
+                r#"{"query" : "{participations(filter:{and:[{numRaffle:{equalTo:\"2\"}},{numbers:{contains:\"5\"}},{numbers:{contains:\"40\"}},{bonusNumbers:{contains:\"7\"}}]}){ nodes { accountId } } }"}"#,
+                subquery_winners_query(DEFAULT_LOTTERY_ID, 2, &[5, 40], &[7])
+            );
+            assert_eq!(
+                r#"{"query" : "{kenoTickets(filter:{and:[{numRaffle:{equalTo:\"2\"}},{numLottery:{equalTo:\"1\"}}]}){ nodes { ticketId numbers } } }"}"#,
+                subquery_keno_query(1, 2)
+            );
+
+            assert_eq!(
+                r#"{"query" : "{participations(where:{numRaffle_eq:2,numbers_containsAll:[5, 40],bonusNumbers_containsAll:[7]}){ accountId } }"}"#,
+                subsquid_winners_query(DEFAULT_LOTTERY_ID, 2, &[5, 40], &[7])
+            );
+            assert_eq!(
+                r#"{"query" : "{participations(where:{numRaffle_eq:2,numLottery_eq:1,numbers_containsAll:[5, 40]}){ accountId } }"}"#,
+                subsquid_winners_query(1, 2, &[5, 40], &[])
+            );
+            assert_eq!(
+                r#"{"query" : "{kenoTickets(where:{numRaffle_eq:2,numLottery_eq:1}){ ticketId numbers } }"}"#,
+                subsquid_keno_query(1, 2)
+            );
+            assert_eq!(
+                r#"{"query" : "{participations(filter:{and:[{numRaffle:{equalTo:\"2\"}},{numLottery:{equalTo:\"1\"}}]}){ nodes { accountId numbers bonusNumbers } } }"}"#,
+                subquery_tickets_query(1, 2)
+            );
+            assert_eq!(
+                r#"{"query" : "{participations(where:{numRaffle_eq:2}){ accountId numbers bonusNumbers } }"}"#,
+                subsquid_tickets_query(DEFAULT_LOTTERY_ID, 2)
+            );
+
+            // the bonus numbers are missing for the participations without bonus numbers
+            let body = br#"{"data":{"participations":[{"accountId":"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","numbers":[5,40]}]}}"#;
+            let result: SubSquidTicketsResponse = serde_json_core::from_slice(body).unwrap().0;
+            assert_eq!(
+                vec![TicketNode {
+                    accountId: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+                    numbers: vec![5, 40],
+                    bonusNumbers: vec![],
+                }],
+                result.data.participations
+            );
+
+            // SubSquid returns the lists without nodes
+            let body = br#"{"data":{"participations":[{"accountId":"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"}]}}"#;
+            let result: SubSquidResponse = serde_json_core::from_slice(body).unwrap().0;
+            assert_eq!(
+                vec!["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"],
+                result
+                    .data
+                    .participations
+                    .iter()
+                    .map(|w| w.accountId)
+                    .collect::<Vec<_>>()
+            );
+
+            let body = br#"{"data":{"kenoTickets":[{"ticketId":0,"numbers":[5,40]},{"ticketId":1,"numbers":[3]}]}}"#;
+            let result: SubSquidKenoResponse = serde_json_core::from_slice(body).unwrap().0;
+            assert_eq!(
+                vec![
+                    KenoTicketNode {
+                        ticketId: 0,
+                        numbers: vec![5, 40]
+                    },
+                    KenoTicketNode {
+                        ticketId: 1,
+                        numbers: vec![3]
+                    },
+                ],
+                result.data.kenoTickets
+            );
+        }
+
         #[ink::test]
         #[ignore = "The target contract must be deployed on the Substrate node and a random number request must be submitted"]
         fn answer_request() {
@@ -858,8 +3191,101 @@ mod lotto_draw {
 
             let lotto = init_contract();
 
-            let r = lotto.answer_request().expect("failed to answer request");
+            let r = lotto
+                .answer_request(config().contract_id)
+                .expect("failed to answer request");
             ink::env::debug_println!("answer request: {r:?}");
         }
+
+        #[ink::test]
+        #[ignore = "The target contracts must be deployed on the Substrate node and a random number request must be submitted"]
+        fn answer_next_request() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+
+            let (contract_id, r) = lotto
+                .answer_next_request()
+                .expect("failed to answer request");
+            ink::env::debug_println!("answer request of {contract_id:02x?}: {r:?}");
+            assert!(lotto.get_target_contracts().contains(&contract_id));
+        }
+
+        #[ink::test]
+        #[ignore = "The target EVM contract must be deployed on the EVM node and a random number request must be submitted"]
+        fn answer_evm_request() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let mut lotto = init_contract();
+
+            let evm_contract_id: EvmContractId = hex::decode(get_env("EVM_CONTRACT_ID"))
+                .expect("hex decode failed")
+                .try_into()
+                .expect("incorrect length");
+            lotto
+                .config_evm_target_contract(get_env("EVM_RPC"), evm_contract_id.to_vec(), None)
+                .unwrap();
+
+            let r = lotto
+                .answer_evm_request(evm_contract_id)
+                .expect("failed to answer request");
+            ink::env::debug_println!("answer evm request: {r:?}");
+        }
+
+        #[ink::test]
+        #[ignore = "The target contract must be deployed on the Substrate node and a random number request must be submitted"]
+        fn dry_run_answer() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+            let contract_id = config().contract_id;
+
+            let requests = lotto
+                .peek_requests(contract_id, 1)
+                .expect("failed to peek requests");
+            ink::env::debug_println!("peek requests: {requests:?}");
+
+            let answer = lotto
+                .dry_run_answer(contract_id)
+                .expect("failed to dry run answer");
+            ink::env::debug_println!("dry run answer: {answer:?}");
+            assert_eq!(Some(&answer.response.request), requests.first());
+            assert_eq!(
+                Action::Reply(answer.response.encode()).encode(),
+                answer.action
+            );
+
+            // nothing is submitted, the request is still in the queue
+            assert_eq!(
+                requests,
+                lotto
+                    .peek_requests(contract_id, 1)
+                    .expect("failed to peek requests")
+            );
+        }
+
+        #[ink::test]
+        #[ignore = "The target contract must be deployed on the Substrate node and several requests must be submitted"]
+        fn answer_requests() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+
+            let summary = lotto
+                .answer_requests(config().contract_id, MAX_REQUESTS_BY_BATCH)
+                .expect("failed to answer requests");
+            ink::env::debug_println!("answer requests: {summary:?}");
+            assert!(
+                summary.processed.len() + summary.failed.len() <= MAX_REQUESTS_BY_BATCH as usize
+            );
+            assert_eq!(
+                summary.processed.is_empty() && summary.failed.is_empty(),
+                summary.tx_id.is_none()
+            );
+        }
     }
 }
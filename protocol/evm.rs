@@ -0,0 +1,729 @@
+//! ABI encoding of the messages exchanged with an EVM rollup anchor (ie a Solidity smart contract).
+//! The messages mirror `LottoRequestMessage` and `LottoResponseMessage`, except the winners
+//! which are EVM addresses (`H160`).
+//!
+//! Request: `abi.encode(uint16 version, uint32 lotteryId, uint32 raffleId, uint8 requestType, bytes args)`
+//! - 0 `DrawNumbers`: `abi.encode(uint8 nbNumbers, uint16 smallestNumber, uint16 biggestNumber, bool hasBonusPool, uint8 bonusNbNumbers, uint16 bonusMinNumber, uint16 bonusMaxNumber)`
+//! - 1 `CheckWinners`: `abi.encode(uint16[] numbers, uint16[] bonusNumbers)`
+//! - 2 `QuickPick`: `abi.encode(uint32 firstSlot, uint32 nbSlots, <same args as DrawNumbers>)`
+//! - 3 `DrawTickets`: `abi.encode(uint32 nbTickets, uint32 totalTickets)`
+//! - 4 `CheckKenoWinners`: `abi.encode(uint16[] numbers)`
+//! - 5 `DrawAndResolve`: same args as `DrawNumbers`
+//! - 6 `CheckTierWinners`: `abi.encode(uint16[] numbers, uint16[] bonusNumbers, (uint8 nbMainMatches, uint8 nbBonusMatches)[] tiers)`
+//!
+//! Response: `abi.encode(uint16 version, bytes request, uint8 responseType, bytes args)`
+//! - 0 `Numbers`: `abi.encode(uint16[] numbers)`
+//! - 1 `Winners`: `abi.encode(address[] winners)`
+//! - 2 `QuickPickNumbers`: `abi.encode(uint16[][] numbers)`
+//! - 3 `Tickets`: `abi.encode(uint32[] tickets)`
+//! - 4 `KenoMatches`: `abi.encode((uint32 ticket, uint8 nbMatches)[] matches)`
+//! - 5 `NumbersAndWinners`: `abi.encode(uint16[] numbers, address[] winners)`
+//! - 6 `TierWinners`: `abi.encode(address[][] winners)`
+//! - 7 `Error`: `abi.encode(bytes error)` (the error of the offchain rollup encoded in SCALE)
+
+use crate::{
+    ensure_version, BonusPool, LottoRequestMessage, Number, ProtocolError, ProtocolVersion,
+    Request, TicketIndex, PROTOCOL_VERSION,
+};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use ethabi::{ParamType, Token, Uint};
+
+/// EVM address of a participant
+pub type H160 = ethabi::Address;
+
+/// Same as `Response` with the EVM addresses of the winners
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum EvmResponse {
+    /// list of numbers
+    Numbers(Vec<Number>),
+    /// list of winners
+    Winners(Vec<H160>),
+    /// list of numbers for each quick pick slot
+    QuickPickNumbers(Vec<Vec<Number>>),
+    /// list of winning tickets
+    Tickets(Vec<TicketIndex>),
+    /// number of matches of each keno ticket
+    KenoMatches(Vec<(TicketIndex, u8)>),
+    /// list of numbers (the bonus numbers follow the main numbers) and list of winners
+    NumbersAndWinners(Vec<Number>, Vec<H160>),
+    /// list of winners for each prize tier, in the order of the tiers of the request
+    TierWinners(Vec<Vec<H160>>),
+    /// error of the offchain rollup (encoded in SCALE) when the request can not be answered
+    Error(Vec<u8>),
+}
+
+/// Same as `LottoResponseMessage` with the EVM addresses of the winners
+/// response pushed in the queue by the offchain rollup and read by the EVM smart contract
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct EvmResponseMessage {
+    /// version of the protocol used to build the message
+    pub version: ProtocolVersion,
+    /// initial request
+    pub request: LottoRequestMessage,
+    /// response
+    pub response: EvmResponse,
+}
+
+impl LottoRequestMessage {
+    /// encode the request for an EVM smart contract
+    pub fn encode_abi(&self) -> Vec<u8> {
+        let (request_type, args): (u8, Vec<Token>) = match &self.request {
+            Request::DrawNumbers(nb_numbers, smallest_number, biggest_number, bonus_pool) => (
+                0,
+                draw_tokens(*nb_numbers, *smallest_number, *biggest_number, bonus_pool),
+            ),
+            Request::CheckWinners(numbers, bonus_numbers) => (
+                1,
+                vec![numbers_token(numbers), numbers_token(bonus_numbers)],
+            ),
+            Request::QuickPick(
+                first_slot,
+                nb_slots,
+                nb_numbers,
+                smallest_number,
+                biggest_number,
+                bonus_pool,
+            ) => {
+                let mut args = vec![uint_token(*first_slot), uint_token(*nb_slots)];
+                args.extend(draw_tokens(
+                    *nb_numbers,
+                    *smallest_number,
+                    *biggest_number,
+                    bonus_pool,
+                ));
+                (2, args)
+            }
+            Request::DrawTickets(nb_tickets, total_tickets) => {
+                (3, vec![uint_token(*nb_tickets), uint_token(*total_tickets)])
+            }
+            Request::CheckKenoWinners(numbers) => (4, vec![numbers_token(numbers)]),
+            Request::DrawAndResolve(nb_numbers, smallest_number, biggest_number, bonus_pool) => (
+                5,
+                draw_tokens(*nb_numbers, *smallest_number, *biggest_number, bonus_pool),
+            ),
+            Request::CheckTierWinners(numbers, bonus_numbers, tiers) => (
+                6,
+                vec![
+                    numbers_token(numbers),
+                    numbers_token(bonus_numbers),
+                    Token::Array(
+                        tiers
+                            .iter()
+                            .map(|(nb_main_matches, nb_bonus_matches)| {
+                                Token::Tuple(vec![
+                                    uint_token(*nb_main_matches),
+                                    uint_token(*nb_bonus_matches),
+                                ])
+                            })
+                            .collect(),
+                    ),
+                ],
+            ),
+        };
+
+        ethabi::encode(&[
+            uint_token(self.version),
+            uint_token(self.lottery_id),
+            uint_token(self.raffle_id),
+            uint_token(request_type),
+            Token::Bytes(ethabi::encode(&args)),
+        ])
+    }
+
+    /// decode a request sent by an EVM smart contract.
+    /// A request built with another version of the protocol is rejected before reading its args
+    pub fn decode_abi(data: &[u8]) -> Result<Self, ProtocolError> {
+        let mut tokens = Tokens::decode(
+            &[
+                ParamType::Uint(16),
+                ParamType::Uint(32),
+                ParamType::Uint(32),
+                ParamType::Uint(8),
+                ParamType::Bytes,
+            ],
+            data,
+        )?;
+        let version = tokens.uint()?;
+        ensure_version(version)?;
+        let lottery_id = tokens.uint()?;
+        let raffle_id = tokens.uint()?;
+        let request_type: u8 = tokens.uint()?;
+        let args = tokens.bytes()?;
+
+        let request = match request_type {
+            0 => {
+                let mut args = Tokens::decode(&draw_types(), &args)?;
+                Request::DrawNumbers(args.uint()?, args.uint()?, args.uint()?, args.bonus_pool()?)
+            }
+            1 => {
+                let mut args = Tokens::decode(&[numbers_type(), numbers_type()], &args)?;
+                Request::CheckWinners(args.numbers()?, args.numbers()?)
+            }
+            2 => {
+                let mut types = vec![ParamType::Uint(32), ParamType::Uint(32)];
+                types.extend(draw_types());
+                let mut args = Tokens::decode(&types, &args)?;
+                Request::QuickPick(
+                    args.uint()?,
+                    args.uint()?,
+                    args.uint()?,
+                    args.uint()?,
+                    args.uint()?,
+                    args.bonus_pool()?,
+                )
+            }
+            3 => {
+                let mut args = Tokens::decode(&[ParamType::Uint(32), ParamType::Uint(32)], &args)?;
+                Request::DrawTickets(args.uint()?, args.uint()?)
+            }
+            4 => {
+                let mut args = Tokens::decode(&[numbers_type()], &args)?;
+                Request::CheckKenoWinners(args.numbers()?)
+            }
+            5 => {
+                let mut args = Tokens::decode(&draw_types(), &args)?;
+                Request::DrawAndResolve(
+                    args.uint()?,
+                    args.uint()?,
+                    args.uint()?,
+                    args.bonus_pool()?,
+                )
+            }
+            6 => {
+                let mut args = Tokens::decode(
+                    &[
+                        numbers_type(),
+                        numbers_type(),
+                        ParamType::Array(Box::new(ParamType::Tuple(vec![
+                            ParamType::Uint(8),
+                            ParamType::Uint(8),
+                        ]))),
+                    ],
+                    &args,
+                )?;
+                let numbers = args.numbers()?;
+                let bonus_numbers = args.numbers()?;
+                let tiers = args
+                    .array()?
+                    .into_iter()
+                    .map(|token| {
+                        let mut tuple = Tokens::from_tuple(token)?;
+                        Ok((tuple.uint()?, tuple.uint()?))
+                    })
+                    .collect::<Result<_, ProtocolError>>()?;
+                Request::CheckTierWinners(numbers, bonus_numbers, tiers)
+            }
+            _ => return Err(ProtocolError::InvalidEncoding),
+        };
+
+        Ok(Self {
+            version,
+            lottery_id,
+            raffle_id,
+            request,
+        })
+    }
+}
+
+impl EvmResponseMessage {
+    /// build the response with the current version of the protocol
+    pub fn new(request: LottoRequestMessage, response: EvmResponse) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            request,
+            response,
+        }
+    }
+
+    /// encode the response for an EVM smart contract
+    pub fn encode_abi(&self) -> Vec<u8> {
+        let (response_type, args): (u8, Vec<Token>) = match &self.response {
+            EvmResponse::Numbers(numbers) => (0, vec![numbers_token(numbers)]),
+            EvmResponse::Winners(winners) => (1, vec![addresses_token(winners)]),
+            EvmResponse::QuickPickNumbers(tickets) => (
+                2,
+                vec![Token::Array(
+                    tickets
+                        .iter()
+                        .map(|numbers| numbers_token(numbers))
+                        .collect(),
+                )],
+            ),
+            EvmResponse::Tickets(tickets) => (
+                3,
+                vec![Token::Array(
+                    tickets.iter().map(|t| uint_token(*t)).collect(),
+                )],
+            ),
+            EvmResponse::KenoMatches(matches) => (
+                4,
+                vec![Token::Array(
+                    matches
+                        .iter()
+                        .map(|(ticket, nb_matches)| {
+                            Token::Tuple(vec![uint_token(*ticket), uint_token(*nb_matches)])
+                        })
+                        .collect(),
+                )],
+            ),
+            EvmResponse::NumbersAndWinners(numbers, winners) => {
+                (5, vec![numbers_token(numbers), addresses_token(winners)])
+            }
+            EvmResponse::TierWinners(winners) => (
+                6,
+                vec![Token::Array(
+                    winners
+                        .iter()
+                        .map(|addresses| addresses_token(addresses))
+                        .collect(),
+                )],
+            ),
+            EvmResponse::Error(error) => (7, vec![Token::Bytes(error.clone())]),
+        };
+
+        ethabi::encode(&[
+            uint_token(self.version),
+            Token::Bytes(self.request.encode_abi()),
+            uint_token(response_type),
+            Token::Bytes(ethabi::encode(&args)),
+        ])
+    }
+
+    /// decode a response sent to an EVM smart contract.
+    /// A response (or an initial request) built with another version of the protocol is rejected
+    pub fn decode_abi(data: &[u8]) -> Result<Self, ProtocolError> {
+        let mut tokens = Tokens::decode(
+            &[
+                ParamType::Uint(16),
+                ParamType::Bytes,
+                ParamType::Uint(8),
+                ParamType::Bytes,
+            ],
+            data,
+        )?;
+        let version = tokens.uint()?;
+        ensure_version(version)?;
+        let request = LottoRequestMessage::decode_abi(&tokens.bytes()?)?;
+        let response_type: u8 = tokens.uint()?;
+        let args = tokens.bytes()?;
+
+        let response = match response_type {
+            0 => EvmResponse::Numbers(Tokens::decode(&[numbers_type()], &args)?.numbers()?),
+            1 => EvmResponse::Winners(Tokens::decode(&[addresses_type()], &args)?.addresses()?),
+            2 => {
+                let mut args =
+                    Tokens::decode(&[ParamType::Array(Box::new(numbers_type()))], &args)?;
+                let tickets = args
+                    .array()?
+                    .into_iter()
+                    .map(numbers_from)
+                    .collect::<Result<_, _>>()?;
+                EvmResponse::QuickPickNumbers(tickets)
+            }
+            3 => {
+                let mut args =
+                    Tokens::decode(&[ParamType::Array(Box::new(ParamType::Uint(32)))], &args)?;
+                let tickets = args
+                    .array()?
+                    .into_iter()
+                    .map(uint_from)
+                    .collect::<Result<_, _>>()?;
+                EvmResponse::Tickets(tickets)
+            }
+            4 => {
+                let mut args = Tokens::decode(
+                    &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+                        ParamType::Uint(32),
+                        ParamType::Uint(8),
+                    ])))],
+                    &args,
+                )?;
+                let matches = args
+                    .array()?
+                    .into_iter()
+                    .map(|token| {
+                        let mut tuple = Tokens::from_tuple(token)?;
+                        Ok((tuple.uint()?, tuple.uint()?))
+                    })
+                    .collect::<Result<_, ProtocolError>>()?;
+                EvmResponse::KenoMatches(matches)
+            }
+            5 => {
+                let mut args = Tokens::decode(&[numbers_type(), addresses_type()], &args)?;
+                EvmResponse::NumbersAndWinners(args.numbers()?, args.addresses()?)
+            }
+            6 => {
+                let mut args =
+                    Tokens::decode(&[ParamType::Array(Box::new(addresses_type()))], &args)?;
+                let winners = args
+                    .array()?
+                    .into_iter()
+                    .map(addresses_from)
+                    .collect::<Result<_, _>>()?;
+                EvmResponse::TierWinners(winners)
+            }
+            7 => EvmResponse::Error(Tokens::decode(&[ParamType::Bytes], &args)?.bytes()?),
+            _ => return Err(ProtocolError::InvalidEncoding),
+        };
+
+        Ok(Self {
+            version,
+            request,
+            response,
+        })
+    }
+}
+
+fn uint_token<T: Into<Uint>>(value: T) -> Token {
+    Token::Uint(value.into())
+}
+
+fn numbers_token(numbers: &[Number]) -> Token {
+    Token::Array(numbers.iter().map(|n| uint_token(*n)).collect())
+}
+
+fn addresses_token(addresses: &[H160]) -> Token {
+    Token::Array(addresses.iter().map(|a| Token::Address(*a)).collect())
+}
+
+/// args to draw the numbers (no bonus pool is encoded with `hasBonusPool` false and zeros)
+fn draw_tokens(
+    nb_numbers: u8,
+    smallest_number: Number,
+    biggest_number: Number,
+    bonus_pool: &Option<BonusPool>,
+) -> Vec<Token> {
+    let bonus_pool_tokens = match bonus_pool {
+        Some(bonus_pool) => vec![
+            Token::Bool(true),
+            uint_token(bonus_pool.nb_numbers),
+            uint_token(bonus_pool.min_number),
+            uint_token(bonus_pool.max_number),
+        ],
+        None => vec![
+            Token::Bool(false),
+            uint_token(0u8),
+            uint_token(0u16),
+            uint_token(0u16),
+        ],
+    };
+    let mut tokens = vec![
+        uint_token(nb_numbers),
+        uint_token(smallest_number),
+        uint_token(biggest_number),
+    ];
+    tokens.extend(bonus_pool_tokens);
+    tokens
+}
+
+fn numbers_type() -> ParamType {
+    ParamType::Array(Box::new(ParamType::Uint(16)))
+}
+
+fn addresses_type() -> ParamType {
+    ParamType::Array(Box::new(ParamType::Address))
+}
+
+fn draw_types() -> Vec<ParamType> {
+    vec![
+        ParamType::Uint(8),
+        ParamType::Uint(16),
+        ParamType::Uint(16),
+        ParamType::Bool,
+        ParamType::Uint(8),
+        ParamType::Uint(16),
+        ParamType::Uint(16),
+    ]
+}
+
+/// decoded tokens, read in the order of the params
+struct Tokens(vec::IntoIter<Token>);
+
+impl Tokens {
+    fn decode(types: &[ParamType], data: &[u8]) -> Result<Self, ProtocolError> {
+        let tokens = ethabi::decode(types, data).or(Err(ProtocolError::InvalidEncoding))?;
+        Ok(Self(tokens.into_iter()))
+    }
+
+    /// tokens of a tuple, read in the order of its fields
+    fn from_tuple(token: Token) -> Result<Self, ProtocolError> {
+        let tokens = token.into_tuple().ok_or(ProtocolError::InvalidEncoding)?;
+        Ok(Self(tokens.into_iter()))
+    }
+
+    fn next(&mut self) -> Result<Token, ProtocolError> {
+        self.0.next().ok_or(ProtocolError::InvalidEncoding)
+    }
+
+    fn uint<T: TryFrom<Uint>>(&mut self) -> Result<T, ProtocolError> {
+        uint_from(self.next()?)
+    }
+
+    fn bool(&mut self) -> Result<bool, ProtocolError> {
+        self.next()?
+            .into_bool()
+            .ok_or(ProtocolError::InvalidEncoding)
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        self.next()?
+            .into_bytes()
+            .ok_or(ProtocolError::InvalidEncoding)
+    }
+
+    fn array(&mut self) -> Result<Vec<Token>, ProtocolError> {
+        self.next()?
+            .into_array()
+            .ok_or(ProtocolError::InvalidEncoding)
+    }
+
+    fn numbers(&mut self) -> Result<Vec<Number>, ProtocolError> {
+        numbers_from(self.next()?)
+    }
+
+    fn addresses(&mut self) -> Result<Vec<H160>, ProtocolError> {
+        addresses_from(self.next()?)
+    }
+
+    fn bonus_pool(&mut self) -> Result<Option<BonusPool>, ProtocolError> {
+        let has_bonus_pool = self.bool()?;
+        let bonus_pool = BonusPool {
+            nb_numbers: self.uint()?,
+            min_number: self.uint()?,
+            max_number: self.uint()?,
+        };
+        Ok(if has_bonus_pool {
+            Some(bonus_pool)
+        } else {
+            None
+        })
+    }
+}
+
+/// the value must fit in the type of the message
+fn uint_from<T: TryFrom<Uint>>(token: Token) -> Result<T, ProtocolError> {
+    let value = token.into_uint().ok_or(ProtocolError::InvalidEncoding)?;
+    T::try_from(value).or(Err(ProtocolError::InvalidEncoding))
+}
+
+fn addresses_from(token: Token) -> Result<Vec<H160>, ProtocolError> {
+    token
+        .into_array()
+        .ok_or(ProtocolError::InvalidEncoding)?
+        .into_iter()
+        .map(|token| token.into_address().ok_or(ProtocolError::InvalidEncoding))
+        .collect()
+}
+
+fn numbers_from(token: Token) -> Result<Vec<Number>, ProtocolError> {
+    token
+        .into_array()
+        .ok_or(ProtocolError::InvalidEncoding)?
+        .into_iter()
+        .map(uint_from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    fn check_request(request: LottoRequestMessage) {
+        assert_eq!(
+            Ok(request.clone()),
+            LottoRequestMessage::decode_abi(&request.encode_abi())
+        );
+    }
+
+    fn check_response(response: EvmResponseMessage) {
+        assert_eq!(
+            Ok(response.clone()),
+            EvmResponseMessage::decode_abi(&response.encode_abi())
+        );
+    }
+
+    fn draw_numbers() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::DrawNumbers(4, 1, 50, None))
+    }
+
+    fn check_winners() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::CheckWinners(vec![5, 40, 8, 2], vec![7]))
+    }
+
+    /// the encoding must be the same as the one of the EVM smart contract
+    #[test]
+    fn test_encode_abi_requests() {
+        assert_eq!(
+            hex!(
+                "0000000000000000000000000000000000000000000000000000000000000001" // version
+                "0000000000000000000000000000000000000000000000000000000000000001" // lottery id
+                "0000000000000000000000000000000000000000000000000000000000000002" // raffle id
+                "0000000000000000000000000000000000000000000000000000000000000000" // request type
+                "00000000000000000000000000000000000000000000000000000000000000a0" // offset of the args
+                "00000000000000000000000000000000000000000000000000000000000000e0" // length of the args
+                "0000000000000000000000000000000000000000000000000000000000000004" // nb numbers
+                "0000000000000000000000000000000000000000000000000000000000000001" // smallest number
+                "0000000000000000000000000000000000000000000000000000000000000032" // biggest number
+                "0000000000000000000000000000000000000000000000000000000000000000" // no bonus pool
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+            )
+            .to_vec(),
+            draw_numbers().encode_abi()
+        );
+
+        let bonus_pool = BonusPool {
+            nb_numbers: 1,
+            min_number: 1,
+            max_number: 10,
+        };
+        check_request(draw_numbers());
+        check_request(LottoRequestMessage::new(
+            1,
+            2,
+            Request::DrawNumbers(5, 1, 50, Some(bonus_pool)),
+        ));
+        check_request(check_winners());
+        check_request(LottoRequestMessage::new(
+            1,
+            2,
+            Request::QuickPick(3, 2, 4, 1, 50, Some(bonus_pool)),
+        ));
+        check_request(LottoRequestMessage::new(1, 2, Request::DrawTickets(2, 10)));
+        check_request(LottoRequestMessage::new(
+            1,
+            2,
+            Request::CheckKenoWinners(vec![5, 40]),
+        ));
+        check_request(LottoRequestMessage::new(
+            1,
+            2,
+            Request::DrawAndResolve(4, 1, 50, None),
+        ));
+        check_request(LottoRequestMessage::new(
+            1,
+            2,
+            Request::CheckTierWinners(vec![5, 40], vec![7], vec![(2, 1), (2, 0)]),
+        ));
+    }
+
+    #[test]
+    fn test_encode_abi_responses() {
+        let winner = H160::from([1u8; 20]);
+        assert_eq!(
+            hex!(
+                "0000000000000000000000000000000000000000000000000000000000000001" // version
+                "0000000000000000000000000000000000000000000000000000000000000080" // offset of the request
+                "0000000000000000000000000000000000000000000000000000000000000001" // response type
+                "0000000000000000000000000000000000000000000000000000000000000280" // offset of the args
+                "00000000000000000000000000000000000000000000000000000000000001e0" // length of the request
+                "0000000000000000000000000000000000000000000000000000000000000001"
+                "0000000000000000000000000000000000000000000000000000000000000001"
+                "0000000000000000000000000000000000000000000000000000000000000002"
+                "0000000000000000000000000000000000000000000000000000000000000001"
+                "00000000000000000000000000000000000000000000000000000000000000a0"
+                "0000000000000000000000000000000000000000000000000000000000000120"
+                "0000000000000000000000000000000000000000000000000000000000000040"
+                "00000000000000000000000000000000000000000000000000000000000000e0"
+                "0000000000000000000000000000000000000000000000000000000000000004"
+                "0000000000000000000000000000000000000000000000000000000000000005"
+                "0000000000000000000000000000000000000000000000000000000000000028"
+                "0000000000000000000000000000000000000000000000000000000000000008"
+                "0000000000000000000000000000000000000000000000000000000000000002"
+                "0000000000000000000000000000000000000000000000000000000000000001"
+                "0000000000000000000000000000000000000000000000000000000000000007"
+                "0000000000000000000000000000000000000000000000000000000000000060" // length of the args
+                "0000000000000000000000000000000000000000000000000000000000000020" // offset of the winners
+                "0000000000000000000000000000000000000000000000000000000000000001" // nb winners
+                "0000000000000000000000000101010101010101010101010101010101010101" // winner
+            )
+            .to_vec(),
+            EvmResponseMessage::new(check_winners(), EvmResponse::Winners(vec![winner]))
+                .encode_abi()
+        );
+
+        check_response(EvmResponseMessage::new(
+            draw_numbers(),
+            EvmResponse::Numbers(vec![5, 40, 8, 2]),
+        ));
+        check_response(EvmResponseMessage::new(
+            check_winners(),
+            EvmResponse::Winners(vec![winner, H160::from([2u8; 20])]),
+        ));
+        check_response(EvmResponseMessage::new(
+            draw_numbers(),
+            EvmResponse::QuickPickNumbers(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]),
+        ));
+        check_response(EvmResponseMessage::new(
+            draw_numbers(),
+            EvmResponse::Tickets(vec![7, 3]),
+        ));
+        check_response(EvmResponseMessage::new(
+            draw_numbers(),
+            EvmResponse::KenoMatches(vec![(0, 2), (1, 0)]),
+        ));
+        check_response(EvmResponseMessage::new(
+            draw_numbers(),
+            EvmResponse::NumbersAndWinners(vec![5, 40, 8, 2], vec![winner]),
+        ));
+        check_response(EvmResponseMessage::new(
+            check_winners(),
+            EvmResponse::TierWinners(vec![vec![], vec![winner, H160::from([2u8; 20])]]),
+        ));
+        check_response(EvmResponseMessage::new(
+            draw_numbers(),
+            EvmResponse::Error(vec![10]),
+        ));
+    }
+
+    #[test]
+    fn test_decode_abi_invalid_messages() {
+        let request = |version: u16, request_type: u8, nb_numbers: u16| {
+            ethabi::encode(&[
+                uint_token(version),
+                uint_token(1u32),
+                uint_token(2u32),
+                uint_token(request_type),
+                Token::Bytes(ethabi::encode(&[
+                    uint_token(nb_numbers),
+                    uint_token(1u16),
+                    uint_token(50u16),
+                    Token::Bool(false),
+                    uint_token(0u8),
+                    uint_token(0u16),
+                    uint_token(0u16),
+                ])),
+            ])
+        };
+        assert_eq!(
+            Ok(draw_numbers()),
+            LottoRequestMessage::decode_abi(&request(PROTOCOL_VERSION, 0, 4))
+        );
+
+        // unknown version
+        assert_eq!(
+            Err(ProtocolError::UnsupportedVersion(PROTOCOL_VERSION + 1)),
+            LottoRequestMessage::decode_abi(&request(PROTOCOL_VERSION + 1, 0, 4))
+        );
+        // unknown request
+        assert_eq!(
+            Err(ProtocolError::InvalidEncoding),
+            LottoRequestMessage::decode_abi(&request(PROTOCOL_VERSION, 7, 4))
+        );
+        // the number of numbers does not fit in a u8
+        assert_eq!(
+            Err(ProtocolError::InvalidEncoding),
+            LottoRequestMessage::decode_abi(&request(PROTOCOL_VERSION, 0, 256))
+        );
+        // truncated message
+        let data = request(PROTOCOL_VERSION, 0, 4);
+        assert_eq!(
+            Err(ProtocolError::InvalidEncoding),
+            LottoRequestMessage::decode_abi(&data[..data.len() - 32])
+        );
+    }
+}
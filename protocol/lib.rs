@@ -0,0 +1,383 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Messages exchanged between the Ink! smart contract and the offchain rollup (phat contract).
+//! Both contracts depend on this crate so the encoding of the messages can not drift.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use ink::primitives::AccountId;
+
+/// ABI encoding of the messages for the EVM smart contracts
+#[cfg(feature = "evm")]
+pub mod evm;
+
+pub type LotteryId = u32;
+pub type RaffleId = u32;
+pub type Number = u16;
+/// Position of a ticket in a classic raffle (first ticket is 0)
+pub type TicketIndex = u32;
+pub type ProtocolVersion = u16;
+
+/// Version of the protocol, to increment when the encoding of a message changes.
+/// Appending a variant to `Request` or `Response` (ie `DrawAndResolve`, `CheckTierWinners` or `Error`)
+/// does not change the encoding of the existing messages, so it is treated as compatible:
+/// a peer which does not know the new variant fails to decode the message instead of misreading it
+pub const PROTOCOL_VERSION: ProtocolVersion = 1;
+
+#[derive(Debug, Eq, PartialEq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ProtocolError {
+    /// the message was built with another version of the protocol
+    UnsupportedVersion(ProtocolVersion),
+    /// the message can not be decoded
+    InvalidEncoding,
+}
+
+/// Second pool of numbers (ie the bonus ball) drawn from another range
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct BonusPool {
+    pub nb_numbers: u8,
+    pub min_number: Number,
+    pub max_number: Number,
+}
+
+/// Message to request the lotto lotto_draw or the list of winners
+/// message pushed in the queue by the Ink! smart contract and read by the offchain rollup
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct LottoRequestMessage {
+    /// version of the protocol used to build the message
+    pub version: ProtocolVersion,
+    /// lottery id
+    pub lottery_id: LotteryId,
+    /// raffle id
+    pub raffle_id: RaffleId,
+    /// request
+    pub request: Request,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Request {
+    /// request to lotto_draw the n number between min and max values
+    /// arg1: number of numbers for the lotto_draw
+    /// arg2:  smallest number for the lotto_draw
+    /// arg2:  biggest number for the lotto_draw
+    /// arg4: second pool of numbers, the bonus numbers follow the main numbers in the response
+    DrawNumbers(u8, Number, Number, Option<BonusPool>),
+    /// request to check if there is a winner for the given numbers
+    /// arg1: main numbers
+    /// arg2: bonus numbers (empty when there is no bonus pool)
+    CheckWinners(Vec<Number>, Vec<Number>),
+    /// request to draw the numbers for the quick pick tickets
+    /// arg1: first slot
+    /// arg2: number of slots (one ticket by slot)
+    /// arg3: number of numbers by ticket
+    /// arg4: smallest number
+    /// arg5: biggest number
+    /// arg6: second pool of numbers, the bonus numbers follow the main numbers in each ticket
+    QuickPick(u32, u32, u8, Number, Number, Option<BonusPool>),
+    /// request to draw the winning tickets of a classic raffle
+    /// arg1: number of tickets to draw
+    /// arg2: number of tickets sold (the tickets are drawn between 0 and this number - 1)
+    DrawTickets(u32, TicketIndex),
+    /// request to count the matches of each keno ticket with the drawn numbers
+    /// arg1: drawn numbers
+    CheckKenoWinners(Vec<Number>),
+    /// request to draw the numbers and check the winners in one round trip
+    /// (same args as `DrawNumbers`, the indexer must be up to date when it is sent)
+    DrawAndResolve(u8, Number, Number, Option<BonusPool>),
+    /// request to find the winners of each prize tier for the given numbers
+    /// arg1: main numbers
+    /// arg2: bonus numbers (empty when there is no bonus pool)
+    /// arg3: prize tiers (number of main matches, number of bonus matches)
+    CheckTierWinners(Vec<Number>, Vec<Number>, Vec<(u8, u8)>),
+}
+
+/// Message sent to provide the lotto lotto_draw or the list of winners
+/// response pushed in the queue by the offchain rollup and read by the Ink! smart contract
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct LottoResponseMessage {
+    /// version of the protocol used to build the message
+    pub version: ProtocolVersion,
+    /// initial request
+    pub request: LottoRequestMessage,
+    /// response
+    pub response: Response,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Response {
+    /// list of numbers
+    Numbers(Vec<Number>),
+    /// list of winners
+    Winners(Vec<AccountId>),
+    /// list of numbers for each quick pick slot
+    QuickPickNumbers(Vec<Vec<Number>>),
+    /// list of winning tickets
+    Tickets(Vec<TicketIndex>),
+    /// number of matches of each keno ticket
+    KenoMatches(Vec<(TicketIndex, u8)>),
+    /// list of numbers (the bonus numbers follow the main numbers) and list of winners
+    NumbersAndWinners(Vec<Number>, Vec<AccountId>),
+    /// list of winners for each prize tier, in the order of the tiers of the request
+    TierWinners(Vec<Vec<AccountId>>),
+    /// error of the offchain rollup (encoded in SCALE) when the request can not be answered
+    Error(Vec<u8>),
+}
+
+impl LottoRequestMessage {
+    /// build the request with the current version of the protocol
+    pub fn new(lottery_id: LotteryId, raffle_id: RaffleId, request: Request) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            lottery_id,
+            raffle_id,
+            request,
+        }
+    }
+
+    /// check if the request was built with the current version of the protocol
+    pub fn ensure_version(&self) -> Result<(), ProtocolError> {
+        ensure_version(self.version)
+    }
+}
+
+impl LottoResponseMessage {
+    /// build the response with the current version of the protocol
+    pub fn new(request: LottoRequestMessage, response: Response) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            request,
+            response,
+        }
+    }
+
+    /// check if the response and the initial request were built with the current version of the protocol
+    pub fn ensure_version(&self) -> Result<(), ProtocolError> {
+        ensure_version(self.version)?;
+        self.request.ensure_version()
+    }
+}
+
+fn ensure_version(version: ProtocolVersion) -> Result<(), ProtocolError> {
+    if version != PROTOCOL_VERSION {
+        return Err(ProtocolError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Debug;
+    use hex_literal::hex;
+    use scale::{Decode, Encode};
+
+    /// the encoding of the message must not change without a new version of the protocol
+    fn check_encoding<T: Encode + Decode + Debug + PartialEq>(message: T, expected: &[u8]) {
+        assert_eq!(expected, message.encode().as_slice());
+        assert_eq!(message, T::decode(&mut &expected[..]).unwrap());
+    }
+
+    fn draw_numbers() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::DrawNumbers(4, 1, 50, None))
+    }
+
+    fn check_winners() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::CheckWinners(vec![5, 40, 8, 2], vec![7]))
+    }
+
+    fn quick_pick() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::QuickPick(3, 2, 4, 1, 50, None))
+    }
+
+    fn draw_tickets() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::DrawTickets(2, 10))
+    }
+
+    fn check_keno_winners() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::CheckKenoWinners(vec![5, 40]))
+    }
+
+    fn draw_and_resolve() -> LottoRequestMessage {
+        LottoRequestMessage::new(1, 2, Request::DrawAndResolve(4, 1, 50, None))
+    }
+
+    fn check_tier_winners() -> LottoRequestMessage {
+        LottoRequestMessage::new(
+            1,
+            2,
+            Request::CheckTierWinners(vec![5, 40], vec![7], vec![(2, 1), (2, 0)]),
+        )
+    }
+
+    #[test]
+    fn test_encode_requests() {
+        check_encoding(
+            draw_numbers(),
+            &hex!("0100 01000000 02000000 00 04 0100 3200 00"),
+        );
+        let bonus_pool = BonusPool {
+            nb_numbers: 1,
+            min_number: 1,
+            max_number: 10,
+        };
+        check_encoding(
+            LottoRequestMessage::new(1, 2, Request::DrawNumbers(5, 1, 50, Some(bonus_pool))),
+            &hex!("0100 01000000 02000000 00 05 0100 3200 01 01 0100 0a00"),
+        );
+        check_encoding(
+            check_winners(),
+            &hex!("0100 01000000 02000000 01 10 0500 2800 0800 0200 04 0700"),
+        );
+        check_encoding(
+            quick_pick(),
+            &hex!("0100 01000000 02000000 02 03000000 02000000 04 0100 3200 00"),
+        );
+        check_encoding(
+            draw_tickets(),
+            &hex!("0100 01000000 02000000 03 02000000 0a000000"),
+        );
+        check_encoding(
+            check_keno_winners(),
+            &hex!("0100 01000000 02000000 04 08 0500 2800"),
+        );
+        check_encoding(
+            draw_and_resolve(),
+            &hex!("0100 01000000 02000000 05 04 0100 3200 00"),
+        );
+        check_encoding(
+            check_tier_winners(),
+            &hex!("0100 01000000 02000000 06 08 0500 2800 04 0700 08 0201 0200"),
+        );
+    }
+
+    #[test]
+    fn test_encode_responses() {
+        check_encoding(
+            LottoResponseMessage::new(draw_numbers(), Response::Numbers(vec![5, 40, 8, 2])),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 00 04 0100 3200 00"
+                "00 10 0500 2800 0800 0200"
+            ),
+        );
+        check_encoding(
+            LottoResponseMessage::new(
+                check_winners(),
+                Response::Winners(vec![AccountId::from([1u8; 32])]),
+            ),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 01 10 0500 2800 0800 0200 04 0700"
+                "01 04 0101010101010101010101010101010101010101010101010101010101010101"
+            ),
+        );
+        check_encoding(
+            LottoResponseMessage::new(
+                quick_pick(),
+                Response::QuickPickNumbers(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]),
+            ),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 02 03000000 02000000 04 0100 3200 00"
+                "02 08 10 0100 0200 0300 0400 10 0500 0600 0700 0800"
+            ),
+        );
+        check_encoding(
+            LottoResponseMessage::new(draw_tickets(), Response::Tickets(vec![7, 3])),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 03 02000000 0a000000"
+                "03 08 07000000 03000000"
+            ),
+        );
+        check_encoding(
+            LottoResponseMessage::new(
+                check_keno_winners(),
+                Response::KenoMatches(vec![(0, 2), (1, 0)]),
+            ),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 04 08 0500 2800"
+                "04 08 00000000 02 01000000 00"
+            ),
+        );
+        check_encoding(
+            LottoResponseMessage::new(
+                draw_and_resolve(),
+                Response::NumbersAndWinners(vec![5, 40, 8, 2], vec![AccountId::from([1u8; 32])]),
+            ),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 05 04 0100 3200 00"
+                "05 10 0500 2800 0800 0200 04 0101010101010101010101010101010101010101010101010101010101010101"
+            ),
+        );
+        check_encoding(
+            LottoResponseMessage::new(
+                check_tier_winners(),
+                Response::TierWinners(vec![vec![], vec![AccountId::from([1u8; 32])]]),
+            ),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 06 08 0500 2800 04 0700 08 0201 0200"
+                "06 08 00 04 0101010101010101010101010101010101010101010101010101010101010101"
+            ),
+        );
+        check_encoding(
+            // HttpRequestFailed in the phat contract
+            LottoResponseMessage::new(check_winners(), Response::Error(vec![10])),
+            &hex!(
+                "0100"
+                "0100 01000000 02000000 01 10 0500 2800 0800 0200 04 0700"
+                "07 04 0a"
+            ),
+        );
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        let mut request = draw_numbers();
+        assert_eq!(Ok(()), request.ensure_version());
+
+        request.version = PROTOCOL_VERSION + 1;
+        assert_eq!(
+            Err(ProtocolError::UnsupportedVersion(PROTOCOL_VERSION + 1)),
+            request.ensure_version()
+        );
+
+        // the version of the initial request is also checked in the response
+        let mut response = LottoResponseMessage::new(request, Response::Numbers(vec![5, 40, 8, 2]));
+        assert_eq!(
+            Err(ProtocolError::UnsupportedVersion(PROTOCOL_VERSION + 1)),
+            response.ensure_version()
+        );
+
+        response.request.version = PROTOCOL_VERSION;
+        assert_eq!(Ok(()), response.ensure_version());
+
+        response.version = 0;
+        assert_eq!(
+            Err(ProtocolError::UnsupportedVersion(0)),
+            response.ensure_version()
+        );
+    }
+}